@@ -0,0 +1,31 @@
+#!/usr/bin/env python
+
+# times some theo-dis runs with the default (vector) locating codes and with the bitset feature.
+# usage: bench-bitset.py ([set-type] [graph] [thresh])*
+
+import sys, subprocess, time
+
+if len(sys.argv) % 3 != 1:
+	print(f"usage: {sys.argv[0]} ([set-type] [graph] [thresh])*")
+	sys.exit(1)
+runs = [sys.argv[i:i + 3] for i in range(1, len(sys.argv), 3)]
+if not runs:
+	runs = [['old', 'tri', '1/3'], ['redic', 'tri', '2/5'], ['detld', 'tri', '2/5'], ['errld', 'tri', '2/5']]
+
+def build(features):
+	subprocess.run(['cargo', 'build', '--release', '--features', features], check = True, stderr = subprocess.DEVNULL)
+	exe = f'target/release/old-set-{features or "vec"}'
+	subprocess.run(['cp', 'target/release/old-set', exe], check = True)
+	return exe
+
+exes = { 'vec': build(''), 'bitset': build('bitset') }
+for run in runs:
+	times = {}
+	outputs = set()
+	for name, exe in exes.items():
+		start = time.time()
+		res = subprocess.run([exe, 'theo', '--strategy', 'dis', '--param', run[0], '--graph', run[1], '--thresh', run[2]], check = True, stdout = subprocess.PIPE)
+		times[name] = time.time() - start
+		outputs.add(res.stdout)
+	status = 'same output' if len(outputs) == 1 else 'OUTPUT DIFFERS'
+	print(f"{' '.join(run)}: vec {times['vec']:.2f}s, bitset {times['bitset']:.2f}s ({times['vec'] / times['bitset']:.2f}x, {status})")
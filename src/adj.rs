@@ -1,11 +1,13 @@
+use std::sync::Arc;
+
+use crate::point::{self, Point2};
+use crate::periodic::PeriodicGraph;
 use crate::util;
 
 #[cfg(test)]
-fn drain<T: AdjacentIterator>(mut iter: T) -> Vec<(isize, isize)> {
+fn drain<T: AdjacentIterator>(mut iter: T) -> Vec<T::Point> {
     let mut v = Vec::with_capacity(16);
-    while let Some(p) = iter.next() {
-        v.push(p);
-    }
+    v.extend(iter.by_ref());
     for _ in 0..10 {
         assert_eq!(iter.next(), None);
     }
@@ -13,46 +15,66 @@ fn drain<T: AdjacentIterator>(mut iter: T) -> Vec<(isize, isize)> {
     v
 }
 
-// additionally required to iterate in lexicographic sorted order and not have duplicates (see unit tests below)
-pub trait AdjacentIterator: Iterator<Item = (isize, isize)> + Clone {
-    type Open: OpenIterator;
-    type Closed: ClosedIterator;
+// additionally required to iterate in lexicographic sorted order and not have duplicates (see unit tests below).
+// the planar graphs are over (row, col) points and the cubic lattices (see cubic.rs) over (row, col, layer) points.
+// an iterator also stands for the graph it walks, so searches are handed one (any will do, like a default one for the built-in graphs)
+// and make the rest from it - that's how the periodic graphs, which are only known at runtime, get theirs to every search.
+pub trait AdjacentIterator: Iterator<Item = <Self as AdjacentIterator>::Point> + Clone + Send + Sync {
+    type Point: point::Point;
+    type Open: OpenIterator<Point = Self::Point>;
+    type Closed: ClosedIterator<Point = Self::Point>;
 
-    fn class(row: isize, col: isize) -> usize;
-    fn with_class(row: isize, col: isize, class: usize) -> Self;
+    fn class(&self, pos: Self::Point) -> usize;
+    fn with_class(&self, pos: Self::Point, class: usize) -> Self;
 
-    fn new(row: isize, col: isize) -> Self {
-        Self::with_class(row, col, Self::class(row, col))
+    fn at(&self, pos: Self::Point) -> Self {
+        self.with_class(pos, self.class(pos))
     }
-    fn at(pos: (isize, isize)) -> Self {
-        Self::new(pos.0, pos.1)
+
+    // a representative point of each class (in class order)
+    fn classes(&self) -> &[Self::Point];
+    // the furthest any neighbor can be in any coordinate - searches size their padding/bounds by this.
+    // everything built in is within a king's move, but powers and loaded graphs can go further.
+    fn reach(&self) -> isize {
+        1
     }
 
-    const CLASSES: &'static [(isize, isize)];
+    // the open and closed neighborhoods on the same graph
+    fn open(&self) -> Self::Open;
+    fn closed(&self) -> Self::Closed;
 }
 
 // some logic might require open/closed so have a special tag for them to use for trait bounds
 pub trait OpenIterator: AdjacentIterator {}
 pub trait ClosedIterator: AdjacentIterator {}
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct ClosedKing {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for ClosedKing {
+    type Point = Point2;
     type Open = OpenKing;
     type Closed = Self;
 
-    fn class(_: isize, _: isize) -> usize {
+    fn class(&self, _: Point2) -> usize {
         0
     }
-    fn with_class(row: isize, col: isize, _: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, _: usize) -> Self {
         Self { row, col, state: 0 }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for ClosedKing {
     type Item = (isize, isize);
@@ -76,28 +98,37 @@ impl Iterator for ClosedKing {
 impl ClosedIterator for ClosedKing {}
 #[test]
 fn test_closed_king() {
-    assert_eq!(drain(ClosedKing::new(0, 0)), &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 0), (0, 1), (1, -1), (1, 0), (1, 1)]);
-    assert_eq!(drain(ClosedKing::new(6, -11)), &[(5, -12), (5, -11), (5, -10), (6, -12), (6, -11), (6, -10), (7, -12), (7, -11), (7, -10)]);
+    assert_eq!(drain(ClosedKing::default().at((0, 0))), &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 0), (0, 1), (1, -1), (1, 0), (1, 1)]);
+    assert_eq!(drain(ClosedKing::default().at((6, -11))), &[(5, -12), (5, -11), (5, -10), (6, -12), (6, -11), (6, -10), (7, -12), (7, -11), (7, -10)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct OpenKing {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for OpenKing {
+    type Point = Point2;
     type Open = Self;
     type Closed = ClosedKing;
 
-    fn class(_: isize, _: isize) -> usize {
+    fn class(&self, _: Point2) -> usize {
         0
     }
-    fn with_class(row: isize, col: isize, _: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, _: usize) -> Self {
         Self { row, col, state: 0 }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for OpenKing {
     type Item = (isize, isize);
@@ -120,28 +151,37 @@ impl Iterator for OpenKing {
 impl OpenIterator for OpenKing {}
 #[test]
 fn test_open_king() {
-    assert_eq!(drain(OpenKing::new(0, 0)), &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)]);
-    assert_eq!(drain(OpenKing::new(6, -11)), &[(5, -12), (5, -11), (5, -10), (6, -12), (6, -10), (7, -12), (7, -11), (7, -10)]);
+    assert_eq!(drain(OpenKing::default().at((0, 0))), &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)]);
+    assert_eq!(drain(OpenKing::default().at((6, -11))), &[(5, -12), (5, -11), (5, -10), (6, -12), (6, -10), (7, -12), (7, -11), (7, -10)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct ClosedGrid {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for ClosedGrid {
+    type Point = Point2;
     type Open = OpenGrid;
     type Closed = Self;
 
-    fn class(_: isize, _: isize) -> usize {
+    fn class(&self, _: Point2) -> usize {
         0
     }
-    fn with_class(row: isize, col: isize, _: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, _: usize) -> Self {
         Self { row, col, state: 0 }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for ClosedGrid {
     type Item = (isize, isize);
@@ -164,28 +204,37 @@ impl Iterator for ClosedGrid {
 impl ClosedIterator for ClosedGrid {}
 #[test]
 fn test_closed_grid() {
-    assert_eq!(drain(ClosedGrid::new(0, 0)), &[(-1, 0), (0, -1), (0, 0), (0, 1), (1, 0)]);
-    assert_eq!(drain(ClosedGrid::new(6, -11)), &[(5, -11), (6, -12), (6, -11), (6, -10), (7, -11)]);
+    assert_eq!(drain(ClosedGrid::default().at((0, 0))), &[(-1, 0), (0, -1), (0, 0), (0, 1), (1, 0)]);
+    assert_eq!(drain(ClosedGrid::default().at((6, -11))), &[(5, -11), (6, -12), (6, -11), (6, -10), (7, -11)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct OpenGrid {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for OpenGrid {
+    type Point = Point2;
     type Open = Self;
     type Closed = ClosedGrid;
 
-    fn class(_: isize, _: isize) -> usize {
+    fn class(&self, _: Point2) -> usize {
         0
     }
-    fn with_class(row: isize, col: isize, _: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, _: usize) -> Self {
         Self { row, col, state: 0 }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for OpenGrid {
     type Item = (isize, isize);
@@ -207,28 +256,37 @@ impl Iterator for OpenGrid {
 impl OpenIterator for OpenGrid {}
 #[test]
 fn test_open_grid() {
-    assert_eq!(drain(OpenGrid::new(0, 0)), &[(-1, 0), (0, -1), (0, 1), (1, 0)]);
-    assert_eq!(drain(OpenGrid::new(6, -11)), &[(5, -11), (6, -12), (6, -10), (7, -11)]);
+    assert_eq!(drain(OpenGrid::default().at((0, 0))), &[(-1, 0), (0, -1), (0, 1), (1, 0)]);
+    assert_eq!(drain(OpenGrid::default().at((6, -11))), &[(5, -11), (6, -12), (6, -10), (7, -11)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct ClosedTri {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for ClosedTri {
+    type Point = Point2;
     type Open = OpenTri;
     type Closed = Self;
 
-    fn class(_: isize, _: isize) -> usize {
+    fn class(&self, _: Point2) -> usize {
         0
     }
-    fn with_class(row: isize, col: isize, _: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, _: usize) -> Self {
         Self { row, col, state: 0 }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for ClosedTri {
     type Item = (isize, isize);
@@ -253,28 +311,37 @@ impl Iterator for ClosedTri {
 impl ClosedIterator for ClosedTri {}
 #[test]
 fn test_closed_tri() {
-    assert_eq!(drain(ClosedTri::new(0, 0)), &[(-1, -1), (-1, 0), (0, -1), (0, 0), (0, 1), (1, 0), (1, 1)]);
-    assert_eq!(drain(ClosedTri::new(6, -11)), &[(5, -12), (5, -11), (6, -12), (6, -11), (6, -10), (7, -11), (7, -10)]);
+    assert_eq!(drain(ClosedTri::default().at((0, 0))), &[(-1, -1), (-1, 0), (0, -1), (0, 0), (0, 1), (1, 0), (1, 1)]);
+    assert_eq!(drain(ClosedTri::default().at((6, -11))), &[(5, -12), (5, -11), (6, -12), (6, -11), (6, -10), (7, -11), (7, -10)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct OpenTri {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for OpenTri {
+    type Point = Point2;
     type Open = Self;
     type Closed = ClosedTri;
 
-    fn class(_: isize, _: isize) -> usize {
+    fn class(&self, _: Point2) -> usize {
         0
     }
-    fn with_class(row: isize, col: isize, _: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, _: usize) -> Self {
         Self { row, col, state: 0 }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for OpenTri {
     type Item = (isize, isize);
@@ -298,31 +365,40 @@ impl Iterator for OpenTri {
 impl OpenIterator for OpenTri {}
 #[test]
 fn test_open_tri() {
-    assert_eq!(drain(OpenTri::new(0, 0)), &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]);
-    assert_eq!(drain(OpenTri::new(6, -11)), &[(5, -12), (5, -11), (6, -12), (6, -10), (7, -11), (7, -10)]);
+    assert_eq!(drain(OpenTri::default().at((0, 0))), &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]);
+    assert_eq!(drain(OpenTri::default().at((6, -11))), &[(5, -12), (5, -11), (6, -12), (6, -10), (7, -11), (7, -10)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct ClosedHex {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for ClosedHex {
+    type Point = Point2;
     type Open = OpenHex;
     type Closed = Self;
 
-    fn class(row: isize, col: isize) -> usize {
+    fn class(&self, (row, col): Point2) -> usize {
         if (row + col) % 2 == 0 { 0 } else { 1 }
     }
-    fn with_class(row: isize, col: isize, class: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, class: usize) -> Self {
         Self {
             row, col,
             state: [0, 5][class],
         }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0), (0, 1)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0), (0, 1)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for ClosedHex {
     type Item = (isize, isize);
@@ -342,37 +418,46 @@ impl Iterator for ClosedHex {
 impl ClosedIterator for ClosedHex {}
 #[test]
 fn test_closed_hex() {
-    assert_eq!(drain(ClosedHex::new(0, 0)), &[(-1, 0), (0, -1), (0, 0), (0, 1)]);
-    assert_eq!(drain(ClosedHex::new(1, 0)), &[(1, -1), (1, 0), (1, 1), (2, 0)]);
-    assert_eq!(drain(ClosedHex::new(2, 0)), &[(1, 0), (2, -1), (2, 0), (2, 1)]);
-    assert_eq!(drain(ClosedHex::new(-3, 0)), &[(-3, -1), (-3, 0), (-3, 1), (-2, 0)]);
-    assert_eq!(drain(ClosedHex::new(-10, 0)), &[(-11, 0), (-10, -1), (-10, 0), (-10, 1)]);
-    assert_eq!(drain(ClosedHex::new(0, 1)), &[(0, 0), (0, 1), (0, 2), (1, 1)]);
-    assert_eq!(drain(ClosedHex::new(1, 1)), &[(0, 1), (1, 0), (1, 1), (1, 2)]);
-    assert_eq!(drain(ClosedHex::new(-3, -1)), &[(-4, -1), (-3, -2), (-3, -1), (-3, 0)]);
+    assert_eq!(drain(ClosedHex::default().at((0, 0))), &[(-1, 0), (0, -1), (0, 0), (0, 1)]);
+    assert_eq!(drain(ClosedHex::default().at((1, 0))), &[(1, -1), (1, 0), (1, 1), (2, 0)]);
+    assert_eq!(drain(ClosedHex::default().at((2, 0))), &[(1, 0), (2, -1), (2, 0), (2, 1)]);
+    assert_eq!(drain(ClosedHex::default().at((-3, 0))), &[(-3, -1), (-3, 0), (-3, 1), (-2, 0)]);
+    assert_eq!(drain(ClosedHex::default().at((-10, 0))), &[(-11, 0), (-10, -1), (-10, 0), (-10, 1)]);
+    assert_eq!(drain(ClosedHex::default().at((0, 1))), &[(0, 0), (0, 1), (0, 2), (1, 1)]);
+    assert_eq!(drain(ClosedHex::default().at((1, 1))), &[(0, 1), (1, 0), (1, 1), (1, 2)]);
+    assert_eq!(drain(ClosedHex::default().at((-3, -1))), &[(-4, -1), (-3, -2), (-3, -1), (-3, 0)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct OpenHex {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for OpenHex {
+    type Point = Point2;
     type Open = Self;
     type Closed = ClosedHex;
 
-    fn class(row: isize, col: isize) -> usize {
+    fn class(&self, (row, col): Point2) -> usize {
         if (row + col) % 2 == 0 { 0 } else { 1 }
     }
-    fn with_class(row: isize, col: isize, class: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, class: usize) -> Self {
         Self {
             row, col,
             state: [0, 4][class], 
         }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0), (0, 1)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0), (0, 1)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for OpenHex {
     type Item = (isize, isize);
@@ -391,37 +476,46 @@ impl Iterator for OpenHex {
 impl OpenIterator for OpenHex {}
 #[test]
 fn test_open_hex() {
-    assert_eq!(drain(OpenHex::new(0, 0)), &[(-1, 0), (0, -1), (0, 1)]);
-    assert_eq!(drain(OpenHex::new(1, 0)), &[(1, -1), (1, 1), (2, 0)]);
-    assert_eq!(drain(OpenHex::new(2, 0)), &[(1, 0), (2, -1), (2, 1)]);
-    assert_eq!(drain(OpenHex::new(-3, 0)), &[(-3, -1), (-3, 1), (-2, 0)]);
-    assert_eq!(drain(OpenHex::new(-10, 0)), &[(-11, 0), (-10, -1), (-10, 1)]);
-    assert_eq!(drain(OpenHex::new(0, 1)), &[(0, 0), (0, 2), (1, 1)]);
-    assert_eq!(drain(OpenHex::new(1, 1)), &[(0, 1), (1, 0), (1, 2)]);
-    assert_eq!(drain(OpenHex::new(-3, -1)), &[(-4, -1), (-3, -2), (-3, 0)]);
+    assert_eq!(drain(OpenHex::default().at((0, 0))), &[(-1, 0), (0, -1), (0, 1)]);
+    assert_eq!(drain(OpenHex::default().at((1, 0))), &[(1, -1), (1, 1), (2, 0)]);
+    assert_eq!(drain(OpenHex::default().at((2, 0))), &[(1, 0), (2, -1), (2, 1)]);
+    assert_eq!(drain(OpenHex::default().at((-3, 0))), &[(-3, -1), (-3, 1), (-2, 0)]);
+    assert_eq!(drain(OpenHex::default().at((-10, 0))), &[(-11, 0), (-10, -1), (-10, 1)]);
+    assert_eq!(drain(OpenHex::default().at((0, 1))), &[(0, 0), (0, 2), (1, 1)]);
+    assert_eq!(drain(OpenHex::default().at((1, 1))), &[(0, 1), (1, 0), (1, 2)]);
+    assert_eq!(drain(OpenHex::default().at((-3, -1))), &[(-4, -1), (-3, -2), (-3, 0)]);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct ClosedTMB {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for ClosedTMB {
+    type Point = Point2;
     type Open = OpenTMB;
     type Closed = Self;
 
-    fn class(row: isize, col: isize) -> usize {
+    fn class(&self, (row, col): Point2) -> usize {
         util::modulus(row + col, 3)
     }
-    fn with_class(row: isize, col: isize, class: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, class: usize) -> Self {
         Self {
             row, col,
             state: [0, 8, 13][class],
         }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0), (0, 1), (0, 2)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0), (0, 1), (0, 2)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for ClosedTMB {
     type Item = (isize, isize);
@@ -443,39 +537,48 @@ impl Iterator for ClosedTMB {
 impl ClosedIterator for ClosedTMB {}
 #[test]
 fn test_closed_tmb() {
-    assert_eq!(drain(ClosedTMB::new(0, 0)), &[(-1, -1), (-1, 0), (0, -1), (0, 0), (0, 1), (1, 0), (1, 1)]);
-    assert_eq!(drain(ClosedTMB::new(0, 1)), &[(-1, 1), (0, 0), (0, 1), (1, 2)]);
-    assert_eq!(drain(ClosedTMB::new(0, 2)), &[(-1, 1), (0, 2), (0, 3), (1, 2)]);
-    assert_eq!(drain(ClosedTMB::new(-1, -2)), &[(-2, -3), (-2, -2), (-1, -3), (-1, -2), (-1, -1), (0, -2), (0, -1)]);
-    assert_eq!(drain(ClosedTMB::new(1, 3)), &[(0, 3), (1, 2), (1, 3), (2, 4)]);
-    assert_eq!(drain(ClosedTMB::new(3, 2)), &[(2, 1), (3, 2), (3, 3), (4, 2)]);
-    assert_eq!(drain(ClosedTMB::new(-2, -2)), &[(-3, -3), (-2, -2), (-2, -1), (-1, -2)]);
-    assert_eq!(drain(ClosedTMB::new(-2, -3)), &[(-3, -3), (-2, -4), (-2, -3), (-1, -2)]);
-    assert_eq!(drain(ClosedTMB::new(-2, 2)), &[(-3, 1), (-3, 2), (-2, 1), (-2, 2), (-2, 3), (-1, 2), (-1, 3)]);
-    assert_eq!(drain(ClosedTMB::new(1, -2)), &[(0, -3), (1, -2), (1, -1), (2, -2)]);
-}
-
-#[derive(Clone, Copy)]
+    assert_eq!(drain(ClosedTMB::default().at((0, 0))), &[(-1, -1), (-1, 0), (0, -1), (0, 0), (0, 1), (1, 0), (1, 1)]);
+    assert_eq!(drain(ClosedTMB::default().at((0, 1))), &[(-1, 1), (0, 0), (0, 1), (1, 2)]);
+    assert_eq!(drain(ClosedTMB::default().at((0, 2))), &[(-1, 1), (0, 2), (0, 3), (1, 2)]);
+    assert_eq!(drain(ClosedTMB::default().at((-1, -2))), &[(-2, -3), (-2, -2), (-1, -3), (-1, -2), (-1, -1), (0, -2), (0, -1)]);
+    assert_eq!(drain(ClosedTMB::default().at((1, 3))), &[(0, 3), (1, 2), (1, 3), (2, 4)]);
+    assert_eq!(drain(ClosedTMB::default().at((3, 2))), &[(2, 1), (3, 2), (3, 3), (4, 2)]);
+    assert_eq!(drain(ClosedTMB::default().at((-2, -2))), &[(-3, -3), (-2, -2), (-2, -1), (-1, -2)]);
+    assert_eq!(drain(ClosedTMB::default().at((-2, -3))), &[(-3, -3), (-2, -4), (-2, -3), (-1, -2)]);
+    assert_eq!(drain(ClosedTMB::default().at((-2, 2))), &[(-3, 1), (-3, 2), (-2, 1), (-2, 2), (-2, 3), (-1, 2), (-1, 3)]);
+    assert_eq!(drain(ClosedTMB::default().at((1, -2))), &[(0, -3), (1, -2), (1, -1), (2, -2)]);
+}
+
+#[derive(Clone, Copy, Default)]
 pub struct OpenTMB {
     row: isize,
     col: isize,
     state: usize,
 }
 impl AdjacentIterator for OpenTMB {
+    type Point = Point2;
     type Open = Self;
     type Closed = ClosedTMB;
 
-    fn class(row: isize, col: isize) -> usize {
+    fn class(&self, (row, col): Point2) -> usize {
         util::modulus(row + col, 3)
     }
-    fn with_class(row: isize, col: isize, class: usize) -> Self {
+    fn with_class(&self, (row, col): Point2, class: usize) -> Self {
         Self {
             row, col,
             state: [0, 7, 11][class],
         }
     }
 
-    const CLASSES: &'static [(isize, isize)] = &[(0, 0), (0, 1), (0, 2)];
+    fn classes(&self) -> &[Point2] {
+        &[(0, 0), (0, 1), (0, 2)]
+    }
+    fn open(&self) -> Self::Open {
+        Default::default()
+    }
+    fn closed(&self) -> Self::Closed {
+        Default::default()
+    }
 }
 impl Iterator for OpenTMB {
     type Item = (isize, isize);
@@ -496,14 +599,474 @@ impl Iterator for OpenTMB {
 impl OpenIterator for OpenTMB {}
 #[test]
 fn test_open_tmb() {
-    assert_eq!(drain(OpenTMB::new(0, 0)), &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]);
-    assert_eq!(drain(OpenTMB::new(0, 1)), &[(-1, 1), (0, 0), (1, 2)]);
-    assert_eq!(drain(OpenTMB::new(0, 2)), &[(-1, 1), (0, 3), (1, 2)]);
-    assert_eq!(drain(OpenTMB::new(-1, -2)), &[(-2, -3), (-2, -2), (-1, -3), (-1, -1), (0, -2), (0, -1)]);
-    assert_eq!(drain(OpenTMB::new(1, 3)), &[(0, 3), (1, 2), (2, 4)]);
-    assert_eq!(drain(OpenTMB::new(3, 2)), &[(2, 1), (3, 3), (4, 2)]);
-    assert_eq!(drain(OpenTMB::new(-2, -2)), &[(-3, -3), (-2, -1), (-1, -2)]);
-    assert_eq!(drain(OpenTMB::new(-2, -3)), &[(-3, -3), (-2, -4), (-1, -2)]);
-    assert_eq!(drain(OpenTMB::new(-2, 2)), &[(-3, 1), (-3, 2), (-2, 1), (-2, 3), (-1, 2), (-1, 3)]);
-    assert_eq!(drain(OpenTMB::new(1, -2)), &[(0, -3), (1, -1), (2, -2)]);
+    assert_eq!(drain(OpenTMB::default().at((0, 0))), &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]);
+    assert_eq!(drain(OpenTMB::default().at((0, 1))), &[(-1, 1), (0, 0), (1, 2)]);
+    assert_eq!(drain(OpenTMB::default().at((0, 2))), &[(-1, 1), (0, 3), (1, 2)]);
+    assert_eq!(drain(OpenTMB::default().at((-1, -2))), &[(-2, -3), (-2, -2), (-1, -3), (-1, -1), (0, -2), (0, -1)]);
+    assert_eq!(drain(OpenTMB::default().at((1, 3))), &[(0, 3), (1, 2), (2, 4)]);
+    assert_eq!(drain(OpenTMB::default().at((3, 2))), &[(2, 1), (3, 3), (4, 2)]);
+    assert_eq!(drain(OpenTMB::default().at((-2, -2))), &[(-3, -3), (-2, -1), (-1, -2)]);
+    assert_eq!(drain(OpenTMB::default().at((-2, -3))), &[(-3, -3), (-2, -4), (-1, -2)]);
+    assert_eq!(drain(OpenTMB::default().at((-2, 2))), &[(-3, 1), (-3, 2), (-2, 1), (-2, 3), (-1, 2), (-1, 3)]);
+    assert_eq!(drain(OpenTMB::default().at((1, -2))), &[(0, -3), (1, -1), (2, -2)]);
+}
+
+// the archimedean lattices are periodic, so rather than writing out a state machine for each we put every point of a rows x cols block
+// in its own class and read the neighbors of each class from a table of offsets (which must be sorted and exclude the center).
+// the blocks are laid out so that every neighbor is within a king's move, so they all have the default reach.
+macro_rules! periodic_lattice {
+    ($open:ident, $closed:ident, $rows:expr, $cols:expr, $offsets:expr, $classes:expr) => {
+        #[derive(Clone, Copy)]
+        pub struct $closed {
+            row: isize,
+            col: isize,
+            offsets: &'static [(isize, isize)],
+            state: usize,
+            center: bool,
+        }
+        impl $closed {
+            const OFFSETS: &'static [&'static [(isize, isize)]] = $offsets;
+        }
+        impl Default for $closed {
+            fn default() -> Self {
+                Self { row: 0, col: 0, offsets: Self::OFFSETS[0], state: 0, center: false }
+            }
+        }
+        impl AdjacentIterator for $closed {
+            type Point = Point2;
+            type Open = $open;
+            type Closed = Self;
+
+            fn class(&self, (row, col): Point2) -> usize {
+                util::modulus(row, $rows) * $cols + util::modulus(col, $cols)
+            }
+            fn with_class(&self, (row, col): Point2, class: usize) -> Self {
+                Self { row, col, offsets: Self::OFFSETS[class], state: 0, center: false }
+            }
+
+            fn classes(&self) -> &[Point2] {
+                $classes
+            }
+            fn open(&self) -> Self::Open {
+                Default::default()
+            }
+            fn closed(&self) -> Self::Closed {
+                Default::default()
+            }
+        }
+        impl Iterator for $closed {
+            type Item = (isize, isize);
+            fn next(&mut self) -> Option<Self::Item> {
+                match self.offsets.get(self.state) {
+                    Some(&d) if self.center || d < (0, 0) => {
+                        self.state += 1;
+                        Some((self.row + d.0, self.col + d.1))
+                    }
+                    _ if !self.center => {
+                        self.center = true;
+                        Some((self.row, self.col))
+                    }
+                    _ => None,
+                }
+            }
+        }
+        impl ClosedIterator for $closed {}
+
+        #[derive(Clone, Copy)]
+        pub struct $open {
+            row: isize,
+            col: isize,
+            offsets: &'static [(isize, isize)],
+            state: usize,
+        }
+        impl Default for $open {
+            fn default() -> Self {
+                Self { row: 0, col: 0, offsets: $closed::OFFSETS[0], state: 0 }
+            }
+        }
+        impl AdjacentIterator for $open {
+            type Point = Point2;
+            type Open = Self;
+            type Closed = $closed;
+
+            fn class(&self, (row, col): Point2) -> usize {
+                util::modulus(row, $rows) * $cols + util::modulus(col, $cols)
+            }
+            fn with_class(&self, (row, col): Point2, class: usize) -> Self {
+                Self { row, col, offsets: $closed::OFFSETS[class], state: 0 }
+            }
+
+            fn classes(&self) -> &[Point2] {
+                $classes
+            }
+            fn open(&self) -> Self::Open {
+                Default::default()
+            }
+            fn closed(&self) -> Self::Closed {
+                Default::default()
+            }
+        }
+        impl Iterator for $open {
+            type Item = (isize, isize);
+            fn next(&mut self) -> Option<Self::Item> {
+                let d = self.offsets.get(self.state)?;
+                self.state += 1;
+                Some((self.row + d.0, self.col + d.1))
+            }
+        }
+        impl OpenIterator for $open {}
+    };
+}
+
+// snub square (3.3.4.3.4) - the square grid with every other cell cut by a diagonal, the direction alternating by row
+periodic_lattice!(OpenSNS, ClosedSNS, 2, 2, &[
+    &[(-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)],
+    &[(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0)],
+    &[(-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)],
+    &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0)],
+], &[(0, 0), (0, 1), (1, 0), (1, 1)]);
+
+// trihexagonal, aka kagome (3.6.3.6)
+periodic_lattice!(OpenKAG, ClosedKAG, 3, 2, &[
+    &[(-1, 0), (0, -1), (0, 1), (1, 1)],
+    &[(-1, 1), (0, -1), (0, 1), (1, 0)],
+    &[(0, 1), (1, -1), (1, 0), (1, 1)],
+    &[(-1, -1), (-1, 0), (0, -1), (1, 0)],
+    &[(-1, 0), (0, -1), (1, -1), (1, 0)],
+    &[(-1, -1), (-1, 0), (-1, 1), (0, 1)],
+], &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]);
+
+// rhombitrihexagonal (3.4.6.4)
+periodic_lattice!(OpenRTH, ClosedRTH, 2, 3, &[
+    &[(0, -1), (0, 1), (1, 0), (1, 1)],
+    &[(-1, -1), (0, -1), (0, 1), (1, 1)],
+    &[(-1, -1), (0, -1), (0, 1), (1, 0)],
+    &[(-1, 0), (0, -1), (0, 1), (1, 1)],
+    &[(-1, -1), (0, -1), (0, 1), (1, 1)],
+    &[(-1, -1), (-1, 0), (0, -1), (0, 1)],
+], &[(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+
+// truncated hexagonal (3.12.12)
+periodic_lattice!(OpenTRH, ClosedTRH, 2, 3, &[
+    &[(0, 1), (1, -1), (1, 0)],
+    &[(-1, 0), (0, -1), (0, 1)],
+    &[(-1, -1), (0, -1), (1, 0)],
+    &[(-1, 0), (0, -1), (0, 1)],
+    &[(0, -1), (1, 0), (1, 1)],
+    &[(-1, 0), (-1, 1), (0, 1)],
+], &[(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+
+// elongated triangular (3.3.3.4.4) - square rows joined by alternating triangle rows
+periodic_lattice!(OpenETRI, ClosedETRI, 2, 1, &[
+    &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0)],
+    &[(-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)],
+], &[(0, 0), (1, 0)]);
+
+// truncated square (4.8.8)
+periodic_lattice!(OpenTSQ, ClosedTSQ, 2, 2, &[
+    &[(0, -1), (1, 0), (1, 1)],
+    &[(-1, -1), (0, 1), (1, 0)],
+    &[(-1, 0), (0, -1), (1, 1)],
+    &[(-1, -1), (-1, 0), (0, 1)],
+], &[(0, 0), (0, 1), (1, 0), (1, 1)]);
+
+// truncated trihexagonal (4.6.12)
+periodic_lattice!(OpenTTRH, ClosedTTRH, 3, 4, &[
+    &[(-1, 0), (0, -1), (0, 1)],
+    &[(0, -1), (0, 1), (1, 1)],
+    &[(-1, -1), (0, -1), (1, 1)],
+    &[(-1, -1), (-1, 0), (0, 1)],
+    &[(0, 1), (1, 0), (1, 1)],
+    &[(0, -1), (0, 1), (1, 1)],
+    &[(-1, -1), (0, -1), (0, 1)],
+    &[(-1, -1), (0, -1), (1, 0)],
+    &[(-1, 0), (0, -1), (1, 0)],
+    &[(-1, -1), (0, 1), (1, 1)],
+    &[(-1, -1), (0, -1), (1, 1)],
+    &[(-1, 0), (0, 1), (1, 0)],
+], &[(0, 0), (0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (1, 2), (1, 3), (2, 0), (2, 1), (2, 2), (2, 3)]);
+
+// snub hexagonal (3.3.3.3.6)
+periodic_lattice!(OpenSNH, ClosedSNH, 3, 4, &[
+    &[(-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)],
+    &[(-1, -1), (-1, 0), (0, -1), (1, -1), (1, 0)],
+    &[(-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 0)],
+    &[(-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)],
+    &[(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0)],
+    &[(-1, 0), (0, -1), (1, -1), (1, 0), (1, 1)],
+    &[(-1, 0), (-1, 1), (0, 1), (1, 0), (1, 1)],
+    &[(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0)],
+    &[(-1, 0), (-1, 1), (0, 1), (1, 0), (1, 1)],
+    &[(-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)],
+    &[(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0)],
+    &[(-1, -1), (-1, 0), (0, -1), (1, -1), (1, 0)],
+], &[(0, 0), (0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (1, 2), (1, 3), (2, 0), (2, 1), (2, 2), (2, 3)]);
+
+// whatever periodic graph was loaded from a file or built as a power of another (see periodic.rs).
+// same walk as the archimedean lattices above, but the table is only known at runtime so every iterator shares a handle to it.
+#[derive(Clone)]
+pub struct ClosedPeriodic {
+    graph: Arc<PeriodicGraph>,
+    row: isize,
+    col: isize,
+    class: usize,
+    state: usize,
+    center: bool,
+}
+impl ClosedPeriodic {
+    pub fn new(graph: Arc<PeriodicGraph>) -> Self {
+        Self { graph, row: 0, col: 0, class: 0, state: 0, center: false }
+    }
+}
+impl AdjacentIterator for ClosedPeriodic {
+    type Point = Point2;
+    type Open = OpenPeriodic;
+    type Closed = Self;
+
+    fn class(&self, (row, col): Point2) -> usize {
+        self.graph.class(row, col)
+    }
+    fn with_class(&self, (row, col): Point2, class: usize) -> Self {
+        Self { graph: self.graph.clone(), row, col, class, state: 0, center: false }
+    }
+
+    fn classes(&self) -> &[Point2] {
+        self.graph.classes()
+    }
+    fn reach(&self) -> isize {
+        self.graph.reach()
+    }
+    fn open(&self) -> Self::Open {
+        OpenPeriodic::new(self.graph.clone())
+    }
+    fn closed(&self) -> Self::Closed {
+        Self::new(self.graph.clone())
+    }
+}
+impl Iterator for ClosedPeriodic {
+    type Item = (isize, isize);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.graph.adjacent(self.class).get(self.state) {
+            Some(&d) if self.center || d < (0, 0) => {
+                self.state += 1;
+                Some((self.row + d.0, self.col + d.1))
+            }
+            _ if !self.center => {
+                self.center = true;
+                Some((self.row, self.col))
+            }
+            _ => None,
+        }
+    }
+}
+impl ClosedIterator for ClosedPeriodic {}
+
+#[derive(Clone)]
+pub struct OpenPeriodic {
+    graph: Arc<PeriodicGraph>,
+    row: isize,
+    col: isize,
+    class: usize,
+    state: usize,
+}
+impl OpenPeriodic {
+    pub fn new(graph: Arc<PeriodicGraph>) -> Self {
+        Self { graph, row: 0, col: 0, class: 0, state: 0 }
+    }
+}
+impl AdjacentIterator for OpenPeriodic {
+    type Point = Point2;
+    type Open = Self;
+    type Closed = ClosedPeriodic;
+
+    fn class(&self, (row, col): Point2) -> usize {
+        self.graph.class(row, col)
+    }
+    fn with_class(&self, (row, col): Point2, class: usize) -> Self {
+        Self { graph: self.graph.clone(), row, col, class, state: 0 }
+    }
+
+    fn classes(&self) -> &[Point2] {
+        self.graph.classes()
+    }
+    fn reach(&self) -> isize {
+        self.graph.reach()
+    }
+    fn open(&self) -> Self::Open {
+        Self::new(self.graph.clone())
+    }
+    fn closed(&self) -> Self::Closed {
+        ClosedPeriodic::new(self.graph.clone())
+    }
+}
+impl Iterator for OpenPeriodic {
+    type Item = (isize, isize);
+    fn next(&mut self) -> Option<Self::Item> {
+        let d = self.graph.adjacent(self.class).get(self.state)?;
+        self.state += 1;
+        Some((self.row + d.0, self.col + d.1))
+    }
+}
+impl OpenIterator for OpenPeriodic {}
+
+// the vertex configuration at a point, like "3.3.4.3.4" - the faces are the chordless cycles through it (up to 12 long) that don't enclose anything.
+// a cycle encloses something if some point next to it can't get away without crossing it (a 12-cycle through p stays within 6 king's moves of it).
+// faces that share an edge out of p are next to each other around it, which puts them in cyclic order without needing an embedding.
+#[cfg(test)]
+fn faces_at<Open: OpenIterator<Point = Point2> + Default>(p: Point2) -> String {
+    use std::collections::{BTreeMap, BTreeSet};
+    let adjacent = |p: (isize, isize)| drain(Open::default().at(p));
+    let dist = |q: (isize, isize)| (q.0 - p.0).abs().max((q.1 - p.1).abs()) as usize;
+    let encloses = |cycle: &[(isize, isize)]| {
+        cycle.iter().flat_map(|&q| adjacent(q)).filter(|q| !cycle.contains(q)).any(|start| {
+            let mut seen: BTreeSet<_> = Some(start).into_iter().collect();
+            let mut stack = vec![start];
+            while let Some(q) = stack.pop() {
+                if dist(q) > 7 {
+                    return false;
+                }
+                for x in adjacent(q) {
+                    if !cycle.contains(&x) && seen.insert(x) {
+                        stack.push(x);
+                    }
+                }
+            }
+            true
+        })
+    };
+
+    // grow chordless paths out of p: each new point can only be adjacent to the last one (or to p, if it closes the cycle).
+    // every step is a king's move, so paths that couldn't make it back to p in time are dropped
+    let mut cycles: BTreeMap<Vec<Point2>, Option<Vec<Point2>>> = Default::default();
+    let mut stack: Vec<Vec<(isize, isize)>> = vec![vec![p]];
+    while let Some(path) = stack.pop() {
+        let last = *path.last().unwrap();
+        for q in adjacent(last) {
+            if path.contains(&q) {
+                continue;
+            }
+            let touches = |i: usize| adjacent(q).contains(&path[i]);
+            if (1..path.len() - 1).any(touches) {
+                continue;
+            }
+            let mut next = path.clone();
+            next.push(q);
+            if path.len() >= 2 && touches(0) {
+                // each cycle comes up once going each way around
+                let mut sorted = next.clone();
+                sorted.sort_unstable();
+                cycles.entry(sorted).or_insert_with(|| if encloses(&next) { None } else { Some(next) });
+            }
+            else if next.len() + dist(q) <= 13 {
+                stack.push(next);
+            }
+        }
+    }
+    // each face as (size, the two neighbors of p on it), then walk around p from one to the next
+    let faces: Vec<_> = cycles.into_values().flatten().map(|c| (c.len(), c[1], *c.last().unwrap())).collect();
+    let mut sizes = vec![faces[0].0];
+    let (mut face, mut edge) = (0, faces[0].2);
+    loop {
+        let next: Vec<_> = (0..faces.len()).filter(|&i| i != face && (faces[i].1 == edge || faces[i].2 == edge)).collect();
+        assert_eq!(next.len(), 1, "{:?} -> {:?} is not on exactly two faces", p, edge);
+        face = next[0];
+        if face == 0 {
+            break;
+        }
+        edge = if faces[face].1 == edge { faces[face].2 } else { faces[face].1 };
+        sizes.push(faces[face].0);
+    }
+    assert_eq!(sizes.len(), faces.len(), "the faces around {:?} don't form a single cycle", p);
+
+    // the usual name is the smallest rotation either way around
+    let mut rev = sizes.clone();
+    rev.reverse();
+    let config = (0..sizes.len()).flat_map(|i| {
+        let rot = |v: &[usize]| v[i..].iter().chain(&v[..i]).copied().collect::<Vec<_>>();
+        vec![rot(&sizes), rot(&rev)]
+    }).min().unwrap();
+    config.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(".")
+}
+
+// checks that a lattice is a consistent graph with the given vertex configuration, which we can only see so much of from here:
+// every point has the right degree and is in the right number of triangles (and all its neighbors are within a king's move),
+// and every class of point has the given vertex configuration, like "3.3.4.3.4"
+#[cfg(test)]
+fn check_lattice<Open: OpenIterator<Point = Point2> + Default, Closed: ClosedIterator<Point = Point2> + Default>(degree: usize, triangles: usize, config: &str) {
+    let adjacent = |p: (isize, isize)| drain(Open::default().at(p));
+    for (class, &c) in Open::default().classes().iter().enumerate() {
+        assert_eq!(Open::default().class(c), class);
+        assert_eq!(Closed::default().class(c), class);
+        assert_eq!(faces_at::<Open>(c), config, "{:?}", c);
+    }
+    for r in -7..7 {
+        for c in -7..7 {
+            let open = adjacent((r, c));
+            let mut closed = open.clone();
+            closed.push((r, c));
+            closed.sort();
+            assert_eq!(drain(Closed::default().at((r, c))), closed);
+            assert_eq!(open.len(), degree);
+            assert!(open.iter().all(|&q| drain(ClosedKing::default().at((r, c))).contains(&q)));
+            for &q in open.iter() {
+                assert!(adjacent(q).contains(&(r, c)), "{:?} -> {:?} is not symmetric", (r, c), q);
+            }
+            let tris = open.iter().enumerate().map(|(i, &a)| open[i + 1..].iter().filter(|b| adjacent(a).contains(b)).count()).sum::<usize>();
+            assert_eq!(tris, triangles, "{:?}", (r, c));
+        }
+    }
+}
+#[test]
+fn test_archimedean() {
+    check_lattice::<OpenSNS, ClosedSNS>(5, 3, "3.3.4.3.4");
+    check_lattice::<OpenKAG, ClosedKAG>(4, 2, "3.6.3.6");
+    check_lattice::<OpenRTH, ClosedRTH>(4, 1, "3.4.6.4");
+    check_lattice::<OpenTRH, ClosedTRH>(3, 1, "3.12.12");
+    check_lattice::<OpenETRI, ClosedETRI>(5, 3, "3.3.3.4.4");
+    check_lattice::<OpenTSQ, ClosedTSQ>(3, 0, "4.8.8");
+    check_lattice::<OpenTTRH, ClosedTTRH>(3, 0, "4.6.12");
+    check_lattice::<OpenSNH, ClosedSNH>(5, 4, "3.3.3.3.6");
+
+    // and a couple spelled out
+    assert_eq!(drain(OpenKAG::default().at((0, 0))), &[(-1, 0), (0, -1), (0, 1), (1, 1)]);
+    assert_eq!(drain(ClosedKAG::default().at((4, -1))), &[(3, -2), (3, -1), (4, -2), (4, -1), (5, -1)]);
+    assert_eq!(drain(OpenETRI::default().at((-3, 2))), &[(-4, 2), (-3, 1), (-3, 3), (-2, 2), (-2, 3)]);
+    assert_eq!(drain(ClosedTSQ::default().at((1, 1))), &[(0, 0), (0, 1), (1, 1), (1, 2)]);
+}
+#[test]
+fn test_reach() {
+    use crate::codesets;
+    use crate::params::Graph;
+    use crate::tessellation::{Geometry, GeometryTessellation, Goal, Tessellation};
+    use std::convert::TryFrom;
+
+    // the square grid squared: closed neighborhoods are radius-2 diamonds
+    const DIAMOND: &[(isize, isize)] = &[(-2, 0), (-1, -1), (-1, 0), (-1, 1), (0, -2), (0, -1), (0, 1), (0, 2), (1, -1), (1, 0), (1, 1), (2, 0)];
+    let sq2 = Arc::new(PeriodicGraph::builtin(&Graph::SQ).power(2));
+    assert_eq!(sq2.adjacent(sq2.class(5, -3)), DIAMOND);
+    assert_eq!(sq2.reach(), 2);
+    let grid2 = ClosedPeriodic::new(sq2);
+    assert_eq!(grid2.reach(), 2);
+    assert_eq!(drain(grid2.at((1, 1))).len(), 13);
+    let k2 = PeriodicGraph::builtin(&Graph::K).power(2);
+    assert_eq!(k2.adjacent(0).len(), 24);
+    assert!(k2.adjacent(0).iter().all(|d| d.0.abs() <= 2 && d.1.abs() <= 2));
+    assert_eq!(PeriodicGraph::builtin(&Graph::HEX).power(1), PeriodicGraph::builtin(&Graph::HEX));
+
+    // the diamonds tile the plane (a perfect code), which a 1x13 tile finds with basis (1, 8), (0, 13)
+    let mut tess = GeometryTessellation::try_from(Geometry::rectangle(1, 13)).unwrap();
+    tess.fit_reach(2).unwrap();
+    assert_eq!(tess.try_satisfy::<codesets::DOM<(isize, isize)>, _>(&grid2, Goal::Exactly(1)), Some(1));
+    for goal in &[0.3, 0.5] {
+        let mut tess = GeometryTessellation::try_from(Geometry::rectangle(2, 3)).unwrap();
+        tess.fit_reach(2).unwrap();
+        let a = tess.try_satisfy::<codesets::OLD<(isize, isize)>, _>(&grid2, Goal::MeetOrBeat(*goal));
+        let b = tess.try_satisfy_sat::<codesets::OLD<(isize, isize)>, _>(&grid2, Goal::MeetOrBeat(*goal));
+        assert_eq!(a, b, "{}", goal);
+
+        // extra padding doesn't change anything for the built-in graphs
+        let c = tess.try_satisfy::<codesets::OLD<(isize, isize)>, _>(&ClosedGrid::default(), Goal::MeetOrBeat(*goal));
+        let d = GeometryTessellation::try_from(Geometry::rectangle(2, 3)).unwrap().try_satisfy::<codesets::OLD<(isize, isize)>, _>(&ClosedGrid::default(), Goal::MeetOrBeat(*goal));
+        assert_eq!(c, d, "{}", goal);
+    }
 }
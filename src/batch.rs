@@ -0,0 +1,171 @@
+// batch runs: a job file has one old-set command per line (without the executable name), like "theo --param ld --graph hex --thresh 1/3".
+// blank lines and lines starting with # are skipped. each job runs as its own process with --format json (so jobs can't clobber
+// each other's output), and the results of every job are gathered into a single json lines file, one line per job in file order.
+
+use std::io::{BufRead, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use old_set::{util, Error};
+
+use crate::output::Json;
+
+pub struct Job {
+    pub line: usize, // where the job came from in the job file (starting at 1)
+    pub args: Vec<String>,
+}
+pub struct JobResult {
+    pub exit_code: Option<i32>, // None if the job was killed (by a signal or for running out of time)
+    pub timed_out: bool,
+    pub seconds: f64,
+    pub stdout: String,
+    pub stderr: String,
+}
+impl JobResult {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+    // the line in the results file for this job
+    pub fn to_json(&self, job: &Job) -> Json {
+        let mut fields = vec![
+            ("line", job.line.into()),
+            ("command", job.args.join(" ").into()),
+            ("status", if self.success() { "ok" } else if self.timed_out { "timed out" } else { "failed" }.into()),
+            ("exit_code", self.exit_code.map(|c| c as isize).into()),
+            ("seconds", self.seconds.into()),
+            ("results", Json::Array(self.stdout.lines().filter(|l| !l.trim().is_empty()).map(result_line).collect())),
+        ];
+        if !self.success() {
+            fields.push(("error", self.stderr.trim().into()));
+        }
+        Json::object(fields)
+    }
+}
+
+// a line a job printed - some output (like --help) is plain text even under --format json, so that's kept as a string
+fn result_line(line: &str) -> Json {
+    match Json::parse(line) {
+        Some(_) => Json::Raw(line.to_owned()),
+        None => Json::String(line.to_owned()),
+    }
+}
+#[test]
+fn test_job_result_to_json() {
+    let job = Job { line: 3, args: vec!["theo".into(), "--help".into()] };
+    let res = JobResult { exit_code: Some(0), timed_out: false, seconds: 0.5, stdout: "{\"found\":true}\n\nusage: old-set theo [options]\n".into(), stderr: String::new() };
+    let json = Json::parse(&res.to_json(&job).to_string()).unwrap();
+    let results = json.get("results").and_then(Json::as_array).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].get("found").is_some());
+    assert_eq!(results[1].as_str(), Some("usage: old-set theo [options]"));
+}
+
+pub fn read_jobs<R: BufRead>(path: &str, f: R) -> Result<Vec<Job>, Error> {
+    let mut jobs = vec![];
+    for (row, line) in f.lines().enumerate() {
+        let line = line.map_err(|e| Error::io("read job file", path, e))?;
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<_> = util::tokens(&line).collect();
+        let format = |col: usize, msg| Error::Format { path: path.to_owned(), line: row + 1, col, msg };
+        match tokens.first() {
+            None => continue,
+            Some(&(col, "batch")) => return Err(format(col, "batch jobs can't run other batches")),
+            _ => (),
+        }
+        if let Some(&(col, _)) = tokens.iter().find(|(_, tok)| *tok == "--format") {
+            return Err(format(col, "batch jobs always run with --format json"));
+        }
+        jobs.push(Job { line: row + 1, args: tokens.into_iter().map(|(_, tok)| tok.to_owned()).collect() });
+    }
+    Ok(jobs)
+}
+#[test]
+fn test_read_jobs() {
+    let jobs = read_jobs("jobs", &b"# survey\ntheo --param ld --graph hex --thresh 1/3\n\n  rect 4 4 --param dom --graph sq --thresh 0.25\n"[..]).unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!((jobs[0].line, jobs[0].args.len()), (2, 7));
+    assert_eq!((jobs[1].line, jobs[1].args[0].as_str()), (4, "rect"));
+
+    let err = |text: &[u8]| match read_jobs("jobs", text) {
+        Err(Error::Format { line, col, msg, .. }) => (line, col, msg),
+        _ => panic!(),
+    };
+    assert_eq!(err(b"smallest --param dom\n batch other.txt\n"), (2, 2, "batch jobs can't run other batches"));
+    assert_eq!(err(b"smallest --param dom --format text\n"), (1, 22, "batch jobs always run with --format json"));
+}
+
+// runs f on every item on a pool of threads, calling done as each one finishes - results are in the same order as items
+pub fn parallel<T, R, F, D>(items: &[T], threads: usize, f: F, done: D) -> Vec<R>
+where T: Sync, R: Send, F: Fn(&T) -> R + Sync, D: Fn(&T, &R) + Sync
+{
+    let next = Mutex::new(0);
+    let results = Mutex::new(items.iter().map(|_| None).collect::<Vec<_>>());
+    thread::scope(|s| {
+        for _ in 0..threads.min(items.len()) {
+            s.spawn(|| loop {
+                let i = {
+                    let mut next = next.lock().unwrap();
+                    if *next >= items.len() { break; }
+                    *next += 1;
+                    *next - 1
+                };
+                let res = f(&items[i]);
+                done(&items[i], &res);
+                results.lock().unwrap()[i] = Some(res);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().map(Option::unwrap).collect()
+}
+
+// runs exe with --format json and args, killing it if it's still going after time_limit
+pub fn run_process(exe: &Path, args: &[String], time_limit: Option<Duration>) -> Result<JobResult, Error> {
+    let start = Instant::now();
+    let mut child = Command::new(exe).arg("--format").arg("json").args(args)
+        .stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn().map_err(|e| Error::io("run", &exe.to_string_lossy(), e))?;
+
+    // read the output as it comes so a chatty process can't fill up the pipe and stall
+    let read = |mut pipe: Box<dyn Read + Send>| thread::spawn(move || {
+        let mut v = vec![];
+        pipe.read_to_end(&mut v).ok();
+        String::from_utf8_lossy(&v).into_owned()
+    });
+    let stdout = read(Box::new(child.stdout.take().unwrap()));
+    let stderr = read(Box::new(child.stderr.take().unwrap()));
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| Error::io("wait for", &exe.to_string_lossy(), e))? {
+            break status;
+        }
+        if time_limit.map(|t| start.elapsed() >= t).unwrap_or(false) {
+            timed_out = true;
+            child.kill().ok(); // it may have just finished on its own
+            break child.wait().map_err(|e| Error::io("wait for", &exe.to_string_lossy(), e))?;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    Ok(JobResult {
+        exit_code: if timed_out { None } else { status.code() },
+        timed_out,
+        seconds: start.elapsed().as_secs_f64(),
+        stdout: stdout.join().unwrap(),
+        stderr: stderr.join().unwrap(),
+    })
+}
+
+// runs every job with exe on a pool of threads, calling done as each one finishes - results are in the same order as jobs
+pub fn run_jobs<F>(exe: &Path, jobs: &[Job], threads: usize, done: F) -> Result<Vec<JobResult>, Error>
+where F: Fn(&Job, &JobResult) + Sync
+{
+    let results = parallel(jobs, threads, |job| run_process(exe, &job.args, None), |job, res| {
+        if let Ok(res) = res { done(job, res); }
+    });
+    results.into_iter().collect()
+}
@@ -0,0 +1,434 @@
+// bitset versions of the sets in codesets: codes are stored as 128-bit masks, so comparing two codes is just a few popcounts.
+// every item that shows up in a set must lie in a single WINDOW x WINDOW square (any one will do) for the masks to be exact,
+// which is always the case for the theo searches on graphs within a king's move (everything is within 5 of the center).
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::codesets::{LOC, Set};
+
+pub use crate::codesets::DOM; // never compares codes, so there's nothing to gain
+
+pub const WINDOW: isize = 11;
+
+pub trait Bit: Copy + Debug + PartialEq {
+    fn bit(self) -> u128;
+}
+impl Bit for (isize, isize) {
+    fn bit(self) -> u128 {
+        1 << (self.0.rem_euclid(WINDOW) * WINDOW + self.1.rem_euclid(WINDOW))
+    }
+}
+
+fn mask<T: Bit>(code: &[T]) -> u128 {
+    code.iter().fold(0, |m, x| m | x.bit())
+}
+fn len(code: u128) -> usize {
+    code.count_ones() as usize
+}
+fn symmetric_diff(a: u128, b: u128) -> usize {
+    len(a ^ b)
+}
+fn max_diff(a: u128, b: u128) -> usize {
+    len(a & !b).max(len(b & !a))
+}
+fn contains(code: u128, pos: u128) -> bool {
+    code & pos != 0
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitLOC<T> {
+    code: u128,
+    item: PhantomData<T>,
+}
+impl<T: Bit> LOC for BitLOC<T> {
+    type Item = T;
+
+    fn dom(&self) -> usize {
+        len(self.code)
+    }
+    fn new(_: T, _: bool, code: Vec<T>) -> Self {
+        Self { code: mask(&code), item: PhantomData }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitLDLOC<T> {
+    code: u128,
+    is_detector: bool,
+    pos: u128,
+    item: PhantomData<T>,
+}
+impl<T: Bit> LOC for BitLDLOC<T> {
+    type Item = T;
+
+    fn dom(&self) -> usize {
+        if self.is_detector {
+            len(self.code) + 1
+        }
+        else {
+            len(self.code)
+        }
+    }
+    fn new(pos: T, is_detector: bool, code: Vec<T>) -> Self {
+        Self { code: mask(&code), is_detector, pos: pos.bit(), item: PhantomData }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LD<T> {
+    codes: Vec<u128>,
+    pushed: Vec<bool>, // which pushes were detectors (which don't store anything)
+    item: PhantomData<T>,
+}
+impl<T> Default for LD<T> {
+    fn default() -> Self {
+        Self { codes: vec![], pushed: vec![], item: PhantomData }
+    }
+}
+impl<T: Bit> Set for LD<T> {
+    type Item = T;
+    type LocatingCode = BitLDLOC<T>;
+
+    fn clear(&mut self) {
+        self.codes.clear();
+        self.pushed.clear();
+    }
+    fn can_add(&self, loc: &Self::LocatingCode) -> bool {
+        loc.is_detector || (loc.code != 0 && !self.codes.contains(&loc.code))
+    }
+    fn add(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            if !loc.is_detector {
+                self.codes.push(loc.code);
+            }
+            true
+        }
+        else { false }
+    }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        let is_detector = loc.is_detector;
+        if self.add(loc) {
+            self.pushed.push(is_detector);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if !self.pushed.pop().unwrap() {
+            self.codes.pop();
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (0, usize::MAX) } else { (1, usize::MAX) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct REDLD<T> {
+    detector_codes: Vec<(u128, u128)>, // (pos, code)
+    non_detector_codes: Vec<u128>,
+    pushed: Vec<bool>, // which list each push went to (true for detectors)
+    item: PhantomData<T>,
+}
+impl<T> Default for REDLD<T> {
+    fn default() -> Self {
+        Self { detector_codes: vec![], non_detector_codes: vec![], pushed: vec![], item: PhantomData }
+    }
+}
+impl<T: Bit> Set for REDLD<T> {
+    type Item = T;
+    type LocatingCode = BitLDLOC<T>;
+
+    fn clear(&mut self) {
+        self.detector_codes.clear();
+        self.non_detector_codes.clear();
+        self.pushed.clear();
+    }
+    fn can_add(&self, loc: &Self::LocatingCode) -> bool {
+        if loc.is_detector {
+            // 1-open-dominated, and 1-open-distinguished from non-detectors by something other than the detector
+            loc.code != 0 && self.non_detector_codes.iter().all(|&other| {
+                symmetric_diff(loc.code, other) - contains(other, loc.pos) as usize >= 1
+            })
+        }
+        else {
+            // 2-open-dominated, 1-open-distinguished from detectors by something other than the detector, and 2-open-distinguished from non-detectors
+            len(loc.code) >= 2
+                && self.detector_codes.iter().all(|&(pos, other)| symmetric_diff(loc.code, other) - contains(loc.code, pos) as usize >= 1)
+                && self.non_detector_codes.iter().all(|&other| symmetric_diff(loc.code, other) >= 2)
+        }
+    }
+    fn add(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            if loc.is_detector {
+                self.detector_codes.push((loc.pos, loc.code));
+            }
+            else {
+                self.non_detector_codes.push(loc.code);
+            }
+            true
+        }
+        else { false }
+    }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        let is_detector = loc.is_detector;
+        if self.add(loc) {
+            self.pushed.push(is_detector);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if self.pushed.pop().unwrap() {
+            self.detector_codes.pop();
+        }
+        else {
+            self.non_detector_codes.pop();
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (1, usize::MAX) } else { (2, usize::MAX) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DETLD<T> {
+    detector_codes: Vec<u128>,
+    non_detector_codes: Vec<u128>,
+    pushed: Vec<bool>, // which list each push went to (true for detectors)
+    item: PhantomData<T>,
+}
+impl<T> Default for DETLD<T> {
+    fn default() -> Self {
+        Self { detector_codes: vec![], non_detector_codes: vec![], pushed: vec![], item: PhantomData }
+    }
+}
+impl<T: Bit> Set for DETLD<T> {
+    type Item = T;
+    type LocatingCode = BitLDLOC<T>;
+
+    fn clear(&mut self) {
+        self.detector_codes.clear();
+        self.non_detector_codes.clear();
+        self.pushed.clear();
+    }
+    fn can_add(&self, loc: &Self::LocatingCode) -> bool {
+        // asymmetric sharp distinguishing between a detector and a non-detector
+        let sharp = |det: u128, non: u128| {
+            let eq = len(det & non);
+            len(non) - eq >= 2 || len(det) - eq >= 1
+        };
+        if loc.is_detector {
+            loc.code != 0
+                && !self.detector_codes.contains(&loc.code)
+                && self.non_detector_codes.iter().all(|&other| sharp(loc.code, other))
+        }
+        else {
+            len(loc.code) >= 2
+                && self.detector_codes.iter().all(|&other| sharp(other, loc.code))
+                && self.non_detector_codes.iter().all(|&other| max_diff(loc.code, other) >= 2)
+        }
+    }
+    fn add(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            if loc.is_detector {
+                self.detector_codes.push(loc.code);
+            }
+            else {
+                self.non_detector_codes.push(loc.code);
+            }
+            true
+        }
+        else { false }
+    }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        let is_detector = loc.is_detector;
+        if self.add(loc) {
+            self.pushed.push(is_detector);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if self.pushed.pop().unwrap() {
+            self.detector_codes.pop();
+        }
+        else {
+            self.non_detector_codes.pop();
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (1, usize::MAX) } else { (2, usize::MAX) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ERRLD<T> {
+    detector_codes: Vec<(u128, u128)>, // (pos, code)
+    non_detector_codes: Vec<u128>,
+    pushed: Vec<bool>, // which list each push went to (true for detectors)
+    item: PhantomData<T>,
+}
+impl<T> Default for ERRLD<T> {
+    fn default() -> Self {
+        Self { detector_codes: vec![], non_detector_codes: vec![], pushed: vec![], item: PhantomData }
+    }
+}
+impl<T: Bit> Set for ERRLD<T> {
+    type Item = T;
+    type LocatingCode = BitLDLOC<T>;
+
+    fn clear(&mut self) {
+        self.detector_codes.clear();
+        self.non_detector_codes.clear();
+        self.pushed.clear();
+    }
+    fn can_add(&self, loc: &Self::LocatingCode) -> bool {
+        if loc.is_detector {
+            // 2-open-dominated, 1-open-distinguished from detectors and 2-open-distinguished from non-detectors by something other than each other
+            len(loc.code) >= 2
+                && self.detector_codes.iter().all(|&(pos, other)| {
+                    symmetric_diff(loc.code, other) - contains(loc.code, pos) as usize - contains(other, loc.pos) as usize >= 1
+                })
+                && self.non_detector_codes.iter().all(|&other| symmetric_diff(loc.code, other) - contains(other, loc.pos) as usize >= 2)
+        }
+        else {
+            // 3-open-dominated, 2-open-distinguished from detectors by something other than the detector, and 3-open-distinguished from non-detectors
+            len(loc.code) >= 3
+                && self.detector_codes.iter().all(|&(pos, other)| symmetric_diff(loc.code, other) - contains(loc.code, pos) as usize >= 2)
+                && self.non_detector_codes.iter().all(|&other| symmetric_diff(loc.code, other) >= 3)
+        }
+    }
+    fn add(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            if loc.is_detector {
+                self.detector_codes.push((loc.pos, loc.code));
+            }
+            else {
+                self.non_detector_codes.push(loc.code);
+            }
+            true
+        }
+        else { false }
+    }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        let is_detector = loc.is_detector;
+        if self.add(loc) {
+            self.pushed.push(is_detector);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if self.pushed.pop().unwrap() {
+            self.detector_codes.pop();
+        }
+        else {
+            self.non_detector_codes.pop();
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (2, usize::MAX) } else { (3, usize::MAX) }
+    }
+}
+
+// the sets on regular (non-ld) codes only differ in their domination and distinguishing requirements
+macro_rules! regular_set {
+    ($name:ident, $dom:expr, $distinct:expr) => {
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name<T> {
+            codes: Vec<u128>,
+            item: PhantomData<T>,
+        }
+        impl<T> Default for $name<T> {
+            fn default() -> Self {
+                Self { codes: vec![], item: PhantomData }
+            }
+        }
+        impl<T: Bit> Set for $name<T> {
+            type Item = T;
+            type LocatingCode = BitLOC<T>;
+
+            fn clear(&mut self) {
+                self.codes.clear();
+            }
+            fn can_add(&self, loc: &Self::LocatingCode) -> bool {
+                let distinct: fn(u128, u128) -> bool = $distinct;
+                len(loc.code) >= $dom && self.codes.iter().all(|&other| distinct(loc.code, other))
+            }
+            fn add(&mut self, loc: Self::LocatingCode) -> bool {
+                if self.can_add(&loc) {
+                    self.codes.push(loc.code);
+                    true
+                }
+                else { false }
+            }
+            fn push(&mut self, loc: Self::LocatingCode) -> bool {
+                self.add(loc)
+            }
+            fn pop(&mut self) {
+                self.codes.pop();
+            }
+            fn code_len_bounds(_: bool) -> (usize, usize) {
+                ($dom, usize::MAX)
+            }
+        }
+    }
+}
+regular_set!(OLD, 1, |a, b| a != b);
+regular_set!(RED, 2, |a, b| symmetric_diff(a, b) >= 2);
+regular_set!(DET, 2, |a, b| max_diff(a, b) >= 2);
+regular_set!(RSP, 3, |a, b| symmetric_diff(a, b) >= 2);
+regular_set!(ERR, 3, |a, b| symmetric_diff(a, b) >= 3);
+
+// feeds the same random codes to a vector-backed set and its bitset counterpart and checks that they always agree
+#[cfg(test)]
+fn check_same<A, B>()
+where A: Set<Item = (isize, isize)>, B: Set<Item = (isize, isize)>
+{
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut rand = move |n: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % n
+    };
+    for round in 0..200 {
+        let (mut a, mut b) = (A::default(), B::default());
+        let mut pushes = 0;
+        for _ in 0..12 {
+            // codes within a 4x4 square (offset by the round to make sure the window can be anywhere)
+            let origin = (round as isize * 7 - 700, round as isize * 3 - 300);
+            let item = |r: u64, c: u64| (origin.0 + r as isize, origin.1 + c as isize);
+            let pos = item(rand(4), rand(4));
+            let is_detector = rand(2) == 0;
+            let code: Vec<(isize, isize)> = (0..16).filter(|_| rand(3) == 0).map(|i| item(i / 4, i % 4)).filter(|&p| p != pos).collect(); // open codes never contain pos
+            let (x, y) = (A::LocatingCode::new(pos, is_detector, code.clone()), B::LocatingCode::new(pos, is_detector, code));
+            assert_eq!(x.dom(), y.dom());
+            if pushes > 0 && rand(4) == 0 {
+                a.pop();
+                b.pop();
+                pushes -= 1;
+            }
+            let res = a.push(x);
+            assert_eq!(res, b.push(y));
+            if res {
+                pushes += 1;
+            }
+        }
+    }
+}
+#[test]
+fn test_bitsets_match_codesets() {
+    use crate::codesets;
+    check_same::<codesets::LD<(isize, isize)>, LD<(isize, isize)>>();
+    check_same::<codesets::REDLD<(isize, isize)>, REDLD<(isize, isize)>>();
+    check_same::<codesets::DETLD<(isize, isize)>, DETLD<(isize, isize)>>();
+    check_same::<codesets::ERRLD<(isize, isize)>, ERRLD<(isize, isize)>>();
+    check_same::<codesets::OLD<(isize, isize)>, OLD<(isize, isize)>>();
+    check_same::<codesets::RED<(isize, isize)>, RED<(isize, isize)>>();
+    check_same::<codesets::DET<(isize, isize)>, DET<(isize, isize)>>();
+    check_same::<codesets::RSP<(isize, isize)>, RSP<(isize, isize)>>();
+    check_same::<codesets::ERR<(isize, isize)>, ERR<(isize, isize)>>();
+}
@@ -0,0 +1,467 @@
+// discharging certificates: when theo proves a lower bound it can write down every configuration it enumerated around each center class,
+// the center's share in each one, and the share it discharged to its neighbors to get back under the threshold.
+// verify re-checks a certificate from scratch, enumerating configurations and neighbor expansions on its own, so checking a bound
+// only means trusting this file and the set definitions in codesets rather than the whole search.
+//
+// the format is line based, with points written as row,col:
+//
+//     old-set certificate
+//     param LD
+//     graph HEX
+//     strategy dis
+//     thresh 1/3
+//     class 0 0,0             (class index and center, followed by every valid configuration around it)
+//     config 0,1 1,1 ...      (the detectors in the open interior - everything at radius 3 is a detector)
+//     share 7/2               (the center's share)
+//     give 0,1 bound 1/4 problems 2 each amount 1/24
+//     final 1/3               (or "impossible row,col" if some detector has no valid expansion at all)
+
+use std::collections::{BTreeSet, HashSet};
+use std::io::BufRead;
+
+use num::traits::{One, Zero};
+
+use crate::{codesets, Error};
+use crate::adj::AdjacentIterator;
+use crate::codesets::LOC;
+use crate::params::{Graph, Parameter};
+use crate::point::{Point, Point2};
+use crate::theo::{Share, TheoStrategy};
+
+// share the center discharged to one of its neighbors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer<P: Point = Point2> {
+    pub neighbor: P,
+    pub bound: Share, // the most share the neighbor can have in any expansion
+    pub problems: usize, // the number of problem detectors the neighbor splits its room under thresh between
+    pub simultaneous: bool, // if problems only counts the ones that can be problems at once
+    pub amount: Share,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome<P: Point = Point2> {
+    Final(Share), // the center's share after discharging
+    Impossible(P), // this detector has no valid expansion, so the configuration can't happen
+}
+
+fn point<P: Point>(p: P) -> String {
+    p.coords().iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")
+}
+pub fn header(param: Parameter, graph: &Graph, strategy: TheoStrategy, thresh: &Share) -> String {
+    let strategy = match strategy {
+        TheoStrategy::Trivial => "trivial",
+        TheoStrategy::Avg => "avg",
+        TheoStrategy::Dis => "dis",
+        TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare => panic!("weighted discharging has no certificates"),
+    };
+    format!("old-set certificate\nparam {:?}\ngraph {:?}\nstrategy {}\nthresh {}\n", param, graph, strategy, thresh)
+}
+pub fn class<P: Point>(class: usize, center: P) -> String {
+    format!("class {} {}\n", class, point(center))
+}
+pub fn config<P: Point>(detectors: &[P], share: &Share, transfers: &[Transfer<P>], outcome: &Outcome<P>) -> String {
+    let mut s = format!("config{}\nshare {}\n", detectors.iter().map(|p| format!(" {}", point(*p))).collect::<String>(), share);
+    for t in transfers {
+        let mode = if t.simultaneous { "at-once" } else { "each" };
+        s += &format!("give {} bound {} problems {} {} amount {}\n", point(t.neighbor), t.bound, t.problems, mode, t.amount);
+    }
+    match outcome {
+        Outcome::Final(x) => s += &format!("final {}\n", x),
+        Outcome::Impossible(p) => s += &format!("impossible {}\n", point(*p)),
+    }
+    s
+}
+
+// what a certificate proved
+pub struct Verified {
+    pub param: Parameter,
+    pub graph: Graph,
+    pub strategy: TheoStrategy,
+    pub thresh: Share,
+    pub configs: usize,
+}
+
+// reads the non-blank lines of a certificate, keeping track of where we are for error messages
+struct Lines<'a, R> {
+    path: &'a str,
+    lines: std::iter::Enumerate<std::io::Lines<R>>,
+    peeked: Option<(usize, String)>,
+    line: usize, // of the last line handed out
+}
+impl<R: BufRead> Lines<'_, R> {
+    fn fail<M: Into<String>>(&self, line: usize, msg: M) -> Error {
+        Error::Certificate { path: self.path.to_owned(), line, msg: msg.into() }
+    }
+    fn peek(&mut self) -> Result<Option<&(usize, String)>, Error> {
+        while self.peeked.is_none() {
+            match self.lines.next() {
+                None => break,
+                Some((i, line)) => {
+                    let line = line.map_err(|e| Error::io("read certificate", self.path, e))?;
+                    if !line.trim().is_empty() {
+                        self.peeked = Some((i + 1, line));
+                    }
+                }
+            }
+        }
+        Ok(self.peeked.as_ref())
+    }
+    // the tokens of the next line, which must start with key
+    fn expect(&mut self, key: &str) -> Result<Vec<String>, Error> {
+        self.peek()?;
+        let (line, text) = match self.peeked.take() {
+            Some(x) => x,
+            None => return Err(self.fail(self.line, format!("expected {} but the certificate ended", key))),
+        };
+        self.line = line;
+        let mut tokens = text.split_whitespace().map(str::to_owned);
+        match tokens.next() {
+            Some(k) if k == key => Ok(tokens.collect()),
+            _ => Err(self.fail(line, format!("expected {}", key))),
+        }
+    }
+    fn next_is(&mut self, key: &str) -> Result<bool, Error> {
+        Ok(self.peek()?.map(|(_, text)| text.split_whitespace().next() == Some(key)).unwrap_or(false))
+    }
+    fn parse<T: std::str::FromStr>(&self, token: Option<&String>, what: &str) -> Result<T, Error> {
+        token.and_then(|t| t.parse().ok()).ok_or_else(|| self.fail(self.line, format!("expected {}", what)))
+    }
+    fn point(&self, token: Option<&String>) -> Result<(isize, isize), Error> {
+        let fail = || self.fail(self.line, "expected a point like row,col");
+        let token = token.ok_or_else(fail)?;
+        let mut parts = token.split(',').map(str::parse);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(r)), Some(Ok(c)), None) => Ok((r, c)),
+            _ => Err(fail()),
+        }
+    }
+    // the whole line must be the single value key is followed by
+    fn value<T: std::str::FromStr>(&mut self, key: &str, what: &str) -> Result<T, Error> {
+        let tokens = self.expect(key)?;
+        if tokens.len() != 1 {
+            return Err(self.fail(self.line, format!("expected {} {}", key, what)));
+        }
+        self.parse(tokens.first(), what)
+    }
+}
+
+// everything within radius r of p
+fn ball<Adj: AdjacentIterator<Point = (isize, isize)>>(adj: &Adj, p: (isize, isize), r: usize) -> BTreeSet<(isize, isize)> {
+    let open = adj.open();
+    let mut s: BTreeSet<_> = std::iter::once(p).collect();
+    for _ in 0..r {
+        let next: Vec<_> = s.iter().flat_map(|&q| open.at(q)).collect();
+        s.extend(next);
+    }
+    s
+}
+fn code<Codes, Adj>(adj: &Adj, detectors: &HashSet<(isize, isize)>, p: (isize, isize)) -> Codes::LocatingCode
+where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>
+{
+    Codes::LocatingCode::new(p, detectors.contains(&p), adj.at(p).filter(|q| detectors.contains(q)).collect())
+}
+fn is_valid<'a, Codes, Adj, I>(adj: &Adj, detectors: &HashSet<(isize, isize)>, region: I) -> bool
+where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>, I: IntoIterator<Item = &'a (isize, isize)>
+{
+    let mut codes = Codes::default();
+    region.into_iter().all(|&p| codes.add(code::<Codes, _>(adj, detectors, p)))
+}
+fn share<Codes, Adj, ShareAdj>(adj: &Adj, share_adj: &ShareAdj, detectors: &HashSet<(isize, isize)>, p: (isize, isize)) -> Share
+where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>, ShareAdj: AdjacentIterator<Point = (isize, isize)>
+{
+    share_adj.at(p).fold(Share::zero(), |s, q| s + Share::new(One::one(), code::<Codes, _>(adj, detectors, q).dom().into()))
+}
+// calls f with every way of making the free points detectors or not (free points must not be detectors to begin with)
+fn for_each_assignment<F: FnMut(&HashSet<(isize, isize)>)>(detectors: &mut HashSet<(isize, isize)>, free: &[(isize, isize)], f: &mut F) {
+    match free.split_first() {
+        None => f(detectors),
+        Some((&p, rest)) => {
+            detectors.insert(p);
+            for_each_assignment(detectors, rest, f);
+            detectors.remove(&p);
+            for_each_assignment(detectors, rest, f);
+        }
+    }
+}
+
+// the points of an expansion that can be anything, and the ones around them that are taken to be detectors
+type Expansion = (Vec<(isize, isize)>, Vec<(isize, isize)>);
+
+// the fixed geometry around a center
+struct Lands {
+    center: (isize, isize),
+    closed: BTreeSet<(isize, isize)>, // everything up to radius 2
+    exterior: BTreeSet<(isize, isize)>, // everything at exactly radius 3
+}
+impl Lands {
+    fn new<Adj: AdjacentIterator<Point = (isize, isize)>>(adj: &Adj, center: (isize, isize)) -> Self {
+        let closed = ball(adj, center, 2);
+        let exterior = ball(adj, center, 3).into_iter().filter(|p| !closed.contains(p)).collect();
+        Self { center, closed, exterior }
+    }
+    // the points outside the closed interior within radius 2 of any of points, and every other point outside it within radius 3 of them or the center.
+    // the first can be anything, while the second are all taken to be detectors.
+    fn expansion<Adj: AdjacentIterator<Point = (isize, isize)>>(&self, adj: &Adj, points: &[(isize, isize)]) -> Expansion {
+        let field: BTreeSet<_> = points.iter().flat_map(|&p| ball(adj, p, 2)).filter(|p| !self.closed.contains(p)).collect();
+        let rest: BTreeSet<_> = points.iter().flat_map(|&p| ball(adj, p, 3)).chain(self.exterior.iter().copied())
+            .filter(|p| !self.closed.contains(p) && !field.contains(p)).collect();
+        (field.into_iter().collect(), rest.into_iter().collect())
+    }
+    // the most share p can have over every valid expansion around it (None if there isn't one)
+    fn max_share<Codes, Adj, ShareAdj>(&self, adj: &Adj, share_adj: &ShareAdj, config: &HashSet<(isize, isize)>, p: (isize, isize)) -> Option<Share>
+    where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>, ShareAdj: AdjacentIterator<Point = (isize, isize)>
+    {
+        let (field, rest) = self.expansion(adj, &[p]);
+        let mut detectors = config.clone();
+        detectors.extend(rest);
+        let region: Vec<_> = self.closed.iter().chain(field.iter()).copied().collect();
+
+        let mut best: Option<Share> = None;
+        for_each_assignment(&mut detectors, &field, &mut |detectors| {
+            if is_valid::<Codes, _, _>(adj, detectors, &region) {
+                let s = share::<Codes, _, _>(adj, share_adj, detectors, p);
+                if best.as_ref().map(|b| &s > b).unwrap_or(true) {
+                    best = Some(s);
+                }
+            }
+        });
+        best
+    }
+    // the most problem detectors next to p at once (counting the center, which is assumed to be one), or None if p's neighbors have no valid expansion
+    fn simultaneous_problems<Codes, Adj, ShareAdj>(&self, adj: &Adj, share_adj: &ShareAdj, config: &HashSet<(isize, isize)>, p: (isize, isize), thresh: &Share) -> Option<usize>
+    where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>, ShareAdj: AdjacentIterator<Point = (isize, isize)>
+    {
+        let others: Vec<_> = adj.open().at(p).filter(|q| *q != self.center && config.contains(q)).collect();
+        if others.is_empty() {
+            return Some(1);
+        }
+        let (field, rest) = self.expansion(adj, &others);
+        let mut detectors = config.clone();
+        detectors.extend(rest);
+        let region: Vec<_> = self.closed.iter().chain(field.iter()).copied().collect();
+
+        let mut most = None;
+        for_each_assignment(&mut detectors, &field, &mut |detectors| {
+            if is_valid::<Codes, _, _>(adj, detectors, &region) {
+                let count = others.iter().filter(|&&q| &share::<Codes, _, _>(adj, share_adj, detectors, q) > thresh).count();
+                most = Some(most.unwrap_or(0).max(count));
+            }
+        });
+        most.map(|m| m + 1)
+    }
+}
+
+// checks one configuration record (the next thing in lines), returning its detectors
+fn verify_config<Codes, Adj, ShareAdj, R>(adj: &Adj, share_adj: &ShareAdj, lines: &mut Lines<R>, lands: &Lands, strategy: TheoStrategy, thresh: &Share) -> Result<Vec<(isize, isize)>, Error>
+where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>, ShareAdj: AdjacentIterator<Point = (isize, isize)>, R: BufRead
+{
+    let tokens = lines.expect("config")?;
+    let config_line = lines.line;
+    let mut points = tokens.iter().map(|t| lines.point(Some(t))).collect::<Result<Vec<_>, _>>()?;
+    points.sort_unstable();
+    points.dedup();
+    if points.len() != tokens.len() || points.iter().any(|p| *p == lands.center || !lands.closed.contains(p)) {
+        return Err(lines.fail(config_line, "configuration detectors must be distinct points in the open interior"));
+    }
+
+    // the configuration itself: the center, its listed detectors, and the whole exterior
+    let config: HashSet<_> = std::iter::once(lands.center).chain(points.iter().copied()).collect();
+    let mut detectors = config.clone();
+    detectors.extend(lands.exterior.iter().copied());
+    if !is_valid::<Codes, _, _>(adj, &detectors, &lands.closed) {
+        return Err(lines.fail(config_line, "configuration is not valid"));
+    }
+
+    let claimed: Share = lines.value("share", "a share")?;
+    let actual = share::<Codes, _, _>(adj, share_adj, &detectors, lands.center);
+    if claimed != actual {
+        return Err(lines.fail(lines.line, format!("center share is {}, not {}", actual, claimed)));
+    }
+
+    let mut transfers = vec![];
+    while lines.next_is("give")? {
+        let tokens = lines.expect("give")?;
+        let line = lines.line;
+        let fields: Vec<_> = tokens.iter().map(String::as_str).collect();
+        let transfer = match fields.as_slice() {
+            [_, "bound", _, "problems", _, mode @ ("each" | "at-once"), "amount", _] => Transfer {
+                neighbor: lines.point(tokens.first())?,
+                bound: lines.parse(tokens.get(2), "a bound")?,
+                problems: lines.parse(tokens.get(4), "a problem count")?,
+                simultaneous: *mode == "at-once",
+                amount: lines.parse(tokens.get(7), "an amount")?,
+            },
+            _ => return Err(lines.fail(line, "expected give row,col bound [share] problems [count] each|at-once amount [share]")),
+        };
+        transfers.push((line, transfer));
+    }
+
+    if lines.next_is("impossible")? {
+        let tokens = lines.expect("impossible")?;
+        let p = lines.point(tokens.first())?;
+        if tokens.len() != 1 || p == lands.center || !config.contains(&p) {
+            return Err(lines.fail(lines.line, "an impossible detector must be a non-center detector in the configuration"));
+        }
+        if lands.max_share::<Codes, _, _>(adj, share_adj, &config, p).is_some() {
+            return Err(lines.fail(lines.line, format!("detector {} has a valid expansion", point(p))));
+        }
+        return Ok(points);
+    }
+
+    let mut working = actual.clone();
+    let mut given = HashSet::new();
+    for (line, t) in transfers {
+        let fail = |msg: String| Error::Certificate { path: lines.path.to_owned(), line, msg };
+        if actual <= *thresh || strategy == TheoStrategy::Trivial {
+            return Err(fail("nothing needs to be discharged here".to_owned()));
+        }
+        if !adj.open().at(lands.center).any(|q| q == t.neighbor) || !config.contains(&t.neighbor) || !given.insert(t.neighbor) {
+            return Err(fail("share can only be given once to each detector next to the center".to_owned()));
+        }
+        if t.bound >= *thresh {
+            return Err(fail("only detectors with share under thresh can take more".to_owned()));
+        }
+        // a neighbor with no valid expansion means the configuration can't happen, so there's nothing to check about it
+        let max = lands.max_share::<Codes, _, _>(adj, share_adj, &config, t.neighbor);
+        if let Some(max) = max.as_ref().filter(|&max| max > &t.bound) {
+            return Err(fail(format!("{} can have share {}, which is over its bound", point(t.neighbor), max)));
+        }
+        let problems = if max.is_none() {
+            None
+        }
+        else if t.simultaneous {
+            if strategy != TheoStrategy::Dis {
+                return Err(fail("only dis counts simultaneous problems".to_owned()));
+            }
+            lands.simultaneous_problems::<Codes, _, _>(adj, share_adj, &config, t.neighbor, thresh)
+        }
+        else {
+            // every detector next to the neighbor that can ever be a problem (the center already is one)
+            Some(adj.open().at(t.neighbor).filter(|q| config.contains(q)).filter(|&q| {
+                q == lands.center || lands.max_share::<Codes, _, _>(adj, share_adj, &config, q).map(|s| s > *thresh).unwrap_or(false)
+            }).count())
+        };
+        if let Some(problems) = problems {
+            if t.problems < problems || (strategy == TheoStrategy::Avg && t.problems != 1) {
+                return Err(fail(format!("{} is next to {} problems, not {}", point(t.neighbor), problems, t.problems)));
+            }
+        }
+        if t.problems == 0 || t.amount != (thresh - &t.bound) / Share::from_integer(t.problems.into()) {
+            return Err(fail("amount must be the room under thresh split between the problems".to_owned()));
+        }
+        working -= t.amount;
+    }
+
+    let claimed: Share = lines.value("final", "a share")?;
+    if claimed != working {
+        return Err(lines.fail(lines.line, format!("share after discharging is {}, not {}", working, claimed)));
+    }
+    if claimed > *thresh {
+        return Err(lines.fail(lines.line, "share after discharging is over thresh"));
+    }
+    Ok(points)
+}
+
+fn verify_with<Codes, Adj, ShareAdj, R>(adj: &Adj, share_adj: &ShareAdj, lines: &mut Lines<R>, strategy: TheoStrategy, thresh: &Share) -> Result<usize, Error>
+where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>, ShareAdj: AdjacentIterator<Point = (isize, isize)>, R: BufRead
+{
+    let mut configs = 0;
+    for (class, &center) in adj.classes().iter().enumerate() {
+        let tokens = lines.expect("class")?;
+        let class_line = lines.line;
+        if tokens.len() != 2 || lines.parse::<usize>(tokens.first(), "a class index")? != class || lines.point(tokens.get(1))? != center {
+            return Err(lines.fail(class_line, format!("expected class {} {}", class, point(center))));
+        }
+        let lands = Lands::new(adj, center);
+
+        let mut seen = HashSet::new();
+        while lines.next_is("config")? {
+            let points = verify_config::<Codes, _, _, R>(adj, share_adj, lines, &lands, strategy, thresh)?;
+            if !seen.insert(points) {
+                return Err(lines.fail(lines.line, "configuration was listed more than once"));
+            }
+        }
+
+        // every listed configuration is valid and distinct, so they're all there if there are as many as there are valid ones
+        let open: Vec<_> = lands.closed.iter().copied().filter(|p| *p != center).collect();
+        let mut detectors: HashSet<_> = std::iter::once(center).chain(lands.exterior.iter().copied()).collect();
+        let mut valid = 0;
+        for_each_assignment(&mut detectors, &open, &mut |detectors| {
+            if is_valid::<Codes, _, _>(adj, detectors, &lands.closed) {
+                valid += 1;
+            }
+        });
+        if valid != seen.len() {
+            return Err(lines.fail(class_line, format!("class has {} valid configurations, but {} were listed", valid, seen.len())));
+        }
+        configs += valid;
+    }
+    if let Some((line, _)) = lines.peek()? {
+        let line = *line;
+        return Err(lines.fail(line, "expected the end of the certificate"));
+    }
+    Ok(configs)
+}
+
+// checks every claim in a certificate, returning what it proved
+pub fn verify<R: BufRead>(path: &str, f: R) -> Result<Verified, Error> {
+    let mut lines = Lines { path, lines: f.lines().enumerate(), peeked: None, line: 0 };
+    if lines.expect("old-set")? != ["certificate"] {
+        return Err(lines.fail(lines.line, "expected old-set certificate"));
+    }
+    let param: Parameter = lines.value::<String>("param", "a set type")?.parse()?;
+    let graph: Graph = lines.value::<String>("graph", "a graph")?.parse()?;
+    let graph_line = lines.line;
+    let strategy: TheoStrategy = lines.value::<String>("strategy", "a strategy")?.parse()?;
+    if let TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare = strategy {
+        return Err(lines.fail(lines.line, "weighted discharging has no certificates"));
+    }
+    let thresh: Share = lines.value("thresh", "a threshold")?;
+    if thresh <= Share::zero() || thresh > Share::one() {
+        return Err(lines.fail(lines.line, "thresh must be in (0, 1]"));
+    }
+    let share_thresh = thresh.recip();
+
+    macro_rules! calc {
+        ($set:ident, $adj:expr, $shadj:expr) => {
+            verify_with::<codesets::$set<(isize, isize)>, _, _, R>(&$adj, &$shadj, &mut lines, strategy, &share_thresh)
+        }
+    }
+    let configs = graph_family!(&graph, |open, closed| param_family!(share param, calc, open, closed),
+        Graph::PERIODIC(_) => Err(lines.fail(graph_line, "periodic graphs have no certificates")))?;
+    Ok(Verified { param, graph, strategy, thresh, configs })
+}
+#[test]
+fn test_certificate_verify() {
+    use crate::theo::{self, TheoCertificate};
+
+    let thresh: Share = "5/6".parse().unwrap();
+    let write = |threads: usize| {
+        let mut out = header(Parameter::ERRIC, &Graph::HEX, TheoStrategy::Dis, &thresh).into_bytes();
+        let mut cert = TheoCertificate { path: "test".into(), out: &mut out };
+        let problems = theo::lower_bound(Parameter::ERRIC, &Graph::HEX, TheoStrategy::Dis, thresh.clone(), None, None, Some(&mut cert), threads).unwrap();
+        assert!(problems.is_empty());
+        String::from_utf8(out).unwrap()
+    };
+    let cert = write(1);
+    assert_eq!(write(3), cert); // the parallel search writes the same certificate
+    assert!(cert.contains("\ngive "));
+
+    let v = verify("test", cert.as_bytes()).unwrap();
+    assert_eq!((v.param, v.graph, v.strategy, v.thresh), (Parameter::ERRIC, Graph::HEX, TheoStrategy::Dis, thresh.clone()));
+    assert!(v.configs > 0);
+
+    let fails = |cert: &str| matches!(verify("test", cert.as_bytes()), Err(Error::Certificate { .. }));
+    // a wrong share, a discharge that gives too much, and a missing configuration all get caught
+    let share = cert.find("\nshare ").unwrap() + 1;
+    let end = share + cert[share..].find('\n').unwrap();
+    assert!(fails(&format!("{}share 1000{}", &cert[..share], &cert[end..])));
+    let give = cert.find("\ngive ").unwrap() + 1;
+    let amount = give + cert[give..].find("amount ").unwrap() + 7;
+    assert!(fails(&format!("{}100{}", &cert[..amount], &cert[amount..])));
+    let first = cert.find("\nconfig ").unwrap() + 1;
+    let second = first + 1 + cert[first + 1..].find("\nconfig ").unwrap() + 1;
+    assert!(fails(&format!("{}{}", &cert[..first], &cert[second..])));
+    assert!(fails(&cert.replace("thresh 5/6", "thresh 6/7")));
+    // and only the built-in graphs have certificates
+    assert!(fails(&cert.replace("graph HEX", "graph file:example-lattices/grid.txt")));
+}
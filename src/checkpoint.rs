@@ -0,0 +1,143 @@
+// periodically saved search state, so long theo and entropy searches can be resumed after an interruption.
+// checkpoints are plain text: a block naming the search (resuming a different search is an error), then one "name value" field per line.
+// multi-line values (like printed geometries) are written as blocks: a "name count" line followed by that many lines.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+pub const INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    InvalidFormat(String),
+    WrongSearch(String),
+}
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::Io(e) => write!(f, "{}", e),
+            CheckpointError::InvalidFormat(msg) => write!(f, "{}", msg),
+            CheckpointError::WrongSearch(search) => write!(f, "checkpoint is for a different search: {}", search),
+        }
+    }
+}
+
+// saves checkpoints to a file, but no more often than once per interval
+pub struct Saver {
+    path: String,
+    interval: Duration,
+    last: Instant,
+}
+impl Saver {
+    pub fn new(path: &str, interval: Duration) -> Self {
+        Self { path: path.to_owned(), interval, last: Instant::now() }
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn is_due(&self) -> bool {
+        self.last.elapsed() >= self.interval
+    }
+    // writes to a temp file first and then renames it over the old checkpoint, so being interrupted mid-write can't lose both
+    pub fn save(&mut self, w: &Writer) -> io::Result<()> {
+        let tmp = format!("{}.tmp", self.path);
+        let mut f = File::create(&tmp)?;
+        f.write_all(w.0.as_bytes())?;
+        f.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        self.last = Instant::now();
+        Ok(())
+    }
+}
+
+pub struct Writer(String);
+impl Writer {
+    pub fn new(search: &str) -> Self {
+        let mut w = Writer(String::new());
+        w.block("search", search);
+        w
+    }
+    pub fn field<T: fmt::Display>(&mut self, name: &str, value: T) {
+        self.0 += &format!("{} {}\n", name, value);
+    }
+    pub fn block(&mut self, name: &str, text: &str) {
+        let lines: Vec<_> = text.lines().collect();
+        self.field(name, lines.len());
+        for line in lines {
+            self.0 += line;
+            self.0.push('\n');
+        }
+    }
+}
+
+pub struct Reader {
+    lines: std::vec::IntoIter<String>,
+}
+impl Reader {
+    // opens a checkpoint, making sure it came from the same search
+    pub fn open(path: &str, search: &str) -> Result<Self, CheckpointError> {
+        let f = BufReader::new(File::open(path).map_err(CheckpointError::Io)?);
+        let lines = f.lines().collect::<Result<Vec<_>, _>>().map_err(CheckpointError::Io)?;
+        Self::from_lines(lines, search)
+    }
+    fn from_lines(lines: Vec<String>, search: &str) -> Result<Self, CheckpointError> {
+        let mut r = Reader { lines: lines.into_iter() };
+        let found = r.block("search")?;
+        if found.trim_end() != search.trim_end() {
+            return Err(CheckpointError::WrongSearch(found.trim_end().to_owned()));
+        }
+        Ok(r)
+    }
+    pub fn field(&mut self, name: &str) -> Result<String, CheckpointError> {
+        let line = match self.lines.next() {
+            Some(line) => line,
+            None => return Err(CheckpointError::InvalidFormat(format!("expected field '{}' but reached end of file", name))),
+        };
+        let mut parts = line.splitn(2, ' ');
+        match (parts.next(), parts.next()) {
+            (Some(n), Some(value)) if n == name => Ok(value.to_owned()),
+            (Some(n), None) if n == name => Ok(String::new()),
+            _ => Err(CheckpointError::InvalidFormat(format!("expected field '{}' but found '{}'", name, line))),
+        }
+    }
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Result<T, CheckpointError> {
+        let value = self.field(name)?;
+        value.parse().map_err(|_| CheckpointError::InvalidFormat(format!("failed to parse '{}' for field '{}'", value, name)))
+    }
+    pub fn block(&mut self, name: &str) -> Result<String, CheckpointError> {
+        let count: usize = self.parse(name)?;
+        let mut text = String::new();
+        for _ in 0..count {
+            match self.lines.next() {
+                Some(line) => { text += &line; text.push('\n'); }
+                None => return Err(CheckpointError::InvalidFormat(format!("block '{}' was cut short", name))),
+            }
+        }
+        Ok(text)
+    }
+}
+#[test]
+fn test_checkpoint_round_trip() {
+    let mut w = Writer::new("theo Dis 2/5");
+    w.field("class", 3);
+    w.field("path", "");
+    w.block("structure", "0 1 \n  1 0 \n");
+    w.field("share", "7/5");
+
+    let lines: Vec<String> = w.0.lines().map(str::to_owned).collect();
+    let mut r = Reader::from_lines(lines.clone(), "theo Dis 2/5").unwrap();
+    assert_eq!(r.parse::<usize>("class").unwrap(), 3);
+    assert_eq!(r.field("path").unwrap(), "");
+    assert_eq!(r.block("structure").unwrap(), "0 1 \n  1 0 \n");
+    assert!(r.field("avg-share").is_err());
+
+    assert!(matches!(Reader::from_lines(lines.clone(), "theo Dis 1/2"), Err(CheckpointError::WrongSearch(_))));
+    let mut r = Reader::from_lines(lines[..4].to_vec(), "theo Dis 2/5").unwrap();
+    r.field("class").unwrap();
+    r.field("path").unwrap();
+    assert!(r.block("structure").is_err());
+}
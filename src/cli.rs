@@ -0,0 +1,152 @@
+// command line parsing: each subcommand declares its positional arguments and named options in one table,
+// which is used both to check the arguments it was given and to print its --help.
+
+use old_set::Error;
+
+pub struct Opt {
+    pub name: &'static str, // including the leading dashes
+    pub values: &'static [&'static str], // placeholders for the values it takes (empty for plain flags)
+    pub required: bool,
+    pub help: &'static str,
+}
+pub struct Command {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub positional: &'static [(&'static str, &'static str)], // (placeholder, help)
+    pub opts: &'static [Opt],
+}
+impl Command {
+    pub fn usage(&self, exe: &str) -> String {
+        let mut s = format!("usage: {} {}", exe, self.name);
+        for (name, _) in self.positional {
+            s += &format!(" [{}]", name);
+        }
+        for opt in self.opts {
+            let mut o = opt.name.to_owned();
+            for v in opt.values {
+                o += &format!(" [{}]", v);
+            }
+            s += &if opt.required { format!(" {}", o) } else { format!(" ({})", o) };
+        }
+        s
+    }
+    pub fn help(&self, exe: &str) -> String {
+        let mut s = format!("{}\n\n{}\n", self.about, self.usage(exe));
+        let pad = |s: String| format!("{:<32}", s);
+        if !self.positional.is_empty() {
+            s += "\narguments:\n";
+            for (name, help) in self.positional {
+                s += &format!("  {}{}\n", pad(format!("[{}]", name)), help);
+            }
+        }
+        s += "\noptions:\n";
+        for opt in self.opts {
+            let mut o = opt.name.to_owned();
+            for v in opt.values {
+                o += &format!(" [{}]", v);
+            }
+            s += &format!("  {}{}{}\n", pad(o), opt.help, if opt.required { " (required)" } else { "" });
+        }
+        s += &format!("  {}{}\n", pad("--help".to_owned()), "print this message");
+        s
+    }
+    fn usage_error(&self, exe: &str, msg: String) -> Error {
+        Error::Usage(format!("{}\n{}\nsee {} {} --help for more information", msg, self.usage(exe), exe, self.name))
+    }
+
+    // returns None if --help was requested, in which case nothing else was checked
+    pub fn parse<'a>(&self, exe: &str, args: &'a [String]) -> Result<Option<Matches<'a>>, Error> {
+        if args.iter().any(|arg| arg == "--help") {
+            return Ok(None);
+        }
+        let mut m = Matches { positional: vec![], opts: vec![] };
+        let mut i = 0;
+        while i < args.len() {
+            let arg = args[i].as_str();
+            i += 1;
+            if !arg.starts_with("--") {
+                if m.positional.len() == self.positional.len() {
+                    return Err(self.usage_error(exe, format!("unexpected argument: {}", arg)));
+                }
+                m.positional.push(arg);
+                continue;
+            }
+            let opt = match self.opts.iter().find(|opt| opt.name == arg) {
+                Some(opt) => opt,
+                None => return Err(self.usage_error(exe, format!("unknown option: {}", arg))),
+            };
+            if m.opts.iter().any(|(name, _)| *name == opt.name) {
+                return Err(self.usage_error(exe, format!("option {} was given more than once", arg)));
+            }
+            if args.len() - i < opt.values.len() {
+                return Err(self.usage_error(exe, format!("option {} expects {} value(s)", arg, opt.values.len())));
+            }
+            m.opts.push((opt.name, args[i..i + opt.values.len()].iter().map(String::as_str).collect()));
+            i += opt.values.len();
+        }
+        if m.positional.len() < self.positional.len() {
+            return Err(self.usage_error(exe, format!("missing argument: [{}]", self.positional[m.positional.len()].0)));
+        }
+        if let Some(opt) = self.opts.iter().find(|opt| opt.required && !m.flag(opt.name)) {
+            return Err(self.usage_error(exe, format!("missing option: {}", opt.name)));
+        }
+        Ok(Some(m))
+    }
+}
+
+pub struct Matches<'a> {
+    positional: Vec<&'a str>,
+    opts: Vec<(&'static str, Vec<&'a str>)>,
+}
+impl<'a> Matches<'a> {
+    pub fn arg(&self, i: usize) -> &'a str {
+        self.positional[i]
+    }
+    pub fn flag(&self, name: &str) -> bool {
+        self.opts.iter().any(|(n, _)| *n == name)
+    }
+    pub fn values(&self, name: &str) -> Option<&[&'a str]> {
+        self.opts.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_slice())
+    }
+    // the value of a single-valued option
+    pub fn value(&self, name: &str) -> Option<&'a str> {
+        self.values(name).map(|v| v[0])
+    }
+    // the value of a required single-valued option (parse already made sure it was given)
+    pub fn get(&self, name: &str) -> &'a str {
+        self.value(name).unwrap()
+    }
+}
+#[test]
+fn test_command_parse() {
+    const CMD: Command = Command {
+        name: "test", about: "a test command",
+        positional: &[("file", "some file")],
+        opts: &[
+            Opt { name: "--param", values: &["set-type"], required: true, help: "the set type" },
+            Opt { name: "--proof", values: &["cnf", "drup"], required: false, help: "proof files" },
+            Opt { name: "--sat", values: &[], required: false, help: "use sat" },
+        ],
+    };
+    let args = |s: &str| s.split_whitespace().map(str::to_owned).collect::<Vec<_>>();
+    let is_usage = |r: Result<Option<Matches>, Error>| matches!(r, Err(Error::Usage(_)));
+
+    let a = args("--param dom g.txt --proof a.cnf b.drup");
+    let m = CMD.parse("old-set", &a).unwrap().unwrap();
+    assert_eq!(m.arg(0), "g.txt");
+    assert_eq!(m.get("--param"), "dom");
+    assert_eq!(m.values("--proof"), Some(&["a.cnf", "b.drup"][..]));
+    assert!(!m.flag("--sat"));
+
+    assert!(CMD.parse("old-set", &args("g.txt --bogus --help")).unwrap().is_none());
+    assert!(is_usage(CMD.parse("old-set", &args("g.txt"))));
+    assert!(is_usage(CMD.parse("old-set", &args("--param dom"))));
+    assert!(is_usage(CMD.parse("old-set", &args("g.txt h.txt --param dom"))));
+    assert!(is_usage(CMD.parse("old-set", &args("g.txt --param dom --param ic"))));
+    assert!(is_usage(CMD.parse("old-set", &args("g.txt --param dom --proof a.cnf"))));
+    assert!(is_usage(CMD.parse("old-set", &args("g.txt --param dom --bogus"))));
+
+    let help = CMD.help("old-set");
+    assert!(help.contains("usage: old-set test [file] --param [set-type] (--proof [cnf] [drup]) (--sat)"));
+    assert!(help.contains("--help"));
+}
@@ -0,0 +1,573 @@
+use std::io::{self, BufRead, Write};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+use crate::codesets;
+use crate::util;
+
+// dimacs-style literal: variables are numbered from 1 and negative values denote negation
+pub type Lit = i32;
+
+#[derive(Default, Clone, Debug)]
+pub struct Cnf {
+    vars: usize,
+    clauses: Vec<Vec<Lit>>,
+    comments: Vec<String>,
+}
+impl Cnf {
+    pub fn with_vars(vars: usize) -> Self {
+        Self { vars, clauses: vec![], comments: vec![] }
+    }
+    pub fn var_count(&self) -> usize {
+        self.vars
+    }
+    pub fn clauses(&self) -> &[Vec<Lit>] {
+        &self.clauses
+    }
+    pub fn new_var(&mut self) -> Lit {
+        self.vars += 1;
+        self.vars as Lit
+    }
+    pub fn comment(&mut self, msg: String) {
+        self.comments.push(msg);
+    }
+    pub fn add_clause<I: IntoIterator<Item = Lit>>(&mut self, clause: I) {
+        let clause: Vec<Lit> = clause.into_iter().collect();
+        debug_assert!(clause.iter().all(|&x| x != 0 && x.unsigned_abs() as usize <= self.vars));
+        self.clauses.push(clause);
+    }
+
+    // adds a clause which only needs to hold when every literal in cond is true
+    fn add_guarded(&mut self, cond: &[Lit], clause: &[Lit]) {
+        self.add_clause(cond.iter().map(|x| -x).chain(clause.iter().copied()));
+    }
+    // when every literal in cond is true, at most k of lits may be true (sinz sequential counter).
+    // lits may contain repeats, in which case each occurrence is counted separately.
+    pub fn at_most(&mut self, cond: &[Lit], lits: &[Lit], k: usize) {
+        let n = lits.len();
+        if k >= n { return; }
+        if k == 0 {
+            for &x in lits {
+                self.add_guarded(cond, &[-x]);
+            }
+            return;
+        }
+
+        // s[i][j] means at least j + 1 of lits[..=i] are true
+        let s: Vec<Vec<Lit>> = (0..n - 1).map(|_| (0..k).map(|_| self.new_var()).collect()).collect();
+        self.add_guarded(cond, &[-lits[0], s[0][0]]);
+        for &x in &s[0][1..] {
+            self.add_guarded(cond, &[-x]);
+        }
+        for i in 1..n - 1 {
+            self.add_guarded(cond, &[-lits[i], s[i][0]]);
+            self.add_guarded(cond, &[-s[i - 1][0], s[i][0]]);
+            for j in 1..k {
+                self.add_guarded(cond, &[-lits[i], -s[i - 1][j - 1], s[i][j]]);
+                self.add_guarded(cond, &[-s[i - 1][j], s[i][j]]);
+            }
+            self.add_guarded(cond, &[-lits[i], -s[i - 1][k - 1]]);
+        }
+        self.add_guarded(cond, &[-lits[n - 1], -s[n - 2][k - 1]]);
+    }
+    // when every literal in cond is true, at least k of lits must be true
+    pub fn at_least(&mut self, cond: &[Lit], lits: &[Lit], k: usize) {
+        let n = lits.len();
+        if k == 0 { return; }
+        if k > n {
+            self.add_guarded(cond, &[]); // can't be satisfied, so cond itself must fail
+        }
+        else if k == 1 {
+            self.add_guarded(cond, lits);
+        }
+        else {
+            let negated: Vec<Lit> = lits.iter().map(|x| -x).collect();
+            self.at_most(cond, &negated, n - k);
+        }
+    }
+    pub fn exactly(&mut self, lits: &[Lit], k: usize) {
+        self.at_least(&[], lits, k);
+        self.at_most(&[], lits, k);
+    }
+    // requires the assignment of lits (first literal most significant, true above false) to be lexicographically
+    // no smaller than its image under perm, i.e. (lits[perm[0]], lits[perm[1]], ...).
+    // with perms from a symmetry group of the formula, every orbit keeps at least its lex-leader.
+    pub fn lex_leader(&mut self, lits: &[Lit], perm: &[usize]) {
+        let moved: Vec<usize> = (0..lits.len()).filter(|&u| perm[u] != u).collect();
+        let mut equal: Vec<Lit> = vec![]; // holds a literal meaning all previous positions were equal (empty meaning trivially true)
+        for (i, &u) in moved.iter().enumerate() {
+            let (a, b) = (lits[u], lits[perm[u]]);
+            self.add_guarded(&equal, &[a, -b]);
+            if i + 1 < moved.len() {
+                let next = self.new_var();
+                self.add_guarded(&equal, &[-a, -b, next]);
+                self.add_guarded(&equal, &[a, b, next]);
+                equal = vec![next];
+            }
+        }
+    }
+
+    pub fn write_dimacs(&self, f: &mut dyn Write) -> io::Result<()> {
+        for c in self.comments.iter() {
+            writeln!(f, "c {}", c)?;
+        }
+        writeln!(f, "p cnf {} {}", self.vars, self.clauses.len())?;
+        for clause in self.clauses.iter() {
+            for x in clause {
+                write!(f, "{} ", x)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ModelReadError {
+    Io(io::Error),
+    InvalidFormat(String),
+    MissingStatus,
+}
+impl fmt::Display for ModelReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelReadError::Io(e) => write!(f, "{}", e),
+            ModelReadError::InvalidFormat(msg) => write!(f, "{}", msg),
+            ModelReadError::MissingStatus => write!(f, "no satisfiability status or model found"),
+        }
+    }
+}
+// reads the output of a sat solver (competition "s"/"v" lines or minisat-style "SAT" followed by literals).
+// returns the true variables of the model, or None if the solver reported unsatisfiable.
+pub fn read_model<R: BufRead>(f: R) -> Result<Option<Vec<bool>>, ModelReadError> {
+    let mut status = None;
+    let mut model: Vec<bool> = vec![];
+    for line in f.lines() {
+        let line = line.map_err(ModelReadError::Io)?;
+        let line = line.trim();
+        let lits = match line.split_whitespace().next() {
+            None | Some("c") => continue,
+            Some("s") => {
+                status = Some(!line.contains("UNSAT"));
+                continue;
+            }
+            Some("SAT") | Some("SATISFIABLE") => { status = Some(true); continue; }
+            Some("UNSAT") | Some("UNSATISFIABLE") => { status = Some(false); continue; }
+            Some("v") => &line[1..],
+            Some(_) => line,
+        };
+        for tok in lits.split_whitespace() {
+            let x = match tok.parse::<Lit>() {
+                Ok(x) => x,
+                Err(_) => return Err(ModelReadError::InvalidFormat(format!("failed to parse '{}' as a literal", tok))),
+            };
+            if x == 0 { continue; }
+            let v = x.unsigned_abs() as usize;
+            if model.len() < v {
+                model.resize(v, false);
+            }
+            model[v - 1] = x > 0;
+        }
+    }
+    match status {
+        Some(true) => Ok(Some(model)),
+        Some(false) => Ok(None),
+        None if !model.is_empty() => Ok(Some(model)), // some solvers just print the model
+        None => Err(ModelReadError::MissingStatus),
+    }
+}
+
+// a locating code problem to encode: var_of maps each point to its detector variable (several points may share one),
+// and codes lists the points whose locating codes must be valid along with their (sorted) neighborhoods.
+pub type Code = (usize, Vec<usize>);
+pub struct CodeProblem<'a> {
+    pub var_of: &'a [Lit],
+    pub codes: &'a [Code],
+}
+
+// implemented by code sets which can be expressed as a cnf formula over the detector variables
+pub trait Encode: codesets::Set {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem);
+}
+
+fn sym_diff(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut res = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] { res.push(a[i]); i += 1; }
+        else if a[i] > b[j] { res.push(b[j]); j += 1; }
+        else { i += 1; j += 1; }
+    }
+    res.extend_from_slice(&a[i..]);
+    res.extend_from_slice(&b[j..]);
+    res
+}
+fn minus(a: &[usize], b: &[usize]) -> Vec<usize> {
+    a.iter().copied().filter(|x| b.binary_search(x).is_err()).collect()
+}
+
+// a cardinality requirement after substituting the points whose detector status is assumed
+enum Req {
+    Satisfied,
+    Vacuous, // the assumptions contradict each other, so there's nothing to require
+    Need(Vec<Lit>, usize),
+}
+impl Req {
+    // at least k of the given points must be detectors, assuming the points in fixed have the given status
+    fn new(problem: &CodeProblem, fixed: &[(usize, bool)], points: &[usize], k: usize) -> Self {
+        let status_of = |v: Lit| {
+            let mut res = None;
+            for &(p, s) in fixed {
+                if problem.var_of[p] == v {
+                    if res == Some(!s) { return Err(()); }
+                    res = Some(s);
+                }
+            }
+            Ok(res)
+        };
+        for &(p, _) in fixed {
+            if status_of(problem.var_of[p]).is_err() {
+                return Req::Vacuous;
+            }
+        }
+
+        let mut k = k;
+        let mut lits = Vec::with_capacity(points.len());
+        for &p in points {
+            let v = problem.var_of[p];
+            match status_of(v).unwrap() {
+                Some(true) => k = k.saturating_sub(1),
+                Some(false) => (),
+                None => lits.push(v),
+            }
+        }
+        if k == 0 { Req::Satisfied } else { Req::Need(lits, k) }
+    }
+}
+fn cond_of(problem: &CodeProblem, fixed: &[(usize, bool)]) -> Vec<Lit> {
+    fixed.iter().map(|&(p, s)| if s { problem.var_of[p] } else { -problem.var_of[p] }).collect()
+}
+
+// when the points in fixed have the given status, at least k of points must be detectors
+fn require(cnf: &mut Cnf, problem: &CodeProblem, fixed: &[(usize, bool)], points: &[usize], k: usize) {
+    if let Req::Need(lits, k) = Req::new(problem, fixed, points, k) {
+        cnf.at_least(&cond_of(problem, fixed), &lits, k);
+    }
+}
+// when the points in fixed have the given status, at most k of points may be detectors
+fn forbid_more(cnf: &mut Cnf, problem: &CodeProblem, fixed: &[(usize, bool)], points: &[usize], k: usize) {
+    let mut trues = 0;
+    let mut lits = Vec::with_capacity(points.len());
+    for &p in points {
+        match fixed.iter().find(|x| problem.var_of[x.0] == problem.var_of[p]) {
+            Some(&(_, true)) => trues += 1,
+            Some(&(_, false)) => (),
+            None => lits.push(problem.var_of[p]),
+        }
+    }
+    let cond = cond_of(problem, fixed);
+    if trues > k { cnf.at_least(&cond, &[], 1); } // can't be satisfied, so cond itself must fail
+    else { cnf.at_most(&cond, &lits, k - trues); }
+}
+// when the points in fixed have the given status, at least one of the two requirements must hold
+fn require_either(cnf: &mut Cnf, problem: &CodeProblem, fixed: &[(usize, bool)], a: (&[usize], usize), b: (&[usize], usize)) {
+    let ra = Req::new(problem, fixed, a.0, a.1);
+    let rb = Req::new(problem, fixed, b.0, b.1);
+    let cond = cond_of(problem, fixed);
+    match (ra, rb) {
+        (Req::Vacuous, _) | (_, Req::Vacuous) | (Req::Satisfied, _) | (_, Req::Satisfied) => (),
+        (Req::Need(la, ka), Req::Need(lb, kb)) => {
+            if ka > la.len() { cnf.at_least(&cond, &lb, kb); }
+            else if kb > lb.len() { cnf.at_least(&cond, &la, ka); }
+            else {
+                // introduce a selector for which requirement we're going to satisfy
+                let y = cnf.new_var();
+                let mut cond_a = cond.clone();
+                cond_a.push(y);
+                cnf.at_least(&cond_a, &la, ka);
+                let mut cond_b = cond;
+                cond_b.push(-y);
+                cnf.at_least(&cond_b, &lb, kb);
+            }
+        }
+    }
+}
+
+// pairs of codes whose neighborhoods are disjoint and don't contain one another are always distinguished by the
+// domination requirements of every set type, so we only need to generate pair constraints for the overlapping ones
+fn interacting_pairs<'a>(problem: &'a CodeProblem) -> impl Iterator<Item = (&'a Code, &'a Code)> {
+    problem.codes.iter().enumerate().flat_map(move |(i, a)| problem.codes[i + 1..].iter().map(move |b| (a, b)))
+        .filter(|(a, b)| {
+            a.1.binary_search(&b.0).is_ok() || b.1.binary_search(&a.0).is_ok() || util::count_equal(&a.1, &b.1) != 0
+        })
+}
+
+impl<T> Encode for codesets::DOM<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (_, n) in problem.codes {
+            require(cnf, problem, &[], n, 1);
+        }
+    }
+}
+impl<T> Encode for codesets::EDOM<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (_, n) in problem.codes {
+            require(cnf, problem, &[], n, 1);
+            forbid_more(cnf, problem, &[], n, 1);
+        }
+    }
+}
+
+// shared encoding for the set types which require every code to be k-dominated and every pair to be d-distinguished
+fn encode_regular(cnf: &mut Cnf, problem: &CodeProblem, k: usize, d: usize) {
+    for (_, n) in problem.codes {
+        require(cnf, problem, &[], n, k);
+    }
+    for (a, b) in interacting_pairs(problem) {
+        require(cnf, problem, &[], &sym_diff(&a.1, &b.1), d);
+    }
+}
+impl<T> Encode for codesets::OLD<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        encode_regular(cnf, problem, 1, 1);
+    }
+}
+impl<T> Encode for codesets::RED<T> where T: Ord + Hash + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        encode_regular(cnf, problem, 2, 2);
+    }
+}
+impl<T> Encode for codesets::RSP<T> where T: Ord + Hash + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        encode_regular(cnf, problem, 3, 2);
+    }
+}
+impl<T> Encode for codesets::ERR<T> where T: Ord + Hash + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        encode_regular(cnf, problem, 3, 3);
+    }
+}
+impl<T> Encode for codesets::DET<T> where T: Ord + Hash + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (_, n) in problem.codes {
+            require(cnf, problem, &[], n, 2);
+        }
+        for (a, b) in interacting_pairs(problem) {
+            require_either(cnf, problem, &[], (&minus(&a.1, &b.1), 2), (&minus(&b.1, &a.1), 2));
+        }
+    }
+}
+
+impl<T> Encode for codesets::LD<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (p, n) in problem.codes {
+            require(cnf, problem, &[(*p, false)], n, 1);
+        }
+        for (a, b) in interacting_pairs(problem) {
+            require(cnf, problem, &[(a.0, false), (b.0, false)], &sym_diff(&a.1, &b.1), 1);
+        }
+    }
+}
+impl<T> Encode for codesets::REDLD<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (p, n) in problem.codes {
+            require(cnf, problem, &[(*p, true)], n, 1);
+            require(cnf, problem, &[(*p, false)], n, 2);
+        }
+        for (a, b) in interacting_pairs(problem) {
+            let diff = sym_diff(&a.1, &b.1);
+            // detector/non-detector pairs must be distinguished by something other than the detector
+            for &(d, n) in &[(a, b), (b, a)] {
+                let k = 1 + n.1.binary_search(&d.0).is_ok() as usize;
+                require(cnf, problem, &[(d.0, true), (n.0, false)], &diff, k);
+            }
+            require(cnf, problem, &[(a.0, false), (b.0, false)], &diff, 2);
+        }
+    }
+}
+impl<T> Encode for codesets::DETLD<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (p, n) in problem.codes {
+            require(cnf, problem, &[(*p, true)], n, 1);
+            require(cnf, problem, &[(*p, false)], n, 2);
+        }
+        for (a, b) in interacting_pairs(problem) {
+            let a_only = minus(&a.1, &b.1);
+            let b_only = minus(&b.1, &a.1);
+            require(cnf, problem, &[(a.0, true), (b.0, true)], &sym_diff(&a.1, &b.1), 1);
+            // detector/non-detector pairs must be asymmetrically sharp distinguished
+            require_either(cnf, problem, &[(a.0, true), (b.0, false)], (&b_only, 2), (&a_only, 1));
+            require_either(cnf, problem, &[(b.0, true), (a.0, false)], (&a_only, 2), (&b_only, 1));
+            require_either(cnf, problem, &[(a.0, false), (b.0, false)], (&a_only, 2), (&b_only, 2));
+        }
+    }
+}
+impl<T> Encode for codesets::ERRLD<T> where T: Ord + Default + Clone + Debug {
+    fn encode(cnf: &mut Cnf, problem: &CodeProblem) {
+        for (p, n) in problem.codes {
+            require(cnf, problem, &[(*p, true)], n, 2);
+            require(cnf, problem, &[(*p, false)], n, 3);
+        }
+        for (a, b) in interacting_pairs(problem) {
+            let diff = sym_diff(&a.1, &b.1);
+            // detectors must be distinguished by something other than each other
+            let k = 1 + a.1.binary_search(&b.0).is_ok() as usize + b.1.binary_search(&a.0).is_ok() as usize;
+            require(cnf, problem, &[(a.0, true), (b.0, true)], &diff, k);
+            for &(d, n) in &[(a, b), (b, a)] {
+                let k = 2 + n.1.binary_search(&d.0).is_ok() as usize;
+                require(cnf, problem, &[(d.0, true), (n.0, false)], &diff, k);
+            }
+            require(cnf, problem, &[(a.0, false), (b.0, false)], &diff, 3);
+        }
+    }
+}
+
+#[cfg(test)]
+fn brute_force_sat(cnf: &Cnf, assignment: &mut [Option<bool>]) -> bool {
+    // simple dpll for checking small formulas in tests
+    loop {
+        let mut unit = None;
+        for clause in cnf.clauses() {
+            let mut open = None;
+            let mut open_count = 0;
+            let mut sat = false;
+            for &x in clause {
+                match assignment[x.unsigned_abs() as usize - 1] {
+                    Some(v) if v == (x > 0) => { sat = true; break; }
+                    Some(_) => (),
+                    None => { open = Some(x); open_count += 1; }
+                }
+            }
+            if sat { continue; }
+            match open_count {
+                0 => return false,
+                1 => { unit = open; break; }
+                _ => (),
+            }
+        }
+        match unit {
+            Some(x) => assignment[x.unsigned_abs() as usize - 1] = Some(x > 0),
+            None => break,
+        }
+    }
+    match assignment.iter().position(Option::is_none) {
+        None => true,
+        Some(v) => {
+            for &val in &[true, false] {
+                let mut a = assignment.to_vec();
+                a[v] = Some(val);
+                if brute_force_sat(cnf, &mut a) { return true; }
+            }
+            false
+        }
+    }
+}
+
+#[test]
+fn test_cardinality() {
+    for n in 0..6 {
+        for k in 0..=n + 1 {
+            let lits: Vec<Lit> = (1..=n as Lit).collect();
+            let mut most = Cnf::with_vars(n);
+            most.at_most(&[], &lits, k);
+            let mut least = Cnf::with_vars(n);
+            least.at_least(&[], &lits, k);
+            for bits in 0..(1 << n) {
+                let count = (0..n).filter(|i| bits & (1 << i) != 0).count();
+                let fix = |cnf: &Cnf| {
+                    let mut a = vec![None; cnf.var_count()];
+                    for (i, x) in a.iter_mut().enumerate().take(n) { *x = Some(bits & (1 << i) != 0); }
+                    a
+                };
+                assert_eq!(brute_force_sat(&most, &mut fix(&most)), count <= k);
+                assert_eq!(brute_force_sat(&least, &mut fix(&least)), count >= k);
+            }
+        }
+    }
+}
+#[test]
+fn test_lex_leader() {
+    // swapping the first and last of three variables: models must have x1 >= x3
+    let mut cnf = Cnf::with_vars(3);
+    cnf.lex_leader(&[1, 2, 3], &[2, 1, 0]);
+    for bits in 0..8u32 {
+        let mut model: Vec<Option<bool>> = (0..3).map(|i| Some(bits & (1 << i) != 0)).collect();
+        model.resize(cnf.var_count(), None);
+        assert_eq!(brute_force_sat(&cnf, &mut model), bits & 1 != 0 || bits & 4 == 0);
+    }
+
+    // rotating four variables (written x1 x2 x3 x4): 1100 and 1010 survive, 0110, 0011 and 0101 don't.
+    // 1001 also survives, since it's only beaten by rotating the other way.
+    let mut cnf = Cnf::with_vars(4);
+    cnf.lex_leader(&[1, 2, 3, 4], &[1, 2, 3, 0]);
+    for &(set, expected) in &[(0b0011, true), (0b0110, false), (0b1100, false), (0b1001, true), (0b0101, true), (0b1010, false)] {
+        let mut model: Vec<Option<bool>> = (0..4).map(|i| Some(set & (1 << i) != 0)).collect();
+        model.resize(cnf.var_count(), None);
+        assert_eq!(brute_force_sat(&cnf, &mut model), expected, "{:04b}", set);
+    }
+}
+
+#[test]
+fn test_read_model() {
+    let m = read_model("c comment\ns SATISFIABLE\nv 1 -2 3\nv -4 0\n".as_bytes()).unwrap();
+    assert_eq!(m, Some(vec![true, false, true, false]));
+    let m = read_model("SAT\n-1 2 0\n".as_bytes()).unwrap();
+    assert_eq!(m, Some(vec![false, true]));
+    assert_eq!(read_model("s UNSATISFIABLE\n".as_bytes()).unwrap(), None);
+    assert_eq!(read_model("UNSAT\n".as_bytes()).unwrap(), None);
+    assert!(read_model("".as_bytes()).is_err());
+    assert!(read_model("v 1 x 0".as_bytes()).is_err());
+}
+
+#[cfg(test)]
+fn check_encoding<S: Encode<Item = usize>>(adj: &[Vec<usize>]) {
+    use crate::codesets::LOC;
+
+    let n = adj.len();
+    let var_of: Vec<Lit> = (1..=n as Lit).collect();
+    let codes: Vec<Code> = adj.iter().cloned().enumerate().collect();
+    let problem = CodeProblem { var_of: &var_of, codes: &codes };
+    let mut cnf = Cnf::with_vars(n);
+    S::encode(&mut cnf, &problem);
+
+    for bits in 0..(1usize << n) {
+        let is_det = |p: usize| bits & (1 << p) != 0;
+        let mut set = S::default();
+        let valid = (0..n).all(|p| {
+            let code = adj[p].iter().copied().filter(|&x| is_det(x)).collect();
+            set.add(S::LocatingCode::new(p, is_det(p), code))
+        });
+        let mut a = vec![None; cnf.var_count()];
+        for (p, x) in a.iter_mut().enumerate().take(n) { *x = Some(is_det(p)); }
+        assert_eq!(brute_force_sat(&cnf, &mut a), valid, "{:?} {:b}", adj, bits);
+    }
+}
+#[test]
+fn test_encodings() {
+    let graphs: Vec<Vec<Vec<usize>>> = vec![
+        vec![vec![1, 2], vec![0, 2], vec![0, 1, 3], vec![2, 4], vec![3, 5], vec![4]],
+        vec![vec![1, 4], vec![0, 2], vec![1, 3], vec![2, 4, 5], vec![0, 3, 5], vec![3, 4]],
+        vec![vec![1, 2, 3], vec![0, 2, 4], vec![0, 1, 5], vec![0, 4, 5], vec![1, 3, 5], vec![2, 3, 4]],
+    ];
+    for open in graphs.iter() {
+        let closed: Vec<Vec<usize>> = open.iter().enumerate().map(|(i, a)| {
+            let mut a = a.clone();
+            a.push(i);
+            a.sort();
+            a
+        }).collect();
+        for adj in &[open, &closed] {
+            check_encoding::<codesets::DOM<usize>>(adj);
+            check_encoding::<codesets::EDOM<usize>>(adj);
+            check_encoding::<codesets::OLD<usize>>(adj);
+            check_encoding::<codesets::RED<usize>>(adj);
+            check_encoding::<codesets::DET<usize>>(adj);
+            check_encoding::<codesets::RSP<usize>>(adj);
+            check_encoding::<codesets::ERR<usize>>(adj);
+        }
+        check_encoding::<codesets::LD<usize>>(open);
+        check_encoding::<codesets::REDLD<usize>>(open);
+        check_encoding::<codesets::DETLD<usize>>(open);
+        check_encoding::<codesets::ERRLD<usize>>(open);
+    }
+}
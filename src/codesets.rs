@@ -1,5 +1,6 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
+use std::hash::Hash;
 
 use crate::util;
 
@@ -16,6 +17,26 @@ pub trait Set: Default + Clone + Debug + PartialEq {
     fn clear(&mut self);
     fn can_add(&self, loc: &Self::LocatingCode) -> bool;
     fn add(&mut self, loc: Self::LocatingCode) -> bool;
+
+    // like add, but the code can be taken back out with pop (don't mix the two between calls to clear)
+    fn push(&mut self, loc: Self::LocatingCode) -> bool;
+    // removes the most recent code that was successfully pushed
+    fn pop(&mut self);
+
+    // the (inclusive) range of code lengths that meets the domination requirements for a detector or non-detector
+    fn code_len_bounds(is_detector: bool) -> (usize, usize);
+    // checks if a partially-decided vertex can no longer be given a valid code, no matter how its undecided neighbors (or itself, if is_detector is None) turn out.
+    // detectors is the number of decided detectors in its code and undecided is the most that could still be added.
+    fn is_doomed(is_detector: Option<bool>, detectors: usize, undecided: usize) -> bool {
+        let fits = |d| {
+            let (low, high) = Self::code_len_bounds(d);
+            detectors <= high && detectors + undecided >= low
+        };
+        match is_detector {
+            Some(d) => !fits(d),
+            None => !fits(true) && !fits(false),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -103,6 +124,13 @@ where T: Ord + Default + Clone + Debug
     fn add(&mut self, loc: Self::LocatingCode) -> bool {
         self.can_add(&loc)
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        self.add(loc)
+    }
+    fn pop(&mut self) {}
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (1, usize::MAX)
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -120,6 +148,13 @@ where T: Ord + Default + Clone + Debug
     fn add(&mut self, loc: Self::LocatingCode) -> bool {
         self.can_add(&loc)
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        self.add(loc)
+    }
+    fn pop(&mut self) {}
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (1, 1)
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -127,6 +162,7 @@ pub struct LD<T>
 where T: Ord
 {
     codes: BTreeSet<Vec<T>>,
+    pushed: Vec<Option<Vec<T>>>, // the code inserted by each push (none for detectors)
 }
 impl<T> Set for LD<T>
 where T: Ord + Default + Clone + Debug
@@ -135,6 +171,7 @@ where T: Ord + Default + Clone + Debug
     type LocatingCode = LDLOC<T>;
     
     fn clear(&mut self) {
+        self.pushed.clear();
         self.codes.clear();
     }
     fn can_add(&self, loc: &Self::LocatingCode) -> bool {
@@ -149,6 +186,27 @@ where T: Ord + Default + Clone + Debug
         }
         else { false }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            if loc.is_detector {
+                self.pushed.push(None);
+            }
+            else {
+                self.codes.insert(loc.code.clone());
+                self.pushed.push(Some(loc.code));
+            }
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if let Some(code) = self.pushed.pop().unwrap() {
+            self.codes.remove(&code);
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (0, usize::MAX) } else { (1, usize::MAX) }
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -157,6 +215,7 @@ where T: Ord
 {
     detector_codes: Vec<(T, Vec<T>)>,
     non_detector_codes: Vec<Vec<T>>,
+    pushed: Vec<bool>, // which list each push went to (true for detectors)
 }
 impl<T> Set for REDLD<T>
 where T: Ord + Default + Clone + Debug
@@ -165,6 +224,7 @@ where T: Ord + Default + Clone + Debug
     type LocatingCode = REDLDLOC<T>;
 
     fn clear(&mut self) {
+        self.pushed.clear();
         self.non_detector_codes.clear();
         self.detector_codes.clear();
     }
@@ -224,6 +284,25 @@ where T: Ord + Default + Clone + Debug
             false
         }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        let is_detector = loc.is_detector;
+        if self.add(loc) {
+            self.pushed.push(is_detector);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if self.pushed.pop().unwrap() {
+            self.detector_codes.pop();
+        }
+        else {
+            self.non_detector_codes.pop();
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (1, usize::MAX) } else { (2, usize::MAX) }
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -232,6 +311,7 @@ where T: Ord
 {
     detector_codes: BTreeSet<Vec<T>>,
     non_detector_codes: Vec<Vec<T>>,
+    pushed: Vec<Option<Vec<T>>>, // the detector code inserted by each push (none for non-detectors)
 }
 impl<T> Set for DETLD<T>
 where T: Ord + Default + Clone + Debug
@@ -240,6 +320,7 @@ where T: Ord + Default + Clone + Debug
     type LocatingCode = LDLOC<T>;
 
     fn clear(&mut self) {
+        self.pushed.clear();
         self.detector_codes.clear();
         self.non_detector_codes.clear();
     }
@@ -297,6 +378,29 @@ where T: Ord + Default + Clone + Debug
             false
         }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            if loc.is_detector {
+                self.detector_codes.insert(loc.code.clone());
+                self.pushed.push(Some(loc.code));
+            }
+            else {
+                self.non_detector_codes.push(loc.code);
+                self.pushed.push(None);
+            }
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        match self.pushed.pop().unwrap() {
+            Some(code) => { self.detector_codes.remove(&code); }
+            None => { self.non_detector_codes.pop(); }
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (1, usize::MAX) } else { (2, usize::MAX) }
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -305,6 +409,7 @@ where T: Ord
 {
     detector_codes: Vec<(T, Vec<T>)>,
     non_detector_codes: Vec<Vec<T>>,
+    pushed: Vec<bool>, // which list each push went to (true for detectors)
 }
 impl<T> Set for ERRLD<T>
 where T: Ord + Default + Clone + Debug
@@ -313,6 +418,7 @@ where T: Ord + Default + Clone + Debug
     type LocatingCode = REDLDLOC<T>;
 
     fn clear(&mut self) {
+        self.pushed.clear();
         self.non_detector_codes.clear();
         self.detector_codes.clear();
     }
@@ -386,6 +492,25 @@ where T: Ord + Default + Clone + Debug
             false
         }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        let is_detector = loc.is_detector;
+        if self.add(loc) {
+            self.pushed.push(is_detector);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        if self.pushed.pop().unwrap() {
+            self.detector_codes.pop();
+        }
+        else {
+            self.non_detector_codes.pop();
+        }
+    }
+    fn code_len_bounds(is_detector: bool) -> (usize, usize) {
+        if is_detector { (2, usize::MAX) } else { (3, usize::MAX) }
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -393,6 +518,7 @@ pub struct OLD<T>
 where T: Ord
 {
     codes: BTreeSet<Vec<T>>,
+    pushed: Vec<Vec<T>>,
 }
 impl<T> Set for OLD<T>
 where T: Ord + Default + Clone + Debug
@@ -401,6 +527,7 @@ where T: Ord + Default + Clone + Debug
     type LocatingCode = RegularLOC<T>;
 
     fn clear(&mut self) {
+        self.pushed.clear();
         self.codes.clear();
     }
     fn can_add(&self, loc: &Self::LocatingCode) -> bool {
@@ -413,14 +540,168 @@ where T: Ord + Default + Clone + Debug
         }
         else { false }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        if self.can_add(&loc) {
+            self.codes.insert(loc.code.clone());
+            self.pushed.push(loc.code);
+            true
+        }
+        else { false }
+    }
+    fn pop(&mut self) {
+        let code = self.pushed.pop().unwrap();
+        self.codes.remove(&code);
+    }
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (1, usize::MAX)
+    }
 }
 
+// every way of deleting k elements from a code (which keeps it sorted)
+fn deletions<T: Clone>(code: &[T], k: usize) -> Vec<Vec<T>> {
+    if k == 0 { return vec![code.to_vec()]; }
+    let mut res = vec![];
+    for i in 0..code.len() {
+        // delete element i and k - 1 elements after it
+        for rest in deletions(&code[i + 1..], k - 1) {
+            let mut key = code[..i].to_vec();
+            key.extend(rest);
+            res.push(key);
+        }
+    }
+    res
+}
+
+// indexes codes by every way of deleting a few of their elements, which lets us find codes close to a new one without comparing against all of them.
+// deleting r elements from A and a from B can give the same key only if |A \ B| <= r and |B \ A| <= a,
+// so checking every small (r, a) pair finds exactly the codes within some distance of a new one.
 #[derive(Default, Clone, Debug, PartialEq)]
-pub struct RED<T> {
+struct CodeIndex<T>
+where T: Eq + Hash
+{
+    levels: Vec<HashMap<Vec<T>, usize>>, // levels[k] counts the keys made by deleting k elements from each code
+}
+impl<T> CodeIndex<T>
+where T: Eq + Hash + Clone
+{
+    // indexes a code with up to depth deletions (the largest a that will be passed to any_close)
+    fn insert(&mut self, code: &[T], depth: usize) {
+        if self.levels.len() <= depth {
+            self.levels.resize_with(depth + 1, Default::default);
+        }
+        for k in 0..=depth {
+            for key in deletions(code, k) {
+                *self.levels[k].entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+    fn remove(&mut self, code: &[T], depth: usize) {
+        for k in 0..=depth {
+            for key in deletions(code, k) {
+                let count = self.levels[k].get_mut(&key).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.levels[k].remove(&key);
+                }
+            }
+        }
+    }
+    fn clear(&mut self) {
+        self.levels.clear();
+    }
+    // checks if deleting r elements from the code and a from some indexed code can make them equal, for any of the given (r, a) pairs
+    fn any_close(&self, code: &[T], pairs: &[(usize, usize)]) -> bool {
+        pairs.iter().any(|&(r, a)| match self.levels.get(a) {
+            Some(level) => deletions(code, r).iter().any(|key| level.contains_key(key)),
+            None => false,
+        })
+    }
+}
+#[test]
+fn test_code_index() {
+    assert_eq!(deletions(&[1, 2, 3], 0), vec![vec![1, 2, 3]]);
+    assert_eq!(deletions(&[1, 2, 3], 1), vec![vec![2, 3], vec![1, 3], vec![1, 2]]);
+    assert_eq!(deletions(&[1, 2, 3], 2), vec![vec![3], vec![2], vec![1]]);
+    assert!(deletions(&[1, 2], 3).is_empty());
+
+    // compare against plain set differences on every pair of subsets of 0..6
+    let subsets: Vec<Vec<u32>> = (0..64u32).map(|s| (0..6).filter(|i| s & (1 << i) != 0).collect()).collect();
+    for b in &subsets {
+        let mut index = CodeIndex::default();
+        index.insert(b, 2);
+        for a in &subsets {
+            let r = a.iter().filter(|x| !b.contains(x)).count();
+            let d = b.iter().filter(|x| !a.contains(x)).count();
+            let common = a.len() - r;
+            for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1), (2, 0), (0, 2)] {
+                // the rest of the deletions have to be common elements, deleted from both
+                let expected = r <= x && d <= y && x - r == y - d && x - r <= common;
+                assert_eq!(index.any_close(a, &[(x, y)]), expected);
+            }
+        }
+        index.remove(b, 2);
+        assert!(index.levels.iter().all(|level| level.is_empty()));
+    }
+}
+
+// codes for the sets that only need to find nearby codes. small sets (like the ones in theo searches) are faster to scan directly,
+// so the index is only built once there are enough codes to make it worth the hashing.
+const INDEX_THRESHOLD: usize = 64;
+
+#[derive(Default, Clone, Debug, PartialEq)]
+struct IndexedCodes<T>
+where T: Eq + Hash
+{
     codes: Vec<Vec<T>>,
+    index: CodeIndex<T>, // empty until there are at least INDEX_THRESHOLD codes
+}
+impl<T> IndexedCodes<T>
+where T: Eq + Hash + Clone
+{
+    fn clear(&mut self) {
+        self.codes.clear();
+        self.index.clear();
+    }
+    // checks if any code is close to the new one, where close is the plain pairwise test and pairs is the equivalent for the index
+    fn any_close(&self, code: &[T], pairs: &[(usize, usize)], close: fn(&[T], &[T]) -> bool) -> bool {
+        if self.codes.len() < INDEX_THRESHOLD {
+            self.codes.iter().any(|other| close(code, other))
+        }
+        else {
+            self.index.any_close(code, pairs)
+        }
+    }
+    fn push(&mut self, code: Vec<T>, depth: usize) {
+        self.codes.push(code);
+        if self.codes.len() == INDEX_THRESHOLD {
+            for code in &self.codes {
+                self.index.insert(code, depth);
+            }
+        }
+        else if self.codes.len() > INDEX_THRESHOLD {
+            self.index.insert(self.codes.last().unwrap(), depth);
+        }
+    }
+    fn pop(&mut self, depth: usize) {
+        if let Some(code) = self.codes.pop() {
+            if self.codes.len() >= INDEX_THRESHOLD {
+                self.index.remove(&code, depth);
+            }
+            else if self.codes.len() + 1 == INDEX_THRESHOLD {
+                self.index.clear();
+            }
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct RED<T>
+where T: Ord + Hash
+{
+    codes: IndexedCodes<T>,
 }
 impl<T> Set for RED<T>
-where T: Ord + Default + Clone + Debug
+where T: Ord + Hash + Default + Clone + Debug
 {
     type Item = T;
     type LocatingCode = RegularLOC<T>;
@@ -430,28 +711,35 @@ where T: Ord + Default + Clone + Debug
     }
     fn can_add(&self, loc: &Self::LocatingCode) -> bool {
         if loc.code.len() < 2 { return false; }
-        for other in &self.codes {
-            if util::symmetric_diff(other, &loc.code) < 2 {
-                return false;
-            }
-        }
-        true
+        // must be 2-distinguished: no other code within symmetric difference 1
+        !self.codes.any_close(&loc.code, &[(0, 0), (1, 0), (0, 1)], |a, b| util::symmetric_diff(a, b) < 2)
     }
     fn add(&mut self, loc: Self::LocatingCode) -> bool {
         if self.can_add(&loc) {
-            self.codes.push(loc.code);
+            self.codes.push(loc.code, 1);
             true
         }
         else { false }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        self.add(loc)
+    }
+    fn pop(&mut self) {
+        self.codes.pop(1);
+    }
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (2, usize::MAX)
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
-pub struct DET<T> {
-    codes: Vec<Vec<T>>,
+pub struct DET<T>
+where T: Ord + Hash
+{
+    codes: IndexedCodes<T>,
 }
 impl<T> Set for DET<T>
-where T: Ord + Default + Clone + Debug
+where T: Ord + Hash + Default + Clone + Debug
 {
     type Item = T;
     type LocatingCode = RegularLOC<T>;
@@ -461,28 +749,35 @@ where T: Ord + Default + Clone + Debug
     }
     fn can_add(&self, loc: &Self::LocatingCode) -> bool {
         if loc.code.len() < 2 { return false; }
-        for other in &self.codes {
-            if util::max_diff(other, &loc.code) < 2 {
-                return false;
-            }
-        }
-        true
+        // must be 2-sharp distinguished: no other code with both differences at most 1
+        !self.codes.any_close(&loc.code, &[(0, 0), (1, 0), (0, 1), (1, 1)], |a, b| util::max_diff(a, b) < 2)
     }
     fn add(&mut self, loc: Self::LocatingCode) -> bool {
         if self.can_add(&loc) {
-            self.codes.push(loc.code);
+            self.codes.push(loc.code, 1);
             true
         }
         else { false }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        self.add(loc)
+    }
+    fn pop(&mut self) {
+        self.codes.pop(1);
+    }
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (2, usize::MAX)
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
-pub struct RSP<T> {
-    codes: Vec<Vec<T>>,
+pub struct RSP<T>
+where T: Ord + Hash
+{
+    codes: IndexedCodes<T>,
 }
 impl<T> Set for RSP<T>
-where T: Ord + Default + Clone + Debug
+where T: Ord + Hash + Default + Clone + Debug
 {
     type Item = T;
     type LocatingCode = RegularLOC<T>;
@@ -492,29 +787,35 @@ where T: Ord + Default + Clone + Debug
     }
     fn can_add(&self, loc: &Self::LocatingCode) -> bool {
         if loc.code.len() < 3 { return false; }
-        for other in &self.codes {
-            let equal = util::count_equal(other, &loc.code);
-            if other.len() + loc.code.len() - 2 * equal < 2 {
-                return false;
-            }
-        }
-        true
+        // must be 2-distinguished: no other code within symmetric difference 1
+        !self.codes.any_close(&loc.code, &[(0, 0), (1, 0), (0, 1)], |a, b| util::symmetric_diff(a, b) < 2)
     }
     fn add(&mut self, loc: Self::LocatingCode) -> bool {
         if self.can_add(&loc) {
-            self.codes.push(loc.code);
+            self.codes.push(loc.code, 1);
             true
         }
         else { false }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        self.add(loc)
+    }
+    fn pop(&mut self) {
+        self.codes.pop(1);
+    }
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (3, usize::MAX)
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
-pub struct ERR<T> {
-    codes: Vec<Vec<T>>,
+pub struct ERR<T>
+where T: Ord + Hash
+{
+    codes: IndexedCodes<T>,
 }
 impl<T> Set for ERR<T>
-where T: Ord + Default + Clone + Debug
+where T: Ord + Hash + Default + Clone + Debug
 {
     type Item = T;
     type LocatingCode = RegularLOC<T>;
@@ -524,21 +825,185 @@ where T: Ord + Default + Clone + Debug
     }
     fn can_add(&self, loc: &Self::LocatingCode) -> bool {
         if loc.code.len() < 3 { return false; }
-        for other in &self.codes {
-            let equal = util::count_equal(other, &loc.code);
-            if other.len() + loc.code.len() - 2 * equal < 3 {
-                return false;
-            }
-        }
-        true
+        // must be 3-distinguished: no other code within symmetric difference 2
+        !self.codes.any_close(&loc.code, &[(0, 0), (1, 0), (0, 1), (1, 1), (2, 0), (0, 2)], |a, b| util::symmetric_diff(a, b) < 3)
     }
     fn add(&mut self, loc: Self::LocatingCode) -> bool {
         if self.can_add(&loc) {
-            self.codes.push(loc.code);
+            self.codes.push(loc.code, 2);
             true
         }
         else { false }
     }
+    fn push(&mut self, loc: Self::LocatingCode) -> bool {
+        self.add(loc)
+    }
+    fn pop(&mut self) {
+        self.codes.pop(2);
+    }
+    fn code_len_bounds(_: bool) -> (usize, usize) {
+        (3, usize::MAX)
+    }
+}
+
+// the indexed sets must accept exactly the same codes as plain pairwise comparisons would (long enough runs to cross INDEX_THRESHOLD and back)
+#[cfg(test)]
+fn check_indexed<S: Set<Item = u32, LocatingCode = RegularLOC<u32>>>(dom: usize, distinct: fn(&[u32], &[u32]) -> bool) {
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut rand = move |n: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % n
+    };
+    for _ in 0..20 {
+        let mut s = S::default();
+        let mut codes: Vec<Vec<u32>> = vec![];
+        for _ in 0..2 {
+            let mut most = 0;
+            for _ in 0..400 {
+                if !codes.is_empty() && rand(16) == 0 {
+                    s.pop();
+                    codes.pop();
+                }
+                let code: Vec<u32> = (0..12).filter(|_| rand(2) == 0).collect();
+                let expected = code.len() >= dom && codes.iter().all(|other| distinct(&code, other));
+                assert_eq!(s.push(RegularLOC::new(0, false, code.clone())), expected);
+                if expected {
+                    codes.push(code);
+                }
+                most = most.max(codes.len());
+            }
+            assert!(most > INDEX_THRESHOLD);
+
+            // drop back down to the unindexed mode and make sure it still works after that
+            while codes.len() > INDEX_THRESHOLD / 2 {
+                s.pop();
+                codes.pop();
+            }
+        }
+    }
+}
+#[test]
+fn test_indexed_sets() {
+    check_indexed::<RED<u32>>(2, |a, b| util::symmetric_diff(a, b) >= 2);
+    check_indexed::<DET<u32>>(2, |a, b| util::max_diff(a, b) >= 2);
+    check_indexed::<RSP<u32>>(3, |a, b| util::symmetric_diff(a, b) >= 2);
+    check_indexed::<ERR<u32>>(3, |a, b| util::symmetric_diff(a, b) >= 3);
+}
+
+// checks a set incrementally while a search decides whether vertices 0, 1, 2, ... are detectors (in that order).
+// codes are pushed into the set as soon as they're fully decided, and vertices that can no longer be dominated properly
+// are caught even sooner, so partial solutions can be rejected long before the search reaches a leaf.
+pub struct PartialCodes<S: Set> {
+    codes: S,
+    labels: Vec<S::Item>,
+    neighborhoods: Vec<Option<Vec<usize>>>,
+    completed: Vec<Vec<usize>>, // the vertices whose codes become fully decided along with each vertex
+    watchers: Vec<Vec<usize>>, // the vertices whose codes depend on each vertex
+    pushed: Vec<usize>, // the number of codes pushed by each decision
+}
+impl<S: Set> PartialCodes<S>
+where S::Item: Clone
+{
+    // labels are the items to use for each vertex, and neighborhoods the (sorted) vertices that can be in its code.
+    // vertices with no neighborhood (e.g. ones whose codes depend on things outside the search) are never checked.
+    pub fn new(labels: Vec<S::Item>, neighborhoods: Vec<Option<Vec<usize>>>) -> Self {
+        let mut completed = vec![vec![]; labels.len()];
+        let mut watchers = vec![vec![]; labels.len()];
+        for (v, neighborhood) in neighborhoods.iter().enumerate() {
+            if let Some(neighborhood) = neighborhood {
+                completed[neighborhood.iter().copied().fold(v, usize::max)].push(v);
+                watchers[v].push(v);
+                for &u in neighborhood {
+                    if u != v {
+                        watchers[u].push(v);
+                    }
+                }
+            }
+        }
+        Self { codes: Default::default(), labels, neighborhoods, completed, watchers, pushed: vec![] }
+    }
+    fn is_doomed(&self, v: usize, decided: usize, is_detector: &[bool], remaining: usize) -> bool {
+        let (mut detectors, mut undecided) = (0, 0);
+        for &u in self.neighborhoods[v].as_ref().unwrap() {
+            if u >= decided {
+                undecided += 1;
+            }
+            else if is_detector[u] {
+                detectors += 1;
+            }
+        }
+        let own = if v < decided { Some(is_detector[v]) } else { None };
+        S::is_doomed(own, detectors, undecided.min(remaining))
+    }
+    // records the decision for vertex pos (is_detector must be up to date for 0..=pos), where remaining is how many more detectors can be added.
+    // returns false (recording nothing) if this dooms the partial solution, otherwise it needs to be undone with pop when backtracking.
+    pub fn push(&mut self, pos: usize, is_detector: &[bool], remaining: usize) -> bool {
+        if self.watchers[pos].iter().any(|&v| self.is_doomed(v, pos + 1, is_detector, remaining)) {
+            return false;
+        }
+        let mut count = 0;
+        for &v in &self.completed[pos] {
+            let code = self.neighborhoods[v].as_ref().unwrap().iter().filter(|&&u| is_detector[u]).map(|&u| self.labels[u].clone()).collect();
+            if !self.codes.push(S::LocatingCode::new(self.labels[v].clone(), is_detector[v], code)) {
+                for _ in 0..count {
+                    self.codes.pop();
+                }
+                return false;
+            }
+            count += 1;
+        }
+        self.pushed.push(count);
+        true
+    }
+    pub fn pop(&mut self) {
+        for _ in 0..self.pushed.pop().unwrap() {
+            self.codes.pop();
+        }
+    }
+}
+#[test]
+fn test_partial_codes() {
+    // a path 0-1-2-3: with closed neighborhoods, 0 is doomed as soon as 0 and 1 are both non-detectors
+    let closed = vec![Some(vec![0, 1]), Some(vec![0, 1, 2]), Some(vec![1, 2, 3]), Some(vec![2, 3])];
+    let mut p = PartialCodes::<DOM<usize>>::new((0..4).collect(), closed.clone());
+    let mut is_detector = vec![false; 4];
+    assert!(p.push(0, &is_detector, 2));
+    assert!(!p.push(1, &is_detector, 2));
+    is_detector[1] = true;
+    assert!(p.push(1, &is_detector, 2));
+    assert!(p.push(2, &is_detector, 1));
+    assert!(!p.push(3, &is_detector, 1)); // 3 only sees 2 and 3
+    p.pop();
+    p.pop();
+    // running out of detectors dooms vertices early as well
+    assert!(p.push(1, &[false, true, false, false], 0));
+    assert!(!p.push(2, &[false, true, false, false], 0));
+
+    // with open neighborhoods, detectors {1, 2} give 0 and 2 the same code, which is caught once 3 is decided
+    let open = vec![Some(vec![1]), Some(vec![0, 2]), Some(vec![1, 3]), Some(vec![2])];
+    let mut p = PartialCodes::<OLD<usize>>::new((0..4).collect(), open);
+    let is_detector = [false, true, true, false];
+    for pos in 0..3 {
+        assert!(p.push(pos, &is_detector, 2));
+    }
+    assert!(!p.push(3, &is_detector, 0));
+    assert_eq!(p.codes.codes.len(), 2);
+    p.pop();
+    p.pop();
+    assert_eq!(p.codes.codes.len(), 0);
+    assert!(p.codes.pushed.is_empty());
+
+    // ignored vertices are never checked
+    let mut p = PartialCodes::<DOM<usize>>::new((0..4).collect(), vec![None, closed[1].clone(), closed[2].clone(), closed[3].clone()]);
+    assert!(p.push(0, &[false; 4], 2));
+    assert!(p.push(1, &[false, false, true, false], 2));
+
+    // a vertex that could still go either way is only doomed if both options are
+    assert!(!REDLD::<usize>::is_doomed(None, 1, 0));
+    assert!(REDLD::<usize>::is_doomed(Some(false), 1, 0));
+    assert!(EDOM::<usize>::is_doomed(None, 2, 3));
 }
 
 #[test]
@@ -546,39 +1011,39 @@ fn test_det_set() {
     type C = <DET<(isize, isize)> as Set>::LocatingCode;
     let mut s: DET<(isize, isize)> = Default::default();
     
-    assert!(s.codes.is_empty());
+    assert!(s.codes.codes.is_empty());
     assert!(!s.add(C::new((0, 0), false, vec![])));
-    assert!(s.codes.is_empty());
+    assert!(s.codes.codes.is_empty());
     assert!(!s.add(C::new((0, 0), false, vec![(0, 1)])));
-    assert!(s.codes.is_empty());
+    assert!(s.codes.codes.is_empty());
     
     assert!(s.add(C::new((0, 0), false, vec![(0, 1), (0, 2)])));
-    assert_eq!(s.codes.len(), 1);
+    assert_eq!(s.codes.codes.len(), 1);
     assert!(!s.add(C::new((0, 0), false, vec![(0, 1), (0, 2)])));
-    assert_eq!(s.codes.len(), 1);
+    assert_eq!(s.codes.codes.len(), 1);
 
     assert!(!s.add(C::new((0, 0), false, vec![(0, 2), (0, 3)])));
-    assert_eq!(s.codes.len(), 1);
+    assert_eq!(s.codes.codes.len(), 1);
 
     assert!(s.add(C::new((0, 0), false, vec![(0, 3), (0, 4)])));
-    assert_eq!(s.codes.len(), 2);
+    assert_eq!(s.codes.codes.len(), 2);
 
     assert!(!s.add(C::new((0, 0), false, vec![(0, 2), (0, 5)])));
-    assert_eq!(s.codes.len(), 2);
+    assert_eq!(s.codes.codes.len(), 2);
     assert!(!s.add(C::new((0, 0), false, vec![(0, 2), (0, 4)])));
-    assert_eq!(s.codes.len(), 2);
+    assert_eq!(s.codes.codes.len(), 2);
 
     assert!(!s.add(C::new((0, 0), false, vec![(0, 3), (0, 4)])));
-    assert_eq!(s.codes.len(), 2);
+    assert_eq!(s.codes.codes.len(), 2);
     assert!(!s.add(C::new((0, 0), false, vec![(0, 3), (0, 4), (0, 5)])));
-    assert_eq!(s.codes.len(), 2);
+    assert_eq!(s.codes.codes.len(), 2);
     assert!(s.add(C::new((0, 0), false, vec![(0, 3), (0, 4), (0, 5), (0, 6)])));
-    assert_eq!(s.codes.len(), 3);
+    assert_eq!(s.codes.codes.len(), 3);
 
     assert!(s.add(C::new((0, 0), false, vec![(0, 6), (1, 2)])));
-    assert_eq!(s.codes.len(), 4);
+    assert_eq!(s.codes.codes.len(), 4);
     assert!(s.add(C::new((0, 0), false, vec![(0, 4), (1, 5), (1, 6)])));
-    assert_eq!(s.codes.len(), 5);
+    assert_eq!(s.codes.codes.len(), 5);
 }
 
 #[test]
@@ -0,0 +1,260 @@
+// three-dimensional lattices, with (row, col, layer) points.
+//
+// every lattice here is vertex-transitive, so it's written as Z^3 with a fixed set of neighbor offsets
+// (fcc and bcc use a basis of their own shortest vectors, so their points are still just integer triples).
+// each one gets open and closed adjacency iterators like the planar graphs in adj.rs, so the periodic search (GeometryTessellation
+// on a box tile) and the discharging lower bounds (theo) run on them unchanged.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::io;
+use std::str::FromStr;
+
+use crate::{codesets, theo, Error};
+use crate::adj::{AdjacentIterator, ClosedIterator, OpenIterator};
+use crate::params::Parameter;
+use crate::point::Point;
+use crate::tessellation::{self, Geometry, GeometryTessellation, Tessellation};
+use crate::theo::{Share, TheoProblem, TheoStrategy};
+
+pub use crate::point::Point3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lattice {
+    SC, KC, FCC, BCC,
+}
+impl Lattice {
+    pub const ALL: [Lattice; 4] = [Lattice::SC, Lattice::KC, Lattice::FCC, Lattice::BCC];
+
+    // the (sorted) offsets to every neighbor of a point
+    pub fn offsets(self) -> &'static [Point3] {
+        match self {
+            // the 6 face neighbors of a cube
+            Lattice::SC => &[(-1, 0, 0), (0, -1, 0), (0, 0, -1), (0, 0, 1), (0, 1, 0), (1, 0, 0)],
+            // the 26 cubes touching a cube at all (the 3d king's move)
+            Lattice::KC => &[
+                (-1, -1, -1), (-1, -1, 0), (-1, -1, 1), (-1, 0, -1), (-1, 0, 0), (-1, 0, 1), (-1, 1, -1), (-1, 1, 0), (-1, 1, 1),
+                (0, -1, -1), (0, -1, 0), (0, -1, 1), (0, 0, -1), (0, 0, 1), (0, 1, -1), (0, 1, 0), (0, 1, 1),
+                (1, -1, -1), (1, -1, 0), (1, -1, 1), (1, 0, -1), (1, 0, 0), (1, 0, 1), (1, 1, -1), (1, 1, 0), (1, 1, 1),
+            ],
+            // in the basis (0, 1, 1), (1, 0, 1), (1, 1, 0), the 12 neighbors are the basis vectors and their differences
+            Lattice::FCC => &[
+                (-1, 0, 0), (-1, 0, 1), (-1, 1, 0), (0, -1, 0), (0, -1, 1), (0, 0, -1),
+                (0, 0, 1), (0, 1, -1), (0, 1, 0), (1, -1, 0), (1, 0, -1), (1, 0, 0),
+            ],
+            // in the basis (-1, 1, 1), (1, -1, 1), (1, 1, -1), the 8 neighbors are the basis vectors and their sum
+            Lattice::BCC => &[(-1, -1, -1), (-1, 0, 0), (0, -1, 0), (0, 0, -1), (0, 0, 1), (0, 1, 0), (1, 0, 0), (1, 1, 1)],
+        }
+    }
+}
+impl FromStr for Lattice {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "sc" | "cubic" => Lattice::SC,
+            "kc" | "king-cube" => Lattice::KC,
+            "fcc" => Lattice::FCC,
+            "bcc" => Lattice::BCC,
+            _ => return Err(Error::UnknownGraph(s.to_owned())),
+        })
+    }
+}
+
+// the lattices are vertex-transitive, so there's just the one class and the neighbors come straight off the offsets
+// (all within a king's move, so they have the default reach). the closed iterator slots the center in among the offsets in sorted order.
+macro_rules! cubic_lattice {
+    ($open:ident, $closed:ident, $lattice:expr) => {
+        #[derive(Clone, Copy, Default)]
+        pub struct $closed {
+            pos: Point3,
+            state: usize,
+            center: bool,
+        }
+        impl AdjacentIterator for $closed {
+            type Point = Point3;
+            type Open = $open;
+            type Closed = Self;
+
+            fn class(&self, _: Point3) -> usize {
+                0
+            }
+            fn with_class(&self, pos: Point3, _: usize) -> Self {
+                Self { pos, state: 0, center: false }
+            }
+
+            fn classes(&self) -> &[Point3] {
+                &[(0, 0, 0)]
+            }
+            fn open(&self) -> Self::Open {
+                Default::default()
+            }
+            fn closed(&self) -> Self::Closed {
+                Default::default()
+            }
+        }
+        impl Iterator for $closed {
+            type Item = Point3;
+            fn next(&mut self) -> Option<Self::Item> {
+                match $lattice.offsets().get(self.state) {
+                    Some(&d) if self.center || d < (0, 0, 0) => {
+                        self.state += 1;
+                        Some(self.pos.add(d))
+                    }
+                    _ if !self.center => {
+                        self.center = true;
+                        Some(self.pos)
+                    }
+                    _ => None,
+                }
+            }
+        }
+        impl ClosedIterator for $closed {}
+
+        #[derive(Clone, Copy, Default)]
+        pub struct $open {
+            pos: Point3,
+            state: usize,
+        }
+        impl AdjacentIterator for $open {
+            type Point = Point3;
+            type Open = Self;
+            type Closed = $closed;
+
+            fn class(&self, _: Point3) -> usize {
+                0
+            }
+            fn with_class(&self, pos: Point3, _: usize) -> Self {
+                Self { pos, state: 0 }
+            }
+
+            fn classes(&self) -> &[Point3] {
+                &[(0, 0, 0)]
+            }
+            fn open(&self) -> Self::Open {
+                Default::default()
+            }
+            fn closed(&self) -> Self::Closed {
+                Default::default()
+            }
+        }
+        impl Iterator for $open {
+            type Item = Point3;
+            fn next(&mut self) -> Option<Self::Item> {
+                let d = $lattice.offsets().get(self.state)?;
+                self.state += 1;
+                Some(self.pos.add(*d))
+            }
+        }
+        impl OpenIterator for $open {}
+    };
+}
+
+cubic_lattice!(OpenSC, ClosedSC, Lattice::SC);
+cubic_lattice!(OpenKC, ClosedKC, Lattice::KC);
+cubic_lattice!(OpenFCC, ClosedFCC, Lattice::FCC);
+cubic_lattice!(OpenBCC, ClosedBCC, Lattice::BCC);
+
+// searches every tiling of a rows x cols x layers box for a periodic detector set meeting the goal (with the sat solver),
+// leaving the solution (if any) in the returned tessellation
+pub fn solve(dims: Point3, param: Parameter, lattice: Lattice, goal: &str) -> Result<Option<GeometryTessellation<Point3>>, Error> {
+    let mut tess = GeometryTessellation::try_from(Geometry::block(dims))?;
+    let goal = tessellation::parse_goal(param, goal, tess.size())?;
+    macro_rules! calc {
+        ($set:ident, $adj:expr) => {
+            tess.try_satisfy_sat::<codesets::$set<Point3>, _>(&$adj, goal)
+        }
+    }
+    let found = match lattice {
+        Lattice::SC => param_family!(param, calc, OpenSC::default(), ClosedSC::default()),
+        Lattice::KC => param_family!(param, calc, OpenKC::default(), ClosedKC::default()),
+        Lattice::FCC => param_family!(param, calc, OpenFCC::default(), ClosedFCC::default()),
+        Lattice::BCC => param_family!(param, calc, OpenBCC::default(), ClosedBCC::default()),
+    };
+    Ok(found.map(|_| tess))
+}
+// runs theo::calc_lower_bound with the code set type and adjacency param denotes on lattice (split across threads if there's more than one).
+// certificates and checkpoints name a planar graph, so the 3d bounds don't have them.
+pub fn lower_bound(param: Parameter, lattice: Lattice, strategy: TheoStrategy, thresh: Share, mut pipe: Option<&mut dyn io::Write>, threads: usize) -> Result<BTreeSet<TheoProblem<Point3>>, Error> {
+    macro_rules! calc {
+        ($set:ident, $adj:expr, $shadj:expr) => {
+            if threads > 1 {
+                theo::calc_lower_bound_parallel::<codesets::$set<Point3>, _, _>(&$adj, &$shadj, strategy, thresh, pipe.as_mut().map(|f| &mut **f as &mut dyn io::Write), None, threads)
+            }
+            else {
+                theo::calc_lower_bound::<codesets::$set<Point3>, _, _>(&$adj, &$shadj, strategy, thresh, pipe.as_mut().map(|f| &mut **f as &mut dyn io::Write), None, None)
+            }
+        }
+    }
+    match lattice {
+        Lattice::SC => param_family!(share param, calc, OpenSC::default(), ClosedSC::default()),
+        Lattice::KC => param_family!(share param, calc, OpenKC::default(), ClosedKC::default()),
+        Lattice::FCC => param_family!(share param, calc, OpenFCC::default(), ClosedFCC::default()),
+        Lattice::BCC => param_family!(share param, calc, OpenBCC::default(), ClosedBCC::default()),
+    }
+}
+#[test]
+fn test_lattices() {
+    // the cartesian coordinates of each lattice's basis
+    let embed = |lattice: Lattice, p: Point3| match lattice {
+        Lattice::SC | Lattice::KC => p,
+        Lattice::FCC => (p.1 + p.2, p.0 + p.2, p.0 + p.1),
+        Lattice::BCC => (-p.0 + p.1 + p.2, p.0 - p.1 + p.2, p.0 + p.1 - p.2),
+    };
+    for (lattice, degree, len2) in &[(Lattice::SC, 6, 1), (Lattice::KC, 26, 0), (Lattice::FCC, 12, 2), (Lattice::BCC, 8, 3)] {
+        let offsets = lattice.offsets();
+        assert_eq!(offsets.len(), *degree);
+        assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+        assert!(offsets.iter().all(|&(a, b, c)| offsets.contains(&(-a, -b, -c))));
+        assert!(offsets.iter().all(|d| d.0.abs() <= 1 && d.1.abs() <= 1 && d.2.abs() <= 1));
+        if *len2 != 0 {
+            // every neighbor is one of the shortest vectors
+            assert!(offsets.iter().all(|&d| { let e = embed(*lattice, d); e.0 * e.0 + e.1 * e.1 + e.2 * e.2 == *len2 }));
+        }
+        assert_eq!(format!("{:?}", lattice).parse::<Lattice>().unwrap(), *lattice);
+    }
+
+    // the iterators are the offsets in order, with the center slotted in for the closed ones
+    fn check<Open: OpenIterator<Point = Point3> + Default, Closed: ClosedIterator<Point = Point3> + Default>(lattice: Lattice) {
+        for &p in &[(0, 0, 0), (3, -1, 2), (-5, 4, 0)] {
+            let open: Vec<_> = Open::default().at(p).collect();
+            let mut closed: Vec<_> = lattice.offsets().iter().map(|&d| p.add(d)).chain(Some(p)).collect();
+            closed.sort_unstable();
+            assert_eq!(open, lattice.offsets().iter().map(|&d| p.add(d)).collect::<Vec<_>>());
+            assert_eq!(Closed::default().at(p).collect::<Vec<_>>(), closed);
+        }
+    }
+    check::<OpenSC, ClosedSC>(Lattice::SC);
+    check::<OpenKC, ClosedKC>(Lattice::KC);
+    check::<OpenFCC, ClosedFCC>(Lattice::FCC);
+    check::<OpenBCC, ClosedBCC>(Lattice::BCC);
+}
+#[test]
+fn test_box_search() {
+    // perfect codes: x + 2y + 3z = 0 (mod 7) on the cubic lattice, and every third cube each way on the king cube
+    let sc = solve((7, 1, 1), Parameter::DOM, Lattice::SC, "0.143").unwrap().unwrap();
+    assert_eq!((sc.geometry().detectors().len(), sc.size()), (1, 7));
+    assert!(solve((2, 2, 2), Parameter::DOM, Lattice::SC, "0.125").unwrap().is_none());
+    let kc = solve((3, 3, 3), Parameter::DOM, Lattice::KC, "0.04").unwrap().unwrap();
+    assert_eq!(kc.geometry().detectors().len(), 1);
+    assert_eq!(solve((3, 3, 3), Parameter::EDOM, Lattice::KC, "1").unwrap().map(|t| t.geometry().detectors().len()), Some(1));
+
+    // a solution has to check out against the code sets directly, across a few periods
+    use crate::codesets::{Set, LOC};
+    let t = solve((2, 2, 2), Parameter::IC, Lattice::BCC, "0.5").unwrap().unwrap();
+    let basis = t.basis();
+    let detectors: BTreeSet<Point3> = Point3::range((-4, -4, -4), (4, 4, 4)).into_iter()
+        .flat_map(|k| t.geometry().detectors().iter().map(move |&p| (0..3).fold(p, |p, i| p.add(basis[i].scale(k.coord(i))))))
+        .collect();
+    let mut codes = codesets::OLD::<Point3>::default();
+    for p in Point3::range((-2, -2, -2), (3, 3, 3)) {
+        let code = ClosedBCC::default().at(p).filter(|q| detectors.contains(q)).collect();
+        assert!(codes.add(LOC::new(p, detectors.contains(&p), code)));
+    }
+}
+#[test]
+fn test_cubic_lower_bound() {
+    // there are dominating sets sparser than 1/2 on every lattice, so that bound can't be proved (without a pipe, the search stops at the first problem)
+    assert!(!lower_bound(Parameter::DOM, Lattice::SC, TheoStrategy::Trivial, "1/2".parse().unwrap(), None, 1).unwrap().is_empty());
+    assert!(!lower_bound(Parameter::DOM, Lattice::BCC, TheoStrategy::Trivial, "1/2".parse().unwrap(), None, 2).unwrap().is_empty());
+    assert!(matches!(lower_bound(Parameter::EDOM, Lattice::SC, TheoStrategy::Trivial, "1".parse().unwrap(), None, 1), Err(Error::Unsupported { .. })));
+}
@@ -0,0 +1,60 @@
+// the errors searches hand back instead of exiting the process - each variant carries enough context to make a good message on its own
+
+use std::fmt;
+use std::io;
+
+use crate::checkpoint::CheckpointError;
+use crate::cnf::ModelReadError;
+use crate::params::{Parameter, ValueError};
+
+#[derive(Debug)]
+pub enum Error {
+    Usage(String), // the command line itself was bad
+    Io { action: &'static str, path: String, source: io::Error }, // action is what we were doing, like "open graph file"
+    Format { path: String, line: usize, col: usize, msg: &'static str }, // a geometry or graph file was malformed (line and col start at 1)
+    UnknownParameter(String),
+    UnknownGraph(String),
+    UnknownStrategy(String),
+    Value(ValueError),
+    Unsupported { param: Parameter, search: &'static str },
+    NoTessellation { shape: String },
+    Checkpoint { path: String, source: CheckpointError },
+    Model { path: String, source: ModelReadError },
+    Certificate { path: String, line: usize, msg: String }, // a certificate was malformed or one of its claims didn't hold
+}
+impl Error {
+    pub fn io(action: &'static str, path: &str, source: io::Error) -> Self {
+        Error::Io { action, path: path.to_owned(), source }
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(msg) => write!(f, "{}", msg),
+            Error::Io { action, path, source } => write!(f, "failed to {} {}: {}", action, path, source),
+            Error::Format { path, line, col, msg } => write!(f, "file {} was invalid format at line {} column {}: {}", path, line, col, msg),
+            Error::UnknownParameter(param) => write!(f, "unknown parameter: {}", param),
+            Error::UnknownGraph(graph) => write!(f, "unknown graph: {}", graph),
+            Error::UnknownStrategy(strategy) => write!(f, "unknown strategy: {}", strategy),
+            Error::Value(e) => write!(f, "{}", e),
+            Error::Unsupported { param, search } => write!(f, "{} does not currently support {:?}", search, param),
+            Error::NoTessellation { shape } => write!(f, "geometry had no valid tessellations:\n{}", shape),
+            Error::Checkpoint { path, source } => write!(f, "failed to resume from checkpoint {}: {}", path, source),
+            Error::Model { path, source } => write!(f, "model file {} was invalid: {}", path, source),
+            Error::Certificate { path, line, msg } => write!(f, "certificate {} failed at line {}: {}", path, line, msg),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+impl From<ValueError> for Error {
+    fn from(e: ValueError) -> Self {
+        Error::Value(e)
+    }
+}
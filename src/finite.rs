@@ -0,0 +1,679 @@
+// arbitrary finite graphs, loaded from edge list files or generated, and searched for detector sets of a given size
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::fs::File;
+use std::mem;
+
+use itertools::Itertools;
+
+use crate::{codesets, cnf, sat, symmetry, util, Engine, Error, SearchCommand};
+use crate::codesets::LOC;
+use crate::params::Parameter;
+
+#[derive(Debug, Clone, Copy)]
+pub enum AdjType {
+    Open, Closed
+}
+
+pub struct FiniteGraphSolver<'a, Codes>
+where Codes: codesets::Set
+{
+    verts: &'a [Vertex],
+    detectors: &'a mut HashSet<usize>,
+    needed: usize,
+    codes: Codes,
+    adj_type: AdjType,
+
+    symmetry: &'a [symmetry::Perm],
+    pub pruning: symmetry::Pruning, // what symmetry breaking has skipped so far
+    is_detector: Vec<bool>, // mirrors detectors during the search, for fast symmetry checks
+    partial: codesets::PartialCodes<Codes>, // the codes completed so far, for rejecting partial solutions early
+}
+impl<'a, Codes> FiniteGraphSolver<'a, Codes>
+where Codes: codesets::Set<Item = usize>
+{
+    fn get_raw_locating_code(&self, p: usize) -> Vec<usize> {
+        let mut v = Vec::with_capacity(9);
+        let adj = match self.adj_type {
+            AdjType::Open => self.verts[p].open_adj.iter(),
+            AdjType::Closed => self.verts[p].closed_adj.iter(),
+        };
+        for x in adj {
+            if self.detectors.contains(x) {
+                v.push(*x);
+            }
+        }
+        v
+    }
+    fn is_old(&mut self) -> bool {
+        self.codes.clear();
+        for i in 0..self.verts.len() {
+            let is_detector = self.detectors.contains(&i);
+            let v = self.get_raw_locating_code(i);
+            let code = Codes::LocatingCode::new(i, is_detector, v);
+            if !self.codes.add(code) {
+                return false;
+            }
+        }
+        true
+    }
+    // only explores detector sets which could be lex-leaders of their orbit under these graph automorphisms
+    pub fn with_symmetry(mut self, perms: &'a [symmetry::Perm]) -> Self {
+        self.symmetry = perms;
+        self
+    }
+    fn is_canonical_so_far(&mut self, decided: usize) -> bool {
+        let is_detector = &self.is_detector;
+        if symmetry::may_be_lex_leader(self.symmetry, decided, |v| is_detector[v]) {
+            return true;
+        }
+        self.pruning.branches += 1;
+        self.pruning.sets += util::binomial(self.verts.len() - decided, self.needed - self.detectors.len());
+        false
+    }
+    fn find_solution_recursive<F>(&mut self, pos: usize, f: &mut F) -> SearchCommand
+    where F: FnMut(&[Vertex], &HashSet<usize>) -> SearchCommand
+    {
+        if self.needed == self.detectors.len() {
+            if self.is_canonical_so_far(self.verts.len()) && self.is_old() {
+                return f(self.verts, self.detectors);
+            }
+        }
+        else if pos < self.verts.len() {
+            if !self.is_canonical_so_far(pos) {
+                return SearchCommand::Continue;
+            }
+            self.detectors.insert(pos);
+            self.is_detector[pos] = true;
+            if self.partial.push(pos, &self.is_detector, self.needed - self.detectors.len()) {
+                let res = self.find_solution_recursive(pos + 1, f);
+                self.partial.pop();
+                if res == SearchCommand::Halt {
+                    return SearchCommand::Halt;
+                }
+            }
+            self.detectors.remove(&pos);
+            self.is_detector[pos] = false;
+            if !self.partial.push(pos, &self.is_detector, self.needed - self.detectors.len()) {
+                return SearchCommand::Continue;
+            }
+            let res = self.find_solution_recursive(pos + 1, f);
+            self.partial.pop();
+            return res;
+        }
+
+        SearchCommand::Continue
+    }
+    pub fn find_solution(&mut self, n: usize, adj_type: AdjType) -> bool {
+        self.for_each_solution(n, adj_type, |_, _| SearchCommand::Halt) == SearchCommand::Halt
+    }
+    // calls f on every solution of size n (in the detectors set) until it asks to halt
+    pub fn for_each_solution<F>(&mut self, n: usize, adj_type: AdjType, mut f: F) -> SearchCommand
+    where F: FnMut(&[Vertex], &HashSet<usize>) -> SearchCommand
+    {
+        self.detectors.clear();
+        self.is_detector = vec![false; self.verts.len()];
+        self.needed = n;
+        self.adj_type = adj_type;
+
+        let neighborhoods = self.verts.iter().map(|v| Some(match adj_type {
+            AdjType::Open => v.open_adj.clone(),
+            AdjType::Closed => v.closed_adj.clone(),
+        })).collect();
+        self.partial = codesets::PartialCodes::new((0..self.verts.len()).collect(), neighborhoods);
+        self.find_solution_recursive(0, &mut f)
+    }
+    // checks the current detector set without searching
+    pub fn verify(&mut self, adj_type: AdjType) -> bool {
+        self.adj_type = adj_type;
+        self.is_old()
+    }
+}
+
+pub struct Vertex {
+    pub label: String,
+    pub open_adj: Vec<usize>,
+    pub closed_adj: Vec<usize>,
+}
+pub struct FiniteGraph {
+    pub verts: Vec<Vertex>,
+    pub detectors: HashSet<usize>,
+}
+impl FiniteGraph {
+    pub fn with_shape(path: &str) -> Result<Self, Error> {
+        let f = File::open(path).map_err(|e| Error::io("open graph file", path, e))?;
+        Self::read_shape(path, BufReader::new(f))
+    }
+    // reads a graph given as whitespace-separated edges "a:b" between vertex labels - path is only for error messages
+    fn read_shape<R: BufRead>(path: &str, f: R) -> Result<Self, Error> {
+        let format = |line: usize, col: usize, msg| Error::Format { path: path.to_owned(), line, col, msg };
+
+        struct Vertexish {
+            label: String,
+            adj: BTreeSet<usize>,
+        }
+        let mut v: Vec<Vertexish> = vec![];
+        let mut m: HashMap<String, usize> = Default::default();
+
+        let get_vert = |verts: &mut Vec<Vertexish>, map: &mut HashMap<String, usize>, a: &str| {
+            match map.get(a) {
+                Some(&p) => p,
+                None => {
+                    verts.push(Vertexish {
+                        label: a.into(),
+                        adj: Default::default(),
+                    });
+                    let p = verts.len() - 1;
+                    map.insert(a.into(), p);
+                    p
+                }
+            }
+        };
+        let mut add_edge = |a: &str, b: &str| {
+            let idx_a = get_vert(&mut v, &mut m, a);
+            let idx_b = get_vert(&mut v, &mut m, b);
+            v[idx_a].adj.insert(idx_b);
+            v[idx_b].adj.insert(idx_a);
+        };
+
+        for (row, line) in f.lines().enumerate() {
+            let line = line.map_err(|e| Error::io("read graph file", path, e))?;
+            for (col, tok) in util::tokens(&line) {
+                let p = match tok.find(':') {
+                    Some(x) => x,
+                    None => return Err(format(row + 1, col, "encountered token without a ':' separator")),
+                };
+                let a = tok[..p].trim();
+                let b = tok[p+1..].trim();
+                if b.find(':').is_some() {
+                    return Err(format(row + 1, col, "encountered token with multiple ':' separators"));
+                }
+                if a == b {
+                    return Err(format(row + 1, col, "encountered reflexive connection"));
+                }
+                add_edge(a, b);
+            }
+        }
+
+        let mut verts: Vec<Vertex> = Vec::with_capacity(v.len());
+        for (i, mut vert) in v.into_iter().enumerate() {
+            let open_adj = vert.adj.iter().copied().collect();
+            vert.adj.insert(i);
+            let closed_adj = vert.adj.iter().copied().collect();
+            verts.push(Vertex {
+                label: vert.label,
+                open_adj,
+                closed_adj,
+            });
+        }
+        Ok(FiniteGraph {
+            verts,
+            detectors: Default::default(),
+        })
+    }
+    pub fn solver<Codes>(&mut self) -> FiniteGraphSolver<'_, Codes>
+    where Codes: codesets::Set<Item = usize>
+    {
+        FiniteGraphSolver {
+            verts: &self.verts,
+            detectors: &mut self.detectors,
+            needed: 0,
+            codes: Default::default(),
+            adj_type: AdjType::Open,
+
+            symmetry: &[],
+            pruning: Default::default(),
+            is_detector: vec![],
+            partial: codesets::PartialCodes::new(vec![], vec![]),
+        }
+    }
+    // encodes the existence of a solution with exactly n detectors - variable i + 1 denotes vertex i being a detector
+    pub fn to_cnf<Codes>(&self, n: usize, adj_type: AdjType) -> cnf::Cnf
+    where Codes: cnf::Encode
+    {
+        let mut cnf = self.to_cnf_unbounded::<Codes>(adj_type);
+        cnf.exactly(&(1..=self.verts.len() as cnf::Lit).collect::<Vec<_>>(), n);
+        cnf
+    }
+    // encodes the code constraints without restricting the number of detectors (vertex i is variable i + 1)
+    pub fn to_cnf_unbounded<Codes>(&self, adj_type: AdjType) -> cnf::Cnf
+    where Codes: cnf::Encode
+    {
+        let var_of: Vec<cnf::Lit> = (1..=self.verts.len() as cnf::Lit).collect();
+        let codes: Vec<cnf::Code> = self.verts.iter().enumerate().map(|(i, v)| {
+            let mut adj = match adj_type {
+                AdjType::Open => v.open_adj.clone(),
+                AdjType::Closed => v.closed_adj.clone(),
+            };
+            adj.sort();
+            (i, adj)
+        }).collect();
+
+        let mut cnf = cnf::Cnf::with_vars(self.verts.len());
+        Codes::encode(&mut cnf, &cnf::CodeProblem { var_of: &var_of, codes: &codes });
+        for (i, v) in self.verts.iter().enumerate() {
+            cnf.comment(format!("var {} = {}", i + 1, v.label));
+        }
+        cnf
+    }
+    // the automorphism group of the graph, from its open neighborhoods
+    pub fn automorphisms(&self) -> symmetry::Automorphisms {
+        let adj: Vec<Vec<usize>> = self.verts.iter().map(|v| {
+            let mut adj = v.open_adj.clone();
+            adj.sort();
+            adj
+        }).collect();
+        symmetry::automorphisms(&adj)
+    }
+    // replaces the current detector set with the one denoted by a model of the formula produced by to_cnf
+    pub fn set_solution(&mut self, model: &[bool]) {
+        self.detectors.clear();
+        self.detectors.extend((0..self.verts.len()).filter(|&i| model.get(i).copied().unwrap_or(false)));
+    }
+    pub fn get_solution(&self) -> Vec<&str> {
+        let mut v: Vec<&str> = self.detectors.iter().map(|&p| self.verts[p].label.as_str()).collect();
+        v.sort();
+        v
+    }
+
+    fn geometric<T: fmt::Debug, F: Fn(&T, &T) -> bool>(points: &[T], f: &F) -> Self {
+        let mut verts = Vec::with_capacity(points.len());
+        for (i, a) in points.iter().enumerate() {
+            let mut adj = vec![];
+            for (j, b) in points[..i].iter().enumerate() {
+                if f(a, b) { adj.push(j); }
+            }
+            for (j, b) in points[i + 1..].iter().enumerate() {
+                if f(a, b) { adj.push(i + 1 + j); }
+            }
+            let open_adj = adj.clone();
+            adj.insert(adj.iter().position(|&x| x > i).unwrap_or(adj.len()), i); // codes are required to be sorted
+            verts.push(Vertex { open_adj, closed_adj: adj, label: format!("{:?}", a) });
+        }
+        Self { verts, detectors: Default::default() }
+    }
+    pub fn path(size: usize) -> Self {
+        let mut vert_pos = Vec::with_capacity(size);
+        for i in 0..size as isize { vert_pos.push(i); }
+        Self::geometric(&vert_pos, &|a, b| (a - b).abs() <= 1)
+    }
+    pub fn cycle(size: usize) -> Self {
+        let mut g = Self::path(size);
+        if size > 2 {
+            g.verts[0].open_adj.push(size - 1);
+            g.verts[0].closed_adj.push(size - 1);
+            g.verts[size - 1].open_adj.insert(0, 0);
+            g.verts[size - 1].closed_adj.insert(0, 0);
+        }
+        g
+    }
+    pub fn ladder(length: usize) -> Self {
+        let mut vert_pos = Vec::with_capacity(length * 2);
+        for i in 0..length as isize { vert_pos.push((0isize, i)); }
+        for i in 0..length as isize { vert_pos.push((1isize, i)); }
+        Self::geometric(&vert_pos, &|a, b| (a.0 - b.0).abs() + (a.1 - b.1).abs() <= 1)
+    }
+    pub fn complete(size: usize) -> Self {
+        let mut vert_pos = Vec::with_capacity(size);
+        for i in 0..size as isize { vert_pos.push(i); }
+        Self::geometric(&vert_pos, &|_, _| true)
+    }
+}
+
+#[test]
+fn test_finite_graph_read_shape() {
+    let g = FiniteGraph::read_shape("test", &b"a:b b:c\nc:a\n"[..]).unwrap();
+    assert_eq!(g.verts.len(), 3);
+    assert!(g.verts.iter().all(|v| v.open_adj.len() == 2 && v.closed_adj.len() == 3));
+
+    let err = |text: &[u8]| match FiniteGraph::read_shape("test", text) {
+        Err(Error::Format { line, col, msg, .. }) => (line, col, msg),
+        _ => panic!(),
+    };
+    assert_eq!(err(b"a:b\n  b:c c\n"), (2, 7, "encountered token without a ':' separator"));
+    assert_eq!(err(b"a:b:c\n"), (1, 1, "encountered token with multiple ':' separators"));
+    assert_eq!(err(b"a:b b:b\n"), (1, 5, "encountered reflexive connection"));
+}
+
+// how a search went besides its answer, for the caller to report - each field is only set if that part of the search ran
+#[derive(Default)]
+pub struct SearchStats {
+    pub automorphisms: Option<symmetry::Automorphisms>, // the group used to deduplicate or break symmetry
+    pub breaking_clauses: Option<usize>, // clauses symmetry breaking added to the sat encoding
+    pub pruning: Option<symmetry::Pruning>, // what the recursive search skipped as non-canonical
+    pub sat: Option<sat::Stats>,
+}
+// the outcome of an enumeration: the number of solutions and the number of orbits they form (the same if not deduplicating)
+pub struct Enumeration {
+    pub total: usize,
+    pub classes: usize,
+    pub stats: SearchStats,
+}
+// the outcome of find_min, whose best solution is left in the detectors set
+pub struct Minimum {
+    pub min: Option<usize>,
+    pub cnf: cnf::Cnf, // the refuted formula, including every cardinality constraint added (the strongest being the last one)
+    pub solver: sat::Solver, // holds the drup proof of the final unsat answer if one was asked for
+}
+
+impl FiniteGraph {
+    // the generators of the automorphism group to break symmetry with (all of them must map a set above it to be canonical)
+    fn breaking_perms(&self, stats: &mut SearchStats) -> Vec<symmetry::Perm> {
+        let aut = self.automorphisms();
+        let perms = symmetry::breaking_perms(&aut.generators);
+        stats.automorphisms = Some(aut);
+        perms
+    }
+    fn break_symmetry(&self, cnf: &mut cnf::Cnf, perms: &[symmetry::Perm], stats: &mut SearchStats) {
+        if perms.is_empty() { return; }
+        let before = cnf.clauses().len();
+        let vars: Vec<cnf::Lit> = (1..=self.verts.len() as cnf::Lit).collect();
+        for p in perms {
+            cnf.lex_leader(&vars, p);
+        }
+        stats.breaking_clauses = Some(cnf.clauses().len() - before);
+    }
+    // looks for a single solution of the given size (left in detectors), optionally only among canonical detector sets
+    pub fn search<Codes>(&mut self, count: usize, adj_type: AdjType, engine: Engine, symmetry: bool) -> (bool, SearchStats)
+    where Codes: cnf::Encode<Item = usize>
+    {
+        let mut stats = SearchStats::default();
+        let perms = if symmetry { self.breaking_perms(&mut stats) } else { vec![] };
+        let found = match engine {
+            Engine::Recursive => {
+                let mut solver = self.solver::<Codes>().with_symmetry(&perms);
+                let found = solver.find_solution(count, adj_type);
+                if symmetry {
+                    stats.pruning = Some(mem::take(&mut solver.pruning));
+                }
+                found
+            }
+            Engine::Sat => {
+                let mut cnf = self.to_cnf::<Codes>(count, adj_type);
+                self.break_symmetry(&mut cnf, &perms, &mut stats);
+                let mut solver = sat::Solver::from_cnf(&cnf);
+                let model = solver.solve();
+                stats.sat = Some(solver.stats());
+                match model {
+                    Some(model) => {
+                        self.set_solution(&model);
+                        debug_assert!(self.solver::<Codes>().verify(adj_type)); // sanity check the encoding against codesets
+                        true
+                    }
+                    None => false,
+                }
+            }
+        };
+        (found, stats)
+    }
+    // calls f on every solution of the given size, or with unique only one per orbit under the automorphism group of the graph.
+    // f gets the sorted detector set and the size of its orbit (1 if not deduplicating).
+    // with symmetry, only canonical sets are explored, which is fine since the orbits of the ones found still cover everything.
+    pub fn enumerate<Codes, F>(&mut self, count: usize, adj_type: AdjType, engine: Engine, unique: bool, symmetry: bool, mut f: F) -> Enumeration
+    where Codes: cnf::Encode<Item = usize>, F: FnMut(&[Vertex], &[usize], usize)
+    {
+        assert!(unique || !symmetry);
+        let mut stats = SearchStats::default();
+        if unique {
+            stats.automorphisms = Some(self.automorphisms());
+        }
+        let perms = match &stats.automorphisms {
+            Some(aut) if symmetry => symmetry::breaking_perms(&aut.generators),
+            _ => vec![],
+        };
+
+        let mut seen: HashSet<Vec<usize>> = Default::default();
+        let (mut total, mut classes) = (0, 0);
+        let aut = stats.automorphisms.take();
+        // reports a solution if it's new, returning every detector set it stands for
+        let mut report = |verts: &[Vertex], detectors: &HashSet<usize>| -> Option<Vec<Vec<usize>>> {
+            let mut set: Vec<usize> = detectors.iter().copied().collect();
+            set.sort_unstable();
+            if seen.contains(&set) { return None; }
+            let orbit = match &aut {
+                Some(aut) => symmetry::set_orbit(&aut.generators, &set),
+                None => vec![set],
+            };
+            total += orbit.len();
+            classes += 1;
+            f(verts, &orbit[0], orbit.len());
+            if unique {
+                seen.extend(orbit.iter().cloned());
+            }
+            Some(orbit)
+        };
+
+        match engine {
+            Engine::Recursive => {
+                let mut solver = self.solver::<Codes>().with_symmetry(&perms);
+                solver.for_each_solution(count, adj_type, |verts, detectors| {
+                    report(verts, detectors);
+                    SearchCommand::Continue
+                });
+                if symmetry {
+                    stats.pruning = Some(mem::take(&mut solver.pruning));
+                }
+            }
+            Engine::Sat => {
+                let mut cnf = self.to_cnf::<Codes>(count, adj_type);
+                self.break_symmetry(&mut cnf, &perms, &mut stats);
+                let mut solver = sat::Solver::from_cnf(&cnf);
+                while let Some(model) = solver.solve() {
+                    self.set_solution(&model);
+                    debug_assert!(self.solver::<Codes>().verify(adj_type)); // sanity check the encoding against codesets
+                    // block the whole class at once (there are exactly count detectors, so one of them has to go)
+                    for set in report(&self.verts, &self.detectors).unwrap() {
+                        solver.add_clause(&set.iter().map(|&i| -(i as cnf::Lit + 1)).collect::<Vec<_>>());
+                    }
+                }
+                stats.sat = Some(solver.stats());
+            }
+        }
+        stats.automorphisms = aut;
+        Enumeration { total, classes, stats }
+    }
+    // finds the minimum solution size by repeatedly asking the sat solver for a strictly smaller solution, calling f on each size found.
+    // the final unsat answer refutes every size below the minimum, which the solver can record as a drup proof.
+    pub fn find_min<Codes, F>(&mut self, adj_type: AdjType, proof: bool, mut f: F) -> Minimum
+    where Codes: cnf::Encode<Item = usize>, F: FnMut(usize)
+    {
+        let vars: Vec<cnf::Lit> = (1..=self.verts.len() as cnf::Lit).collect();
+        let mut cnf = self.to_cnf_unbounded::<Codes>(adj_type);
+        let mut solver = sat::Solver::default();
+        if proof {
+            solver.enable_proof();
+        }
+        let mut added = 0;
+        let mut best: Option<HashSet<usize>> = None;
+        loop {
+            for c in &cnf.clauses()[added..] {
+                solver.add_clause(c);
+            }
+            added = cnf.clauses().len();
+
+            match solver.solve() {
+                None => break,
+                Some(model) => {
+                    self.set_solution(&model);
+                    debug_assert!(self.solver::<Codes>().verify(adj_type)); // sanity check the encoding against codesets
+                    let size = self.detectors.len();
+                    f(size);
+                    best = Some(self.detectors.clone());
+                    if size == 0 { break; }
+                    cnf.at_most(&[], &vars, size - 1);
+                }
+            }
+        }
+        let min = best.as_ref().map(HashSet::len);
+        self.detectors = best.unwrap_or_default();
+        Minimum { min, cnf, solver }
+    }
+}
+
+// expands $calc!(set, adjacency) with the code set type and adjacency type param denotes on a finite graph
+macro_rules! finite_family {
+    ($param:expr, $calc:ident) => {
+        match $param {
+            Parameter::DOM => $calc!(DOM, Closed),
+            Parameter::ODOM => $calc!(DOM, Open),
+            Parameter::EDOM => $calc!(EDOM, Closed),
+            Parameter::EODOM => $calc!(EDOM, Open),
+            Parameter::LD => $calc!(LD, Open),
+            Parameter::REDLD => $calc!(REDLD, Open),
+            Parameter::DETLD => $calc!(DETLD, Open),
+            Parameter::ERRLD => $calc!(ERRLD, Open),
+            Parameter::IC => $calc!(OLD, Closed),
+            Parameter::REDIC => $calc!(RED, Closed),
+            Parameter::DETIC => $calc!(DET, Closed),
+            Parameter::RSPIC => $calc!(RSP, Closed),
+            Parameter::ERRIC => $calc!(ERR, Closed),
+            Parameter::OLD => $calc!(OLD, Open),
+            Parameter::REDOLD => $calc!(RED, Open),
+            Parameter::DETOLD => $calc!(DET, Open),
+            Parameter::RSPOLD => $calc!(RSP, Open),
+            Parameter::ERROLD => $calc!(ERR, Open),
+        }
+    }
+}
+
+// runs FiniteGraph::search with the code set type and adjacency param denotes
+pub fn search(g: &mut FiniteGraph, param: Parameter, count: usize, engine: Engine, symmetry: bool) -> (bool, SearchStats) {
+    macro_rules! calc {
+        ($set:ident, $adj:ident) => { g.search::<codesets::$set<usize>>(count, AdjType::$adj, engine, symmetry) }
+    }
+    finite_family!(param, calc)
+}
+// runs FiniteGraph::enumerate with the code set type and adjacency param denotes
+pub fn enumerate<F>(g: &mut FiniteGraph, param: Parameter, count: usize, engine: Engine, unique: bool, symmetry: bool, f: F) -> Enumeration
+where F: FnMut(&[Vertex], &[usize], usize)
+{
+    macro_rules! calc {
+        ($set:ident, $adj:ident) => { g.enumerate::<codesets::$set<usize>, _>(count, AdjType::$adj, engine, unique, symmetry, f) }
+    }
+    finite_family!(param, calc)
+}
+// runs FiniteGraph::find_min with the code set type and adjacency param denotes
+pub fn find_min<F: FnMut(usize)>(g: &mut FiniteGraph, param: Parameter, proof: bool, f: F) -> Minimum {
+    macro_rules! calc {
+        ($set:ident, $adj:ident) => { g.find_min::<codesets::$set<usize>, _>(AdjType::$adj, proof, f) }
+    }
+    finite_family!(param, calc)
+}
+// the cnf asking for a solution with exactly count detectors, as FiniteGraph::to_cnf encodes it for param
+pub fn to_cnf(g: &FiniteGraph, param: Parameter, count: usize) -> cnf::Cnf {
+    macro_rules! calc {
+        ($set:ident, $adj:ident) => { g.to_cnf::<codesets::$set<usize>>(count, AdjType::$adj) }
+    }
+    finite_family!(param, calc)
+}
+// checks whether the current detector set is a solution for param
+pub fn verify(g: &mut FiniteGraph, param: Parameter) -> bool {
+    macro_rules! calc {
+        ($set:ident, $adj:ident) => { g.solver::<codesets::$set<usize>>().verify(AdjType::$adj) }
+    }
+    finite_family!(param, calc)
+}
+
+#[cfg(test)]
+fn enumerate_counts<Codes>(mut g: FiniteGraph, count: usize, adj_type: AdjType, engine: Engine, unique: bool, symmetry: bool) -> (usize, usize)
+where Codes: cnf::Encode<Item = usize>
+{
+    let res = g.enumerate::<Codes, _>(count, adj_type, engine, unique, symmetry, |_, _, _| ());
+    (res.total, res.classes)
+}
+#[test]
+fn test_finite_enumerate() {
+    // c6 has 3 dominating sets of size 2 (antipodal pairs, all rotations of each other) and 14 of size 3
+    for &engine in &[Engine::Recursive, Engine::Sat] {
+        assert_eq!(enumerate_counts::<codesets::DOM<usize>>(FiniteGraph::cycle(6), 2, AdjType::Closed, engine, false, false), (3, 3));
+        assert_eq!(enumerate_counts::<codesets::DOM<usize>>(FiniteGraph::cycle(6), 2, AdjType::Closed, engine, true, false), (3, 1));
+        assert_eq!(enumerate_counts::<codesets::DOM<usize>>(FiniteGraph::cycle(6), 3, AdjType::Closed, engine, false, false).0, 14);
+        assert_eq!(enumerate_counts::<codesets::OLD<usize>>(FiniteGraph::complete(4), 4, AdjType::Closed, engine, true, false), (0, 0));
+        let (total, classes) = enumerate_counts::<codesets::OLD<usize>>(FiniteGraph::cycle(12), 6, AdjType::Closed, engine, true, false);
+        let (all, _) = enumerate_counts::<codesets::OLD<usize>>(FiniteGraph::cycle(12), 6, AdjType::Closed, engine, false, false);
+        assert_eq!(total, all);
+        assert_lt!(classes, total);
+
+        // symmetry breaking only skips sets that are covered by the orbits of the ones it does find
+        assert_eq!(enumerate_counts::<codesets::OLD<usize>>(FiniteGraph::cycle(12), 6, AdjType::Closed, engine, true, true), (total, classes));
+        assert_eq!(enumerate_counts::<codesets::DOM<usize>>(FiniteGraph::cycle(9), 3, AdjType::Closed, engine, true, true), (3, 1));
+        assert_eq!(enumerate_counts::<codesets::LD<usize>>(FiniteGraph::ladder(4), 3, AdjType::Open, engine, true, true),
+            enumerate_counts::<codesets::LD<usize>>(FiniteGraph::ladder(4), 3, AdjType::Open, engine, true, false));
+    }
+}
+#[test]
+fn test_finite_search_symmetry() {
+    for size in 3..9 {
+        for count in 1..size {
+            for &engine in &[Engine::Recursive, Engine::Sat] {
+                for g in &mut [FiniteGraph::cycle, FiniteGraph::ladder, FiniteGraph::complete] {
+                    let (plain, _) = g(size).search::<codesets::OLD<usize>>(count, AdjType::Closed, engine, false);
+                    assert_eq!(g(size).search::<codesets::OLD<usize>>(count, AdjType::Closed, engine, true).0, plain);
+                    let (plain, _) = g(size).search::<codesets::DET<usize>>(count, AdjType::Open, engine, false);
+                    assert_eq!(g(size).search::<codesets::DET<usize>>(count, AdjType::Open, engine, true).0, plain);
+                }
+            }
+        }
+    }
+}
+#[test]
+fn test_finite_min() {
+    let min = |mut g: FiniteGraph, param: Parameter| find_min(&mut g, param, false, |_| ()).min;
+    assert_eq!(min(FiniteGraph::path(7), Parameter::DOM), Some(3));
+    assert_eq!(min(FiniteGraph::cycle(9), Parameter::DOM), Some(3));
+    assert_eq!(min(FiniteGraph::cycle(12), Parameter::IC), Some(6));
+    assert_eq!(min(FiniteGraph::complete(4), Parameter::IC), None);
+    assert_eq!(min(FiniteGraph::complete(5), Parameter::DOM), Some(1));
+
+    // the best solution is what's left in the detectors
+    let mut g = FiniteGraph::path(7);
+    assert_eq!(find_min(&mut g, Parameter::DOM, false, |_| ()).min, Some(g.detectors.len()));
+    assert!(verify(&mut g, Parameter::DOM));
+}
+
+// finds the smallest graph (by vertices, then edges) that has a solution at all - the solution is left in its detectors, and its edges come with it
+pub fn smallest(param: Parameter) -> Result<(FiniteGraph, Vec<(usize, usize)>), Error> {
+    if let Parameter::EDOM | Parameter::EODOM = param {
+        return Err(Error::Unsupported { param, search: "smallest" });
+    }
+    let test = |graph: &mut FiniteGraph| {
+        let vertex_count = graph.verts.len();
+        macro_rules! calc {
+            ($set:ident, $adj:ident) => { graph.solver::<codesets::$set<usize>>().find_solution(vertex_count, AdjType::$adj) }
+        }
+        finite_family!(param, calc)
+    };
+    // we have to handle 1 vertex graph separately because following logic does combinations(2), which for a singleton graph is nothing
+    let mut singleton_graph = FiniteGraph { verts: vec![Vertex { label: 0.to_string(), open_adj: vec![], closed_adj: vec![0] }], detectors: Default::default() };
+    if test(&mut singleton_graph) { return Ok((singleton_graph, vec![])); }
+    for vertex_count in 2.. {
+        let complete_edges: Vec<(usize, usize)> = (0..vertex_count).tuple_combinations().collect();
+        for edge_count in 0..=complete_edges.len() {
+            for edges in complete_edges.iter().copied().combinations(edge_count) {
+                let mut vert_adjs = vec![Vec::with_capacity(vertex_count); vertex_count];
+                for &(a, b) in edges.iter() {
+                    debug_assert_ne!(a, b); // sanity check
+                    vert_adjs[a].push(b);
+                    vert_adjs[b].push(a);
+                }
+                let verts = vert_adjs.into_iter().enumerate().map(|(i, mut adj)| {
+                    let open_adj = adj.clone();
+                    adj.insert(adj.iter().position(|&x| x > i).unwrap_or(adj.len()), i);
+                    Vertex { label: i.to_string(), open_adj, closed_adj: adj }
+                }).collect();
+                let mut graph = FiniteGraph { verts, detectors: Default::default() };
+                if test(&mut graph) { return Ok((graph, edges)); }
+            }
+        }
+    }
+    unreachable!(); // the loop over vertex counts only ends by returning
+}
+#[test]
+fn test_smallest() {
+    assert_eq!(smallest(Parameter::DOM).unwrap().0.verts.len(), 1);
+    let (g, edges) = smallest(Parameter::ODOM).unwrap();
+    assert_eq!((g.verts.len(), edges), (2, vec![(0, 1)]));
+    assert!(matches!(smallest(Parameter::EDOM), Err(Error::Unsupported { param: Parameter::EDOM, .. })));
+}
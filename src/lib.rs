@@ -0,0 +1,42 @@
+// the old-set library: locating code set types, tessellation and finite graph searches, and discharging lower bounds.
+// nothing in here prints results or exits the process - errors come back as Results and the old-set binary decides what to do with them.
+
+#[macro_use]
+extern crate more_asserts;
+
+pub mod util;
+pub mod point;
+pub mod adj;
+pub mod periodic;
+pub mod codesets;
+pub mod perf;
+pub mod cnf;
+pub mod sat;
+pub mod symmetry;
+pub mod checkpoint;
+pub mod error;
+#[cfg(feature = "bitset")]
+pub mod bitcodes;
+
+#[macro_use]
+pub mod params;
+pub mod tessellation;
+pub mod finite;
+pub mod theo;
+pub mod certificate;
+pub mod cubic;
+pub mod strip;
+
+pub use error::Error;
+
+// selects how a solution is searched for: plain subset recursion or the built-in sat solver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Recursive,
+    Sat,
+}
+
+#[derive(PartialEq, Eq)]
+pub enum SearchCommand {
+    Continue, Halt,
+}
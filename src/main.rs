@@ -1,18 +1,14 @@
-use std::collections::{BTreeSet, BTreeMap, HashMap, HashSet};
-use std::fmt;
-use std::io::{self, BufRead, BufReader};
+use std::collections::BTreeSet;
+use std::io::{self, BufReader, Write};
 use std::fs::File;
-use std::path::Path;
-use std::mem;
 use std::convert::TryFrom;
-use std::str::FromStr;
-use std::thread;
-use std::sync::{Mutex, Arc};
+use std::fmt;
+use std::sync::Mutex;
 
-use itertools::Itertools;
 use num::{BigRational, BigInt};
-use num::traits::{Zero, One};
+use num::traits::ToPrimitive;
 
+#[cfg(test)]
 #[macro_use]
 extern crate more_asserts;
 
@@ -25,1768 +21,1024 @@ macro_rules! crash {
         std::process::exit($val);
     }};
 }
-
-mod util;
-mod adj;
-mod codesets;
-mod perf;
-
-use adj::AdjacentIterator;
-use codesets::LOC;
-use perf::{PointMap, PointSet};
-
-enum Goal {
-    MeetOrBeat(f64),
-    Exactly(usize),
-}
-impl Goal {
-    fn get_value(&self, total_size: usize) -> usize {
-        match self {
-            Goal::MeetOrBeat(v) => (total_size as f64 * v).floor() as usize,
-            Goal::Exactly(v) => *v,
-        }
-    }
-}
-
-trait Solver {
-    fn is_old<Adj>(&mut self) -> bool where Adj: adj::AdjacentIterator;
-    fn try_satisfy<Adj>(&mut self, goal: Goal) -> Option<usize> where Adj: adj::AdjacentIterator;
+// progress and other side notes, which go to stderr with --format json so stdout only has results
+macro_rules! info {
+    ($($msg:expr),+) => {{
+        if output::is_json() { eprintln!($($msg),+); } else { println!($($msg),+); }
+    }};
 }
 
-trait Tessellation: fmt::Display {
-    fn size(&self) -> usize;
-    fn try_satisfy<Codes, Adj>(&mut self, goal: Goal) -> Option<usize>
-    where Codes: codesets::Set<Item = (isize, isize)>, Adj: adj::AdjacentIterator;
+mod output;
+mod cli;
+mod batch;
+mod survey;
+
+use old_set::{util, cnf, checkpoint, certificate, periodic, Engine, Error, SearchCommand};
+use old_set::params::{self, Parameter, Graph, ValueError};
+use old_set::tessellation::{self, EntropySearch, Geometry, GeometryTessellation};
+use old_set::finite::{self, FiniteGraph, Minimum, SearchStats};
+use old_set::cubic::{self, Lattice};
+use old_set::point::Point;
+use old_set::strip;
+use old_set::theo::{self, AutoTheoLimits, AutoTheoSearch, TheoStrategy, TheoProblem, TheoCheckpoint, TheoCertificate, Share};
+use output::Json;
+use cli::{Command, Matches, Opt};
+
+// selects which solutions are reported: the first one found, all of them, or one per symmetry class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Enumerate {
+    First,
+    All,
+    Unique,
 }
 
-enum GeometryWithShapeError {
-    FileOpenFailure,
-    InvalidFormat(&'static str),
+// a tiling (shape, detectors and basis) as json fields
+fn geometry_json_fields<P: Point + Into<Json>>(geo: &Geometry<P>, basis: &[P]) -> Vec<(&'static str, Json)> {
+    vec![
+        ("shape", geo.shape().iter().copied().collect::<Vec<_>>().into()),
+        ("detectors", geo.detectors().iter().copied().collect::<Vec<_>>().into()),
+        ("basis", basis.to_vec().into()),
+    ]
 }
-struct Geometry {
-    shape: BTreeSet<(isize, isize)>,
-    detectors: BTreeSet<(isize, isize)>,
-    w: isize,
-    h: isize,
-}
-impl Geometry {
-    fn with_shape(path: &str) -> Result<Self, GeometryWithShapeError> {
-        let f = BufReader::new(match File::open(path) {
-            Ok(x) => x,
-            Err(_) => return Err(GeometryWithShapeError::FileOpenFailure),
-        });
-        let mut shape: BTreeSet<(isize, isize)> = Default::default();
-        for (row, line) in f.lines().map(Result::unwrap).enumerate() {
-            for (col, item) in line.split_whitespace().enumerate() {
-                match item {
-                    x if x.len() != 1 => return Err(GeometryWithShapeError::InvalidFormat("expected geometry element to be length 1")),
-                    "." => (),
-                    "@" => { shape.insert((row as isize, col as isize)); },
-                    _ => return Err(GeometryWithShapeError::InvalidFormat("encountered unexpected character")),
-                };
-            }
-        }
-        if shape.is_empty() {
-            return Err(GeometryWithShapeError::InvalidFormat("shape is empty"));
-        }
-        Ok(Geometry::for_printing(&shape, [].iter().copied()))
-    }
-    fn for_printing<I>(shape: &BTreeSet<(isize, isize)>, detectors: I) -> Self
-    where I: Iterator<Item = (isize, isize)>
-    {
-        assert!(!shape.is_empty());
-
-        let min = (
-            shape.iter().map(|(a, _)| *a).min().unwrap(),
-            shape.iter().map(|(_, b)| *b).min().unwrap(),
-        );
-        let max = (
-            shape.iter().map(|(a, _)| *a).max().unwrap(),
-            shape.iter().map(|(_, b)| *b).max().unwrap(),
-        );
+fn theo_print_result<G: fmt::Debug, P: Point + Into<Json>>(f: &mut dyn io::Write, param: Parameter, graph: G, strategy: TheoStrategy, thresh: &Share, problems: &BTreeSet<TheoProblem<P>>) {
+    // attempt to convert to a floating-point representation
+    let float = match thresh.numer().to_string().parse::<f64>() {
+        Ok(n) => match thresh.denom().to_string().parse::<f64>() {
+            Ok(d) => Some(n / d),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    };
 
-        Self {
-            shape: shape.iter().map(|p| (p.0 - min.0, p.1 - min.1)).collect(),
-            detectors: detectors.map(|p| (p.0 - min.0, p.1 - min.1)).collect(),
-            h: max.0 - min.0 + 1,
-            w: max.1 - min.1 + 1,
-        }
-    }
-    fn rectangle(rows: usize, cols: usize) -> Self {
-        assert!(rows > 0 && cols > 0);
-        let shape = (0..rows as isize).flat_map(|r| (0..cols as isize).map(move |c| (r, c))).collect();
-        Self {
-            shape,
-            detectors: Default::default(),
-            h: rows as isize,
-            w: cols as isize,
-        }
-    }
-    fn width(&self) -> isize {
-        self.w
-    }
-    fn height(&self) -> isize {
-        self.h
-    }
-    fn size(&self) -> usize {
-        self.shape.len()
-    }
-    fn sub_geometries(self, size: usize) -> impl Iterator<Item=Geometry> {
-        self.shape.into_iter().combinations(size).map(|set| Geometry::for_printing(&set.into_iter().collect(), [].iter().copied()))
-    }
-}
-impl fmt::Display for Geometry {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut working_row = !0;
-        let mut working_col = 0;
-        for x in &self.shape {
-            if x.0 != working_row {
-                if working_row != !0 {
-                    for _ in 0..(x.0 - working_row) { writeln!(f)?; }
-                }
-                working_row = x.0;
-                working_col = 0;
-            }
-            for _ in 0..(x.1 - working_col) { write!(f, "  ")?; }
-            working_col = x.1 + 1;
-            write!(f, "{} ", if self.detectors.contains(x) { 1 } else { 0 })?;
+    if output::is_json() {
+        let problems: Vec<_> = problems.iter().map(|p| Json::object(vec![
+            ("center", p.center.into()),
+            ("share", Json::rational(&p.share)),
+            ("avg_share", Json::rational(&p.avg_share)),
+            ("structure", p.structure.as_str().into()),
+        ])).collect();
+        let mut fields = json_header(param, graph);
+        fields.extend(vec![
+            ("strategy", format!("{:?}", strategy).into()),
+            ("thresh", Json::rational(thresh)),
+            ("thresh_float", float.into()),
+            ("proved", problems.is_empty().into()),
+            ("problems", problems.into()),
+        ]);
+        writeln!(f, "{}", Json::object(fields)).unwrap();
+    }
+    // if there were no problems then we've proven thresh works
+    else if problems.is_empty() {
+        let float = float.map(|x| x.to_string()).unwrap_or_else(|| "f64 overflow".to_string());
+        writeln!(f, "found theo lower bound {} ({})", thresh, float).unwrap();
+    }
+    // otherwise there were problems - conservatively, all we can say is that it didn't work
+    else {
+        // print out all the problems we encountered (already in desired print order)
+        for p in problems.iter() {
+            writeln!(f, "problem: {} ({}) (center {:?})\n{}", p.share, p.avg_share, p.center, p.structure).unwrap();
         }
-        writeln!(f)?;
-        Ok(())
+        writeln!(f, "total problems: {}", problems.len()).unwrap();
     }
 }
 
-type TessellationMap = (HashMap<(isize, isize), (isize, isize)>, (isize, isize), (isize, isize));
-struct GeometrySolver<'a, Codes> {
-    shape: &'a BTreeSet<(isize, isize)>,
-    interior: &'a BTreeSet<(isize, isize)>,
-    shape_with_padding: &'a BTreeSet<(isize, isize)>,
-    first_per_row: &'a HashSet<(isize, isize)>,
-    old_set: &'a mut BTreeSet<(isize, isize)>,
-    
-    tessellation_maps: &'a [TessellationMap],
-    current_tessellation_map: &'a TessellationMap,
-    src_basis_a: &'a mut (isize, isize),
-    src_basis_b: &'a mut (isize, isize),
-
-    codes: Codes,
-    needed: usize,
-
-    classes_to_check: Vec<usize>,
-    checked_classes: Vec<usize>,
+// the fields every json result about a set type on a graph starts with
+fn json_header<G: fmt::Debug>(param: Parameter, graph: G) -> Vec<(&'static str, Json)> {
+    vec![("parameter", format!("{:?}", param).into()), ("graph", format!("{:?}", graph).into())]
 }
-impl<'a, Codes> GeometrySolver<'a, Codes>
-where Codes: codesets::Set<Item = (isize, isize)>
-{
-    fn get_locating_code<Adj: adj::AdjacentIterator>(&self, pos: (isize, isize), offset: (isize, isize)) -> Codes::LocatingCode {
-        let mut v = Vec::with_capacity(9);
-        let class = Adj::class(pos.0 + offset.0, pos.1 + offset.1); // compute the effective class as if we applied the given offset
-        for x in Adj::with_class(pos.0, pos.1, class) {
-            if self.old_set.contains(self.current_tessellation_map.0.get(&x).unwrap()) {
-                v.push(x);
-            }
-        }
-        let is_detector = self.old_set.contains(self.current_tessellation_map.0.get(&pos).unwrap());
-        Codes::LocatingCode::new(pos, is_detector, v)
-    }
-    fn is_old_interior_up_to<Adj: adj::AdjacentIterator>(&mut self, row: isize) -> bool {
-        self.codes.clear();
-        for p in self.interior {
-            if p.0 >= row - 1 { break; }
-            let loc = self.get_locating_code::<Adj>(*p, (0, 0)); // interior uses no offset
-            if !self.codes.add(loc) {
-                return false;
-            }
-        }
-        true
-    }
-    fn calc_old_min_interior<'b, Adj, P>(&mut self, mut pos: P) -> bool
-    where Adj: adj::AdjacentIterator, P: Iterator<Item = (usize, &'b (isize, isize))> + Clone
-    {
-        if self.needed == self.old_set.len() {
-            return self.is_old::<Adj>();
-        } else if let Some((i, &p)) = pos.next() {
-            if i + (self.needed - self.old_set.len()) > self.shape.len() {
-                return false;
-            }
-
-            let good_so_far = !self.first_per_row.contains(&p) || self.is_old_interior_up_to::<Adj>(p.0);
-            if !good_so_far {
-                return false;
-            }
-
-            self.old_set.insert(p);
-            if self.calc_old_min_interior::<Adj, _>(pos.clone()) {
-                return true;
-            }
-            self.old_set.remove(&p);
-            return self.calc_old_min_interior::<Adj, _>(pos);
-        }
-
-        false
+fn tess_helper_print(tess: &GeometryTessellation, param: Parameter, graph: &Graph, min: usize) {
+    let n = tess.geometry().size();
+    let d = util::gcd(min, n);
+    if output::is_json() {
+        let mut fields = json_header(param, graph);
+        fields.extend(vec![
+            ("found", true.into()),
+            ("density", Json::rational(&BigRational::new(min.into(), n.into()))),
+            ("density_float", (min as f64 / n as f64).into()),
+            ("size", n.into()),
+        ]);
+        fields.extend(geometry_json_fields(tess.geometry(), tess.basis()));
+        println!("{}", Json::object(fields));
+        return;
     }
+    println!("found a {}/{} ({}) solution:\n{}", (min / d), (n / d), (min as f64 / n as f64), tess);
 }
-impl<Codes> Solver for GeometrySolver<'_, Codes>
-where Codes: codesets::Set<Item = (isize, isize)>
-{
-    fn is_old<Adj: adj::AdjacentIterator>(&mut self) -> bool {
-        'next_tess: for tess in self.tessellation_maps {
-            self.current_tessellation_map = tess;
-
-            // check for validity in all induced classes, including the native one (class 0)
-            self.classes_to_check.clear();
-            self.checked_classes.clear();
-            self.classes_to_check.push(0);
-            while let Some(class) = self.classes_to_check.pop() {
-                self.checked_classes.push(class);
-                let c = Adj::CLASSES[class]; // for an effective offset (center), we can just use the class position itself
-
-                // check validity for this class - on failure move on to the next tessellation
-                self.codes.clear();
-                for pos in self.shape_with_padding {
-                    let loc = self.get_locating_code::<Adj>(*pos, c); // generate loc codes for this class
-                    if !self.codes.add(loc) {
-                        continue 'next_tess;
-                    }
-                }
-
-                // compute the induced classes from the current tessellation basis vectors
-                let b1 = tess.1;
-                let b2 = tess.2;
-                for &induced_class in &[Adj::class(c.0 + b1.0, c.1 + b1.1), Adj::class(c.0 + b2.0, c.1 + b2.1)] {
-                    // if we haven't seen it before, add it to the list of classes to check
-                    if !self.classes_to_check.contains(&induced_class) && !self.checked_classes.contains(&induced_class) {
-                        self.classes_to_check.push(induced_class);
-                    }
-                }
-            }
+fn tess_helper(mut tess: GeometryTessellation, param: &str, graph: &str, goal: &str, engine: Engine, enumerate: Enumerate, threads: usize) -> Result<(), Error> {
+    let param: Parameter = param.parse()?;
+    let graph: Graph = graph.parse()?;
 
-            // merciful domi, we've done it! update the source basis vecs before returning the good news
-            *self.src_basis_a = tess.1;
-            *self.src_basis_b = tess.2;
-            return true;
-        }
-        false // otherwise no tessellation worked - failure
+    if enumerate != Enumerate::First {
+        tess_helper_enumerate(&mut tess, param, &graph, goal, engine, enumerate == Enumerate::Unique)?;
+        return Ok(());
     }
-    fn try_satisfy<Adj: adj::AdjacentIterator>(&mut self, goal: Goal) -> Option<usize> {
-        assert_eq!(Adj::CLASSES[0], (0, 0)); // for the love of all that's holy let class 0 be an identity
-
-        self.old_set.clear();
-        self.needed = goal.get_value(self.shape.len());
-
-        if self.calc_old_min_interior::<Adj, _>(self.shape.iter().enumerate()) { Some(self.needed) } else { None }
+    match tessellation::solve(&mut tess, param, &graph, goal, engine, threads)? {
+        Some(min) => tess_helper_print(&tess, param, &graph, min),
+        None if output::is_json() => println!("{}", Json::object(json_header(param, &graph).into_iter().chain(vec![("found", false.into())]))),
+        None => println!("no solution found"),
     }
+    Ok(())
 }
-
-#[derive(Debug)]
-enum TessellationFailure {
-    NoValidTessellations,
+fn box_helper(dims: cubic::Point3, param: &str, lattice: &str, goal: &str) -> Result<(), Error> {
+    let param: Parameter = param.parse()?;
+    let lattice: Lattice = lattice.parse()?;
+
+    match cubic::solve(dims, param, lattice, goal)? {
+        Some(tess) => {
+            let (min, n) = (tess.geometry().detectors().len(), tess.geometry().size());
+            if output::is_json() {
+                let mut fields = json_header(param, lattice);
+                fields.extend(vec![
+                    ("found", true.into()),
+                    ("density", Json::rational(&BigRational::new(min.into(), n.into()))),
+                    ("density_float", (min as f64 / n as f64).into()),
+                    ("size", n.into()),
+                    ("dims", tess.geometry().extent().into()),
+                    ("detectors", tess.geometry().detectors().iter().copied().collect::<Vec<_>>().into()),
+                    ("basis", tess.basis().to_vec().into()),
+                ]);
+                println!("{}", Json::object(fields));
+            }
+            else {
+                let d = util::gcd(min, n);
+                println!("found a {}/{} ({}) solution:\n{}", min / d, n / d, min as f64 / n as f64, tess);
+            }
+        }
+        None if output::is_json() => println!("{}", Json::object(json_header(param, lattice).into_iter().chain(vec![("found", false.into())]))),
+        None => println!("no solution found"),
+    }
+    Ok(())
 }
-struct GeometryTessellation {
-    geo: Geometry,
-    interior: BTreeSet<(isize, isize)>,
-    shape_with_padding: BTreeSet<(isize, isize)>,
-    first_per_row: HashSet<(isize, isize)>,
-    tessellation_maps: Vec<TessellationMap>,
-    basis_a: (isize, isize),
-    basis_b: (isize, isize),
+fn cube_theo_helper(param: &str, lattice: &str, thresh: &str, strategy: TheoStrategy, threads: usize) -> Result<(), Error> {
+    let param: Parameter = param.parse()?;
+    let lattice: Lattice = lattice.parse()?;
+    let thresh = params::parse_thresh_frac(thresh)?;
+    if !output::is_json() {
+        println!("lower bound for {:?} set on {:?} lattice - {:?} thresh {}", param, lattice, strategy, thresh);
+    }
+
+    let mut stdout = io::stdout();
+    let mut sink = io::sink();
+    let pipe: &mut dyn io::Write = if output::is_json() { &mut sink } else { &mut stdout };
+    let problems = cubic::lower_bound(param, lattice, strategy, thresh.clone(), Some(pipe), threads)?;
+    theo_print_result(&mut io::stdout(), param, lattice, strategy, &thresh, &problems);
+    Ok(())
 }
-impl fmt::Display for GeometryTessellation {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "{}", self.geo)?;
-        writeln!(f, "basis: {:?} {:?}", self.basis_a, self.basis_b)?;
-        writeln!(f, "size: {}", self.size())?;
-        Ok(())
-    }
+fn strip_helper(width: usize, param: &str, graph: &str, cylinder: bool) -> Result<(), Error> {
+    let param: Parameter = param.parse()?;
+    let graph: Graph = graph.parse()?;
+    let shape = if cylinder { "cylinder" } else { "strip" };
+
+    let density = strip::min_density(param, &graph, width, cylinder)?;
+    if output::is_json() {
+        let mut fields = json_header(param, &graph);
+        fields.extend(vec![
+            ("width", width.into()),
+            ("cylinder", cylinder.into()),
+            ("found", density.is_some().into()),
+            ("density", density.as_ref().map(Json::rational).into()),
+            ("density_float", density.as_ref().and_then(|x| x.to_f64()).into()),
+        ]);
+        println!("{}", Json::object(fields));
+        return Ok(());
+    }
+    match density {
+        Some(x) => println!("optimal density on the width {} {}: {} ({})", width, shape, x, x.to_f64().unwrap_or(f64::NAN)),
+        None => println!("the width {} {} has no such set", width, shape),
+    }
+    Ok(())
 }
-impl GeometryTessellation {
-    fn solver<Codes>(&mut self) -> GeometrySolver<'_, Codes>
-    where Codes: codesets::Set<Item = (isize, isize)>
-    {
-        GeometrySolver::<Codes> {
-            shape: &self.geo.shape,
-            interior: &self.interior,
-            shape_with_padding: &self.shape_with_padding,
-            first_per_row: &self.first_per_row,
-            old_set: &mut self.geo.detectors,
-            
-            tessellation_maps: &self.tessellation_maps,
-            current_tessellation_map: &self.tessellation_maps[0],
-            src_basis_a: &mut self.basis_a,
-            src_basis_b: &mut self.basis_b,
-
-            codes: Default::default(),
-            needed: 0,
-
-            classes_to_check: Vec::with_capacity(8),
-            checked_classes: Vec::with_capacity(8),
+// streams every tiling meeting the goal, or with unique only one per class under translations, rotations and reflections.
+// returns the number of tilings reported.
+fn tess_helper_enumerate(tess: &mut GeometryTessellation, param: Parameter, graph: &Graph, goal: &str, engine: Engine, unique: bool) -> Result<usize, Error> {
+    let mut found = 0;
+    let report = |geo: &Geometry, basis: &[(isize, isize)], count: usize| {
+        found += 1;
+        if output::is_json() {
+            let mut fields = json_header(param, graph);
+            fields.push(("solution", found.into()));
+            if unique {
+                fields.push(("images", count.into()));
+            }
+            fields.extend(geometry_json_fields(geo, basis));
+            println!("{}", Json::object(fields));
+        }
+        else if unique {
+            println!("solution {} ({} distinct images):\n{}basis: {:?} {:?}", found, count, geo, basis[0], basis[1]);
         }
-    }
-}
-impl TryFrom<Geometry> for GeometryTessellation {
-    type Error = TessellationFailure;
-    fn try_from(geo: Geometry) -> Result<Self, Self::Error> {
-        let interior: BTreeSet<_> = geo.shape.iter().filter(|&x| adj::OpenKing::at(*x).all(|p| geo.shape.contains(&p))).copied().collect();
-        let first_per_row = {
-            let mut s: HashSet<(isize, isize)> = Default::default();
-            let mut r = !0;
-            for p in geo.shape.iter() {
-                if p.0 != r {
-                    r = p.0;
-                    s.insert(*p);
-                }
-            }
-            s
-        };
-
-        let shape_with_padding: BTreeSet<_> = {
-            let mut t = geo.shape.clone();
-            t.extend(geo.shape.iter().flat_map(|x| adj::OpenKing::at(*x)));
-            t
-        };
-        let shape_with_extra_padding: BTreeSet<_> = {
-            let mut t = shape_with_padding.clone();
-            t.extend(shape_with_padding.iter().flat_map(|x| adj::OpenKing::at(*x)));
-            t
-        };
-
-        let tessellation_maps: Vec<_> = {
-            let mut valid_tessellations: BTreeMap<BTreeMap<(isize, isize), (isize, isize)>, ((isize, isize), (isize, isize))> = Default::default();
-            let mut p: HashSet<(isize, isize)> = HashSet::with_capacity(geo.shape.len() * 25);
-            let mut m: BTreeMap<(isize, isize), (isize, isize)> = Default::default();
-            
-            // needs to be 2w and 2h so that we allow them to slip between one another
-            let basis_vecs: Vec<_> = (0..=2*geo.height()).flat_map(|r| (0..=2*geo.width()).map(move |c| (r, c))).collect();
-            for basis_a in basis_vecs.iter() {
-                'next_basis: for basis_b in basis_vecs.iter() {
-                    // clear the caches
-                    p.clear();
-                    m.clear();
-
-                    // attempt the tessellation
-                    for &to in geo.shape.iter() {
-                        for i in -3..=3 {
-                            for j in -3..=3 {
-                                let from = (to.0 + basis_a.0 * i + basis_b.0 * j, to.1 + basis_a.1 * i + basis_b.1 * j);
-                                if !p.insert(from) {
-                                    continue 'next_basis; // on overlap, this is no good - on to the next
-                                }
-                                if shape_with_extra_padding.contains(&from) {
-                                    m.insert(from, to);
-                                }
-                            }
-                        }
-                    }
-                    if m.len() != shape_with_extra_padding.len() {
-                        continue 'next_basis; // if tessellation is not dense, this is no good - on to the next
-                    }
-
-                    // we get to this point then tessellation is ok - add it - if we already had it, keep the old one
-                    valid_tessellations.entry(mem::take(&mut m)).or_insert((*basis_a, *basis_b));
-                }
-            }
-            if valid_tessellations.is_empty() {
-                return Err(TessellationFailure::NoValidTessellations);
-            }
-            valid_tessellations.into_iter().map(|(a, (b, c))| (a.into_iter().collect(), b, c)).collect()
-        };
-        let first_basis_a = tessellation_maps[0].1; // the specific values don't really matter, but better to at least use real values
-        let first_basis_b = tessellation_maps[0].2;
+        else {
+            println!("solution {}:\n{}basis: {:?} {:?}", found, geo, basis[0], basis[1]);
+        }
+        SearchCommand::Continue
+    };
+    tessellation::for_each_solution(tess, param, graph, goal, engine, unique, report)?;
 
-        Ok(Self {
-            geo, interior, shape_with_padding, tessellation_maps, first_per_row,
-            basis_a: first_basis_a,
-            basis_b: first_basis_b,
-        })
-    }
-}
-impl Tessellation for GeometryTessellation {
-    fn size(&self) -> usize {
-        self.geo.shape.len()
+    if output::is_json() {
+        println!("{}", Json::object(json_header(param, graph).into_iter().chain(vec![("solutions", found.into()), ("unique", unique.into())])));
     }
-    fn try_satisfy<Codes, Adj>(&mut self, goal: Goal) -> Option<usize>
-    where Codes: codesets::Set<Item = (isize, isize)>, Adj: adj::AdjacentIterator
-    {
-        self.solver::<Codes>().try_satisfy::<Adj>(goal)
+    else if unique {
+        println!("found {} solutions up to symmetry", found);
     }
-}
-
-struct ExpansionLands {
-    field: Vec<(isize, isize)>,
-    total_exterior: Vec<(isize, isize)>,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum TheoStrategy {
-    Trivial,
-    Avg,
-    Dis,
-    DisWeightExcess,
-    DisWeightShare,
-}
-impl Default for TheoStrategy {
-    fn default() -> Self {
-        TheoStrategy::Trivial
+    else {
+        println!("found {} solutions", found);
     }
+    Ok(found)
 }
-
-type Share = BigRational;
-
-#[derive(PartialEq, Eq)]
-enum SearchCommand {
-    Continue, Halt,
-}
-
-#[derive(PartialEq, PartialOrd, Eq, Ord)]
-struct TheoProblem {
-    center: (isize, isize),
-    share: Share,
-    avg_share: Share,
-    structure: String,
-}
-
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum MaxShareMode {
-    Max,
-    MaxOrOverThresh,
-}
-struct TheoSearcher<'a, 'b, Codes> {
-    center: (isize, isize),
-    closed_interior: &'a [(isize, isize)], // everything up to radius 2
-    exterior: &'a [(isize, isize)], // everything at exactly radius 3
-    detectors: &'a mut PointSet,
-
-    expansion_map: &'a PointMap<ExpansionLands>, // maps a neighbor of radius 1 or 2 to its outer points up to radius 2, plus a radius 3 border
-
-    codes: &'a mut Codes,
-
-    thresh: &'a Share,
-    pipe: &'a mut Option<&'b mut dyn io::Write>,
-    problems: &'a mut BTreeSet<TheoProblem>,
-    strategy: TheoStrategy,
-}
-impl<Codes> TheoSearcher<'_, '_, Codes>
-where Codes: codesets::Set<Item = (isize, isize)>
-{
-    #[must_use]
-    fn get_locating_code<Adj>(&self, pos: (isize, isize)) -> Codes::LocatingCode
-    where Adj: AdjacentIterator
-    {
-        let mut v = Vec::with_capacity(9);
-        for p in Adj::at(pos) {
-            if self.detectors.contains(&p) {
-                v.push(p);
-            }
-        }
-        let is_detector = self.detectors.contains(&pos);
-        Codes::LocatingCode::new(pos, is_detector, v)
-    }
-    #[must_use]
-    fn is_valid_over<Adj, T>(&mut self, range: T) -> bool
-    where Adj: AdjacentIterator, T: Iterator<Item = (isize, isize)>
-    {
-        self.codes.clear();
-        for p in range {
-            let loc = self.get_locating_code::<Adj>(p);
-            if !self.codes.add(loc) {
-                return false;
+#[test]
+fn test_tess_enumerate() {
+    for &(rows, cols, param, graph, goal) in &[(2, 2, "old", "grid", "0.5"), (3, 3, "ic", "king", "0.5"), (2, 3, "ld", "hex", "0.5"), (2, 2, "dom", "tmb", "0.5")] {
+        let param: Parameter = param.parse().unwrap();
+        let graph: Graph = graph.parse().unwrap();
+        let mut counts = vec![];
+        for &engine in &[Engine::Recursive, Engine::Sat] {
+            for &unique in &[false, true] {
+                let mut tess = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                counts.push(tess_helper_enumerate(&mut tess, param, &graph, goal, engine, unique).unwrap());
             }
         }
-        true
+        assert_eq!(counts[0], counts[2]); // both engines find the same tilings
+        assert_le!(counts[1], counts[0]);
+        assert_le!(counts[3], counts[2]);
+        assert_eq!(counts[1] > 0, counts[0] > 0);
+        assert_eq!(counts[0] > 0, tessellation::solve(&mut GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap(), param, &graph, goal, Engine::Recursive, 1).unwrap().is_some());
     }
-    #[must_use]
-    fn calc_share<Adj, ShareAdj>(&self, pos: (isize, isize)) -> Share
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator
-    {
-        assert!(self.detectors.contains(&pos));
 
-        let mut share = Zero::zero();
-        for p in ShareAdj::at(pos) {
-            let c = self.get_locating_code::<Adj>(p).dom();
-            share += Share::new(One::one(), c.into());
-        }
-        share
+    // half of a 2x2 king tile is a domino or a diagonal - the vertical domino is the horizontal one turned, so it isn't counted again
+    for &engine in &[Engine::Recursive, Engine::Sat] {
+        let mut tess = GeometryTessellation::try_from(Geometry::rectangle(2, 2)).unwrap();
+        assert_eq!(tess_helper_enumerate(&mut tess, Parameter::DOM, &Graph::K, "0.5", engine, true).unwrap(), 2);
     }
-    fn max_simultaneous_problem_neighbors_recursive<Adj, ShareAdj>(&mut self, neighbors: &[(isize, isize)], total_field: &[(isize, isize)], total_exterior: &[(isize, isize)], mut field_pos: std::slice::Iter<(isize, isize)>) -> Option<usize>
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator
-    {
-        match field_pos.next() {
-            None => {
-                // if it's not a valid configuration, don't even bother looking at it (return None to denote illegality)
-                for point in total_exterior {
-                    self.detectors.insert(*point);
-                }
-                if !self.is_valid_over::<Adj, _>(self.closed_interior.iter().chain(total_field.iter()).copied()) {
-                    return None;
-                }
-                // otherwise count the number of neighbors with problem shares
-                Some(neighbors.iter().filter(|x| self.calc_share::<Adj, ShareAdj>(**x) > *self.thresh).count())
-            }
-            Some(p) => {
-                // perform the recursive field expansion
-                self.detectors.insert(*p);
-                let a = self.max_simultaneous_problem_neighbors_recursive::<Adj, ShareAdj>(neighbors, total_field, total_exterior, field_pos.clone());
-                self.detectors.remove(p);
-                let b = self.max_simultaneous_problem_neighbors_recursive::<Adj, ShareAdj>(neighbors, total_field, total_exterior, field_pos.clone());
-
-                // return none if both failed, otherwise return whatever was larger
-                match (a, b) {
-                    (None, None) => None,
-                    (Some(a), None) => Some(a),
-                    (None, Some(b)) => Some(b),
-                    (Some(a), Some(b)) => Some(a.max(b)),
-                }
-            }
-        }
-    }
-    fn max_simultaneous_problem_neighbors<Adj, ShareAdj>(&mut self, pos: (isize, isize)) -> usize
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator
-    {
-        assert!(self.detectors.contains(&pos));
-        let mut non_center_neighbors = Vec::with_capacity(8);
-        for neighbor in Adj::Open::at(pos) {
-            if neighbor != self.center && self.detectors.contains(&neighbor) {
-                non_center_neighbors.push(neighbor);
-            }
-        }
-        if non_center_neighbors.is_empty() {
-            return 1; // if there are no non-center neighbors, we just have the one problem (center is assumed to have problem share)
-        }
-
-        // gather up all the fields we will have to expand in total
-        let bounds = ((self.center.0 - 5, self.center.1 - 5), (self.center.0 + 5, self.center.1 + 5));
-        let mut total_field = PointSet::with_bounds(bounds.0, bounds.1);
-        let mut total_exterior = PointSet::with_bounds(bounds.0, bounds.1);
-
-        for neighbor in non_center_neighbors.iter() {
-            let lands = self.expansion_map.get(neighbor).unwrap();
-            total_field.extend(lands.field.iter().copied());
-            total_exterior.extend(lands.total_exterior.iter().copied());
-        }
-        for point in total_field.iter() {
-            total_exterior.remove(&point);
-        }
+}
+fn entropy_helper(big_geo: Geometry, entropy_size: &str, param: &str, graph: &str, goal: &str, threadc: &str, checkpoint: &CheckpointOptions) -> Result<(), Error> {
+    let param: Parameter = param.parse()?;
+    let graph: Graph = graph.parse()?;
 
-        #[cfg(debug)]
-        {
-            println!("total field:\n{}", Geometry::for_printing(&total_field.iter().collect(), self.detectors.iter()));
-            println!("total exterior:\n{}", Geometry::for_printing(&total_exterior.iter().collect(), self.detectors.iter()));
-            println!("badditude: {}", total_field.iter().count());
-            println!("-------------------------------------");
-        }
+    let entropy_size = match entropy_size.parse::<usize>() {
+        Ok(v) => v,
+        Err(_) => return Err(ValueError::Malformed(format!("failed to parse '{}' as positive integer", entropy_size)).into()),
+    };
+    let cpus = num_cpus::get();
+    let threadc = match threadc.parse::<usize>() {
+        Ok(0) => return Err(ValueError::OutOfRange("cannot use 0 threads".to_owned()).into()),
+        Ok(x) if x > cpus => return Err(ValueError::OutOfRange(format!("this system has only {} cores, but {} were requested", cpus, x)).into()),
+        Ok(x) => x,
+        Err(_) => return Err(ValueError::Malformed(format!("failed to parse '{}' as positive integer", threadc)).into()),
+    };
 
-        // convert sets into vectors for fastitude
-        let total_field = total_field.iter().collect::<Vec<_>>();
-        let total_exterior = total_exterior.iter().collect::<Vec<_>>();
-        
-        // we have the center problem (by assumption), plus max of non-center neighbors via recursive expansion
-        1 + self.max_simultaneous_problem_neighbors_recursive::<Adj, ShareAdj>(&non_center_neighbors, &total_field, &total_exterior, total_field.iter()).unwrap()
+    let mut search = EntropySearch::new(big_geo, entropy_size, param, graph.clone(), goal)?;
+    if let Some(resume) = &checkpoint.resume {
+        let (done, redo) = search.resume(resume)?;
+        info!("resuming from checkpoint {} ({} geometries done, {} to redo)", resume, done, redo);
     }
-    #[must_use]
-    fn calc_max_share_expansion_recursive<Adj, ShareAdj, P>(&mut self, pos: (isize, isize), lands: &ExpansionLands, mut ext_pos: P, mode: MaxShareMode) -> Share
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator, P: Iterator<Item = (isize, isize)> + Clone
-    {
-        match ext_pos.next() {
-            None => {
-                // if it's an invalid configuration, don't even bother looking at it
-                for p in lands.total_exterior.iter() {
-                    self.detectors.insert(*p);
-                }
-                if !self.is_valid_over::<Adj, _>(self.closed_interior.iter().chain(lands.field.iter()).copied()) {
-                    return -Share::one(); // return -1 to denote nothing (no share here at all)
-                }
-
-                // otherwise return the share
-                return self.calc_share::<Adj, ShareAdj>(pos);
-            }
-            Some(p) => {
-                self.detectors.insert(p);
-                let r1 = self.calc_max_share_expansion_recursive::<Adj, ShareAdj, _>(pos, lands, ext_pos.clone(), mode);
-                if mode == MaxShareMode::MaxOrOverThresh && &r1 > self.thresh {
-                    return r1; // if > thresh max will be too - short circuit if allowed
-                }
-                self.detectors.remove(&p);
-                let r2 = self.calc_max_share_expansion_recursive::<Adj, ShareAdj, _>(pos, lands, ext_pos, mode);
+    let res = search.run(threadc, checkpoint.saver())?;
 
-                // return max share found
-                return if r1 >= r2 { r1 } else { r2 };
-            }
+    match res.found {
+        Some((tess, min)) => tess_helper_print(&tess, param, &graph, min),
+        None if output::is_json() => {
+            println!("{}", Json::object(json_header(param, &graph).into_iter().chain(vec![("found", false.into()), ("tested", res.tested.into())])));
         }
+        None => println!("no solution found (tested {} geometries)", res.tested),
+    }
+    Ok(())
+}
+#[allow(clippy::too_many_arguments)]
+fn theo_helper(param: &str, graph: &str, thresh: &str, strategy: TheoStrategy, mut pipe: Option<&mut dyn io::Write>, checkpoint: Option<&CheckpointOptions>, cert: Option<&str>, threads: usize) -> Result<bool, Error> {
+    let param: Parameter = param.parse()?;
+    let graph: Graph = graph.parse()?;
+    if cert.is_some() {
+        theo::check_certificate(&graph, strategy)?;
     }
-    // expands around boundary to radius 2, returning the maximum share or some possible share > thresh for short-circuitting.
-    // returns -1 if no valid configuration exists.
-    #[must_use]
-    fn calc_max_share_expansion<Adj, ShareAdj>(&mut self, pos: (isize, isize), mode: MaxShareMode) -> Share
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator
-    {
-        let lands = self.expansion_map.get(&pos).unwrap();
 
-        // go ahead and prepare the total exterior before we start searching so we don't have to do it at every terminal node
-        for p in lands.total_exterior.iter() {
-            self.detectors.insert(*p);
+    let thresh = params::parse_thresh_frac(thresh)?;
+    if let Some(ref mut f) = pipe {
+        if !output::is_json() {
+            writeln!(f, "lower bound for {:?} set on {:?} graph - {:?} thresh {}", param, graph, strategy, thresh).unwrap();
         }
-
-        // compute the max share recursively
-        self.calc_max_share_expansion_recursive::<Adj, ShareAdj, _>(pos, lands, lands.field.iter().copied(), mode)
     }
-    #[must_use]
-    fn do_averaging<Adj, ShareAdj>(&mut self, center_share: &Share) -> Share
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator
-    {
-        assert_gt!(center_share, &self.thresh); // by hypothesis, center is a problem
 
-        // cache share values since they take forever to compute
-        let mut shares: HashMap<(isize, isize), Share> = HashMap::with_capacity(25);
-        shares.insert(self.center, center_share.clone());
-
-        // also keep track of averaging candidates
-        let mut candidates: Vec<(Share, (isize, isize))> = Default::default();
-
-        // compute the weakest max share mode we need in order to work
-        let max_share_mode = match self.strategy {
-            TheoStrategy::Trivial => panic!("we shouldn't be here"),
-            TheoStrategy::Avg | TheoStrategy::Dis => MaxShareMode::MaxOrOverThresh,
-            TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare => MaxShareMode::Max,
+    let mut checkpoint = checkpoint.and_then(|opts| opts.saver().map(|saver| {
+        let search = periodic::spell_out(format!("theo {:?} {:?} {:?} {}", param, graph, strategy, thresh), &graph);
+        let (class, path, problems) = match &opts.resume {
+            Some(resume) => theo::read_theo_checkpoint(resume, &search).map_err(|source| Error::Checkpoint { path: resume.clone(), source })?,
+            None => Default::default(),
         };
-
-        // for each neighbor of center which is a detector
-        for neighbor in Adj::Open::at(self.center) {
-            if !self.detectors.contains(&neighbor) {
-                continue;
-            }
-
-            // compute max share of neighbor and store in cache
-            let share = match self.calc_max_share_expansion::<Adj, ShareAdj>(neighbor, max_share_mode) {
-                x if x < Share::zero() => return Share::zero(), // if invalid there were no legal configurations in the first place - return something that will always be <= thresh
-                x => x,
-            };
-            shares.insert(neighbor, share.clone());
-
-            // if this is strictly less than thresh it's an averaging candidate
-            if &share < self.thresh {
-                candidates.push((share, neighbor));
-            }
-        }
-
-        // sort averagee/discharge candidates by ascending share (use position to break ties just to guarantee invariant exec order)
-        candidates.sort();
-
-        // go through the average/discharge candidates and keep track of working share as we do averaging/discharging
-        let mut working_share = center_share.clone();
-        'next_candidate: for (share, neighbor) in candidates.iter() {
-            // look at each of my adjacent detectors and keep track of how many problems i'm next to
-            let mut adj_problems = 0;
-            let mut sum_weights = Share::zero(); // this is only updated if using weighted strategy
-            for other in Adj::Open::at(*neighbor) {
-                if !self.detectors.contains(&other) {
-                    continue;
-                }
-
-                // compute its max share - use cache for lookups when possible (at this point center and neighbors are in cache, so only misses are boundary points)
-                let sh = match shares.entry(other).or_insert_with(|| self.calc_max_share_expansion::<Adj, ShareAdj>(other, max_share_mode)) {
-                    x if *x < Share::zero() => return Share::zero(), // if invalid there were no legal configurations in the first place - return something that will always be <= thresh
-                    x => x,
-                };
-
-                // if this is strictly larger than thresh it's a problem
-                if &*sh > self.thresh {
-                    adj_problems += 1; // mark as a problem
-
-                    // do any extra needed work for whatever strategy we're using
-                    match self.strategy {
-                        TheoStrategy::Trivial => panic!("we shouldn't be here"),
-                        TheoStrategy::Avg => if adj_problems > 1 { continue 'next_candidate; } // averaging is discharging that requires adj_problems == 1
-                        TheoStrategy::Dis => (),                                               // discharge has no other requirements
-                        TheoStrategy::DisWeightExcess => sum_weights += &*sh - self.thresh,    // weighted discharging with excess
-                        TheoStrategy::DisWeightShare => sum_weights += &*sh,                   // weighted discharging with share
-                    }
-                }
-            }
-            assert_ne!(adj_problems, 0); // this should never be zero because by hypothesis center itself is a problem
-
-            // compute the total amount of safe discharge
-            let max_safe_discharge = {
-                let pool = self.thresh - share; // the total amount of share this neighbor can accept
-                match self.strategy {
-                    TheoStrategy::Trivial => panic!("we shouldn't be here"),
-                    TheoStrategy::Avg | TheoStrategy::Dis => pool / Share::from_integer(adj_problems.into()), // same logic due to above
-                    TheoStrategy::DisWeightExcess => pool * ((center_share - self.thresh) / sum_weights),     // same logic as avg/dis, except weighted by excess
-                    TheoStrategy::DisWeightShare => pool * (center_share / sum_weights),                      // same but different weights
-                }
-            };
-            assert_gt!(max_safe_discharge, Share::zero()); // sanity check
-
-            // apply maximum safe discharging - if we drop down to or below the target thresh, we're done - yay!
-            working_share -= max_safe_discharge;
-            if &working_share <= self.thresh {
-                return working_share;
+        if let (Some(resume), true) = (&opts.resume, pipe.is_some()) {
+            info!("resuming from checkpoint {} (center class {}, {} problems so far)", resume, class, problems.len());
+            if !problems.is_empty() && !output::is_json() {
+                println!("encountered problems...\n"); // the early warning won't show up again
             }
         }
-        // otherwise we failed to correct - discharge still has some hope
-        match self.strategy {
-            TheoStrategy::Trivial => panic!("we shouldn't be here"),
-            TheoStrategy::Avg => (), // this can't do anything better
-            TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare => (), // these aren't supported for batch discharge source logic yet - probably never will be
-            TheoStrategy::Dis => {
-                let mut new_working_share = center_share.clone(); // get a new working share
-
-                // go through the candidates again
-                for (share, neighbor) in candidates.iter() {
-                    let simultaneous_adj_problems = self.max_simultaneous_problem_neighbors::<Adj, ShareAdj>(*neighbor);
-                    assert!(simultaneous_adj_problems > 0); // sanity check
-
-                    let dis = (self.thresh - share) / Share::from_integer(simultaneous_adj_problems.into());
-                    assert_gt!(dis, Share::zero()); // sanity check
-
-                    new_working_share -= dis;
-                    if &new_working_share <= self.thresh {
-                        return new_working_share;
-                    }
-                }
-
-                // at the end we should at least do as well as the non-simultaneous expansion
-                assert_le!(new_working_share, working_share);
-                working_share = new_working_share; // replace with the better value
-            }
-        }
-        // but if that also failed, just return the best we could do
-        working_share
-    }
-    #[must_use]
-    fn calc_recursive<Adj, ShareAdj, P>(&mut self, mut pos: P) -> SearchCommand
-    where Adj: AdjacentIterator, ShareAdj: AdjacentIterator, P: Iterator<Item = (isize, isize)> + Clone
-    {
-        match pos.next() {
-            // if we have no positions remaining, check for first order validity
-            None => {
-                // fill in the exterior
-                for p in self.exterior.iter() {
-                    self.detectors.insert(*p);
-                }
-                // if not valid over the ball2 iter field, ignore (invalid configuration)
-                if !self.is_valid_over::<Adj, _>(self.closed_interior.iter().copied()) {
-                    return SearchCommand::Continue;
-                }
-
-                // compute share of center
-                let share = self.calc_share::<Adj, ShareAdj>(self.center);
-                
-                // compute average share - if share is over thresh, attempt to perform averaging if enabled, otherwise just use same value
-                let avg_share = {
-                    if &share > self.thresh && self.strategy != TheoStrategy::Trivial {
-                        let avg = self.do_averaging::<Adj, ShareAdj>(&share);
-                        assert_ge!(avg, Share::zero()); // should be valid
-                        assert_le!(avg, share); // should never be worse than we started with
-                        avg
-                    }
-                    else {
-                        share.clone() 
-                    }
-                };
+        Ok::<_, Error>(TheoCheckpoint { search, saver, class, path, problems })
+    })).transpose()?;
 
-                // if it was over thresh, display as problem case
-                if &avg_share > self.thresh {
-                    // gather up all the info into a problem description
-                    let geo = Geometry::for_printing(&self.closed_interior.iter().copied().collect(), self.detectors.iter());
-                    let structure = format!("{}", geo);
-                    let problem = TheoProblem {
-                        center: self.center,
-                        share,
-                        avg_share,
-                        structure,
-                    };
-                    // add to problems list (automatically sorted like we want)
-                    self.problems.insert(problem);
-
-                    match *self.pipe {
-                        // if printing enabled, show early warning if this was the first problem (nice since we delay problem printing till end for sorted order)
-                        Some(ref mut f) => {
-                            if self.problems.len() == 1 {
-                                writeln!(f, "encountered problems...\n").unwrap();
-                            }
-                        }
-                        // otherwise we're not interested in the problems, so stop looking for more of them
-                        None => return SearchCommand::Halt,
-                    }
-                }
-
-                return SearchCommand::Continue;
-            }
-            // otherwise recurse on both branches at this position
-            Some(p) => {
-                self.detectors.insert(p);
-                if self.calc_recursive::<Adj, ShareAdj, _>(pos.clone()) == SearchCommand::Halt {
-                    return SearchCommand::Halt;
+    // the early warning is only for text output, but the search still needs a pipe to collect every problem
+    let mut sink = io::sink();
+    let search_pipe = match pipe.as_mut() {
+        Some(_) if output::is_json() => Some(&mut sink as &mut dyn io::Write),
+        Some(f) => Some(&mut **f as &mut dyn io::Write),
+        None => None,
+    };
+    // the certificate is written as the search goes, and thrown away if the bound doesn't hold after all
+    let mut cert_file = match cert {
+        Some(path) => {
+            let mut f = io::BufWriter::new(File::create(path).map_err(|e| Error::io("create certificate", path, e))?);
+            f.write_all(certificate::header(param, &graph, strategy, &thresh).as_bytes()).map_err(|e| Error::io("write certificate", path, e))?;
+            Some((path, f))
+        }
+        None => None,
+    };
+    let res = {
+        let mut cert = cert_file.as_mut().map(|(path, f)| TheoCertificate { path: path.to_string(), out: f });
+        theo::lower_bound(param, &graph, strategy, thresh.clone(), search_pipe, checkpoint.as_mut(), cert.as_mut(), threads)
+    };
+    if let Some((path, mut f)) = cert_file {
+        match &res {
+            Ok(problems) if problems.is_empty() => {
+                f.flush().map_err(|e| Error::io("write certificate", path, e))?;
+                if pipe.is_some() {
+                    info!("wrote certificate to {}", path);
                 }
-                self.detectors.remove(&p);
-                return self.calc_recursive::<Adj, ShareAdj, _>(pos);
             }
-        }
-    }
-}
-#[must_use]
-fn calc_lower_bound<Codes, Adj, ShareAdj>(strategy: TheoStrategy, thresh: Share, mut pipe: Option<&mut dyn io::Write>) -> bool
-where Codes: codesets::Set<Item = (isize, isize)> + 'static, Adj: AdjacentIterator, ShareAdj: AdjacentIterator
-{
-    assert_gt!(thresh, Share::zero()); // we require thresh in (0, 1]
-    assert_le!(thresh, Share::one());
-
-    let mut closed_interior = PointSet::default(); // everything up to radius 2
-    let mut open_interior = PointSet::default();   // everything up to radius 2 except center
-    let mut exterior = PointSet::default();        // everything at exactly radius 3
-    let mut detectors = PointSet::default();
-
-    let mut expansion_map = PointMap::default();
-
-    let mut codes: Codes = Default::default();
-    let mut problems: BTreeSet<TheoProblem> = Default::default();
-
-    // convenience function since PointSet cannot impl FromIterator
-    fn collect<I: IntoIterator<Item = (isize, isize)>>(bounds: &((isize, isize), (isize, isize)), iter: I) -> PointSet {
-        let mut s = PointSet::with_bounds(bounds.0, bounds.1);
-        s.extend(iter);
-        s
-    };
-
-    let share_thresh = thresh.recip();
-
-    // fold recursive results from all provided center values
-    for &center in Adj::CLASSES {
-        // set bounds for all the sets/maps we need
-        let bounds = ((center.0 - 5, center.1 - 5), (center.0 + 5, center.1 + 5));
-
-        closed_interior.set_bounds(bounds.0, bounds.1);
-        open_interior.set_bounds(bounds.0, bounds.1);
-        exterior.set_bounds(bounds.0, bounds.1);
-        detectors.set_bounds(bounds.0, bounds.1);
-    
-        expansion_map.set_bounds(bounds.0, bounds.1);
-        
-        // --------------------------------------------------------------------------------
-
-        // generate closed interior - everything up to radius 2
-        closed_interior.clear();
-        closed_interior.extend(Adj::Open::at(center).flat_map(Adj::Closed::at));
-
-        #[cfg(debug)]
-        println!("closed interior:\n{}", Geometry::for_printing(&closed_interior, &Default::default()));
-
-        // generate open interior - everything up to radius 2 except the center
-        open_interior.clone_from(&closed_interior);
-        open_interior.remove(&center);
-
-        #[cfg(debug)]
-        println!("open interior:\n{}", Geometry::for_printing(&open_interior, &Default::default()));
-
-        // generate exterior - everything at exactly radius 3
-        exterior.clear();
-        exterior.extend(closed_interior.iter().flat_map(Adj::Open::at).filter(|p| !closed_interior.contains(p)));
-
-        #[cfg(debug)]
-        println!("exterior:\n{}", Geometry::for_printing(&exterior, &Default::default()));
-
-        // generate boundary - everything at exactly radius 2
-        let boundary = collect(&bounds, exterior.iter().flat_map(Adj::Open::at).filter(|p| closed_interior.contains(p)));
-
-        #[cfg(debug)]
-        println!("boundary:\n{}", Geometry::for_printing(&boundary, &Default::default()));
-
-        // clear and repopulate expansion map with neighbors and boundary points
-        expansion_map.clear();
-        for p in Adj::Open::at(center).chain(boundary.iter()) {
-            let ball2 = collect(&bounds, Adj::Open::at(p).flat_map(Adj::Closed::at));
-            let ball3 = collect(&bounds, ball2.iter().flat_map(Adj::Closed::at));
-
-            let field = collect(&bounds, ball2.iter().filter(|x| !closed_interior.contains(x)));
-            let total_exterior = collect(&bounds, exterior.iter().chain(ball3.iter()).filter(|x| !closed_interior.contains(x) && !field.contains(x)));
-
-            let lands = ExpansionLands {
-                field: field.iter().collect(),
-                total_exterior: total_exterior.iter().collect(),
-            };
-            #[cfg(debug)]
-            {
-                println!("{:?} field:          {:?}", p, lands.field);
-                println!("{:?} total_exterior: {:?}", p, lands.total_exterior);
-                println!();
+            _ => {
+                drop(f);
+                std::fs::remove_file(path).ok();
             }
-
-            expansion_map.insert(p, lands);
-        }
-
-        // each pass starts with no detectors except the center
-        detectors.clear();
-        detectors.insert(center);
-
-        // generate recursive search object (encodes the borrow contracts for borrowchecker)
-        let closed_interior_vec: Vec<_> = closed_interior.iter().collect();
-        let exterior_vec: Vec<_> = exterior.iter().collect();
-        let mut searcher = TheoSearcher {
-            center,
-            closed_interior: &closed_interior_vec,
-            exterior: &exterior_vec,
-            detectors: &mut detectors,
-
-            expansion_map: &expansion_map,
-
-            codes: &mut codes,
-
-            thresh: &share_thresh,
-            pipe: &mut pipe,
-            problems: &mut problems,
-            strategy,
-        };
-
-        // perform center folding
-        if searcher.calc_recursive::<Adj, ShareAdj, _>(open_interior.iter()) == SearchCommand::Halt {
-            break;
         }
     }
-
+    let problems = res?;
     if let Some(f) = pipe {
-        // if there were no problems then we've proven thresh works
-        if problems.is_empty() {
-            // attempt to convert to a floating-point representation
-            let float = match thresh.numer().to_string().parse::<f64>() {
-                Ok(n) => match thresh.denom().to_string().parse::<f64>() {
-                    Ok(d) => (n / d).to_string(),
-                    Err(_) => "f64 overflow".to_string(),
-                },
-                Err(_) => "f64 overflow".to_string(),
-            };
-
-            // print results and return what we found
-            writeln!(f, "found theo lower bound {} ({})", thresh, float).unwrap();
-        }
-        // otherwise there were problems - conservatively, all we can say is that it didn't work
-        else {
-            // print out all the problems we encountered (already in desired print order)
-            for p in problems.iter() {
-                writeln!(f, "problem: {} ({}) (center {:?})\n{}", p.share, p.avg_share, p.center, p.structure).unwrap();
-            }
-            writeln!(f, "total problems: {}", problems.len()).unwrap();
-        }
+        theo_print_result(f, param, &graph, strategy, &thresh, &problems);
     }
 
     // return true if we succeeded, otherwise false
-    problems.is_empty()
+    Ok(problems.is_empty())
 }
-
-#[derive(Debug)]
-enum AdjType {
-    Open, Closed
-}
-
-struct FiniteGraphSolver<'a, Codes> {
-    verts: &'a [Vertex],
-    detectors: &'a mut HashSet<usize>,
-    needed: usize,
-    codes: Codes,
-    adj_type: AdjType,
-}
-impl<Codes> FiniteGraphSolver<'_, Codes>
-where Codes: codesets::Set<Item = usize>
-{
-    fn get_raw_locating_code(&self, p: usize) -> Vec<usize> {
-        let mut v = Vec::with_capacity(9);
-        let adj = match self.adj_type {
-            AdjType::Open => self.verts[p].open_adj.iter(),
-            AdjType::Closed => self.verts[p].closed_adj.iter(),
-        };
-        for x in adj {
-            if self.detectors.contains(x) {
-                v.push(*x);
-            }
-        }
-        v
+fn verify_cert_helper(path: &str) -> Result<(), Error> {
+    let f = File::open(path).map_err(|e| Error::io("open certificate", path, e))?;
+    let v = certificate::verify(path, BufReader::new(f))?;
+    if output::is_json() {
+        let mut fields = json_header(v.param, &v.graph);
+        fields.extend(vec![
+            ("strategy", format!("{:?}", v.strategy).into()),
+            ("thresh", Json::rational(&v.thresh)),
+            ("verified", true.into()),
+            ("configurations", v.configs.into()),
+        ]);
+        println!("{}", Json::object(fields));
     }
-    fn is_old(&mut self) -> bool {
-        self.codes.clear();
-        for i in 0..self.verts.len() {
-            let is_detector = self.detectors.contains(&i);
-            let v = self.get_raw_locating_code(i);
-            let code = Codes::LocatingCode::new(i, is_detector, v);
-            if !self.codes.add(code) {
-                return false;
-            }
-        }
-        true
+    else {
+        println!("verified certificate: {} is a lower bound for {:?} set on {:?} graph - {:?} ({} configurations)", v.thresh, v.param, v.graph, v.strategy, v.configs);
     }
-    fn find_solution_recursive(&mut self, pos: usize) -> bool {
-        if self.needed == self.detectors.len() {
-            if self.is_old() {
-                return true;
-            }
+    Ok(())
+}
+// searches for the best lower bound theo can prove, printing each step and the best certified bound once one of the limits is hit
+fn auto_theo_helper(set: &str, graph: &str, strategy: TheoStrategy, search: AutoTheoSearch, limits: &AutoTheoLimits, threads: usize) -> Result<BigRational, Error> {
+    let param: Parameter = set.parse()?;
+    let graph: Graph = graph.parse()?;
+    let res = theo::auto_lower_bound(param, &graph, strategy, search, limits, threads, |low, high, rat| {
+        if output::is_json() {
+            println!("{}", Json::object(vec![("low", Json::rational(low)), ("high", Json::rational(high)), ("prediction", Json::rational(rat))]));
         }
-        else if pos < self.verts.len() {
-            self.detectors.insert(pos);
-            if self.find_solution_recursive(pos + 1) {
-                return true;
-            }
-            self.detectors.remove(&pos);
-            return self.find_solution_recursive(pos + 1);
+        else {
+            println!("search space: [{}, {}]\nprediction: {}", low, high, rat);
         }
+    })?;
 
-        false
+    let float = res.bound.to_f64().unwrap_or(f64::NAN);
+    if output::is_json() {
+        println!("{}", Json::object(vec![("lower_bound", Json::rational(&res.bound)), ("lower_bound_float", float.into()), ("iterations", res.iterations.into()), ("stop", res.stop.to_string().into())]));
     }
-    fn find_solution(&mut self, n: usize, adj_type: AdjType) -> bool {
-        self.detectors.clear();
-        self.needed = n;
-        self.adj_type = adj_type;
-        self.find_solution_recursive(0)
+    else {
+        println!("certified lower bound {} ({}) after {} iterations - {}", res.bound, float, res.iterations, res.stop);
     }
+    Ok(res.bound)
 }
-
-enum GraphLoadError {
-    FileOpenFailure,
-    InvalidFormat(&'static str),
+#[derive(Default)]
+struct CheckpointOptions {
+    save: Option<String>,
+    resume: Option<String>, // also where new checkpoints go, unless --checkpoint says otherwise
 }
-struct Vertex {
-    label: String,
-    open_adj: Vec<usize>,
-    closed_adj: Vec<usize>,
+impl CheckpointOptions {
+    fn saver(&self) -> Option<checkpoint::Saver> {
+        self.save.as_ref().or(self.resume.as_ref()).map(|path| checkpoint::Saver::new(path, checkpoint::INTERVAL))
+    }
 }
-struct FiniteGraph {
-    verts: Vec<Vertex>,
-    detectors: HashSet<usize>,
+fn parse_checkpoint_options(m: &Matches) -> CheckpointOptions {
+    CheckpointOptions { save: m.value("--checkpoint").map(str::to_owned), resume: m.value("--resume").map(str::to_owned) }
 }
-impl FiniteGraph {
-    fn with_shape<P: AsRef<Path>>(path: P) -> Result<Self, GraphLoadError> {
-        let mut f = BufReader::new(match File::open(path) {
-            Ok(x) => x,
-            Err(_) => return Err(GraphLoadError::FileOpenFailure),
-        });
-
-        struct Vertexish {
-            label: String,
-            adj: BTreeSet<usize>,
-        }
-        let mut v: Vec<Vertexish> = vec![];
-        let mut m: HashMap<String, usize> = Default::default();
-
-        let get_vert = |verts: &mut Vec<Vertexish>, map: &mut HashMap<String, usize>, a: &str| {
-            match map.get(a) {
-                Some(&p) => p,
-                None => {
-                    verts.push(Vertexish {
-                        label: a.into(),
-                        adj: Default::default(),
-                    });
-                    let p = verts.len() - 1;
-                    map.insert(a.into(), p);
-                    p
-                }
-            }
-        };
-        let mut add_edge = |a: &str, b: &str| {
-            let idx_a = get_vert(&mut v, &mut m, a);
-            let idx_b = get_vert(&mut v, &mut m, b);
-            v[idx_a].adj.insert(idx_b);
-            v[idx_b].adj.insert(idx_a);
-        };
-
-        let mut s = String::new();
-        while { s.clear(); let r = f.read_line(&mut s); r.is_ok() && r.unwrap() != 0 } {
-            for tok in s.split_whitespace() {
-                let p = match tok.find(':') {
-                    Some(x) => x,
-                    None => return Err(GraphLoadError::InvalidFormat("encountered token without a ':' separator")),
-                };
-                let a = tok[..p].trim();
-                let b = tok[p+1..].trim();
-                if b.find(':').is_some() {
-                    return Err(GraphLoadError::InvalidFormat("encoundered token with multiple ':' separators"));
-                }
-                if a == b {
-                    return Err(GraphLoadError::InvalidFormat("encountered reflexive connection"));
-                }
-                add_edge(a, b);
-            }
-        }
-
-        let mut verts: Vec<Vertex> = Vec::with_capacity(v.len());
-        for (i, mut vert) in v.into_iter().enumerate() {
-            let open_adj = vert.adj.iter().copied().collect();
-            vert.adj.insert(i);
-            let closed_adj = vert.adj.iter().copied().collect();
-            verts.push(Vertex {
-                label: vert.label,
-                open_adj,
-                closed_adj,
-            });
-        }
-        Ok(FiniteGraph {
-            verts,
-            detectors: Default::default(),
-        })
-    }
-    fn solver<Codes>(&mut self) -> FiniteGraphSolver<'_, Codes>
-    where Codes: codesets::Set<Item = usize>
-    {
-        FiniteGraphSolver {
-            verts: &self.verts,
-            detectors: &mut self.detectors,
-            needed: 0,
-            codes: Default::default(),
-            adj_type: AdjType::Open,
-        }
+enum FiniteMode<'a> {
+    Search(SearchOptions),
+    ExportCnf(&'a str),
+    CheckModel(&'a str),
+}
+fn parse_finite_mode<'a>(m: &Matches<'a>) -> Result<FiniteMode<'a>, Error> {
+    let searching = ["--sat", "--all", "--unique", "--symmetry"].iter().any(|flag| m.flag(flag));
+    Ok(match (m.value("--cnf"), m.value("--model")) {
+        (Some(_), Some(_)) => return Err(Error::Usage("--cnf and --model can't be used together".to_owned())),
+        (Some(_), None) | (None, Some(_)) if searching => return Err(Error::Usage("--cnf and --model don't search, so they can't be used with search options".to_owned())),
+        (Some(path), None) => FiniteMode::ExportCnf(path),
+        (None, Some(path)) => FiniteMode::CheckModel(path),
+        (None, None) => FiniteMode::Search(parse_search_options(m)?),
+    })
+}
+#[derive(Debug, Clone, Copy)]
+struct SearchOptions {
+    engine: Engine,
+    enumerate: Enumerate,
+    symmetry: bool, // only search for canonical solutions under the automorphism group (finite graphs only)
+}
+fn parse_search_options(m: &Matches) -> Result<SearchOptions, Error> {
+    let opts = SearchOptions {
+        engine: if m.flag("--sat") { Engine::Sat } else { Engine::Recursive },
+        enumerate: if m.flag("--unique") { Enumerate::Unique } else if m.flag("--all") { Enumerate::All } else { Enumerate::First },
+        symmetry: m.flag("--symmetry"),
+    };
+    if opts.symmetry && opts.enumerate == Enumerate::All {
+        return Err(Error::Usage("--symmetry skips non-canonical solutions, so it can't be used with --all (use --unique instead)".to_owned()));
     }
-    fn get_solution(&self) -> Vec<&str> {
-        let mut v: Vec<&str> = self.detectors.iter().map(|&p| self.verts[p].label.as_str()).collect();
-        v.sort();
-        v
+    Ok(opts)
+}
+fn finite_export_cnf(cnf: &cnf::Cnf, path: &str) -> Result<(), Error> {
+    let mut f = io::BufWriter::new(File::create(path).map_err(|e| Error::io("create cnf file", path, e))?);
+    cnf.write_dimacs(&mut f).and_then(|_| f.flush()).map_err(|e| Error::io("write cnf file", path, e))?;
+    info!("wrote cnf with {} vars and {} clauses to {}", cnf.var_count(), cnf.clauses().len(), path);
+    Ok(())
+}
+fn finite_read_model(g: &mut FiniteGraph, path: &str) -> Result<bool, Error> {
+    let f = BufReader::new(File::open(path).map_err(|e| Error::io("open model file", path, e))?);
+    match cnf::read_model(f) {
+        Ok(Some(model)) => { g.set_solution(&model); Ok(true) }
+        Ok(None) => Ok(false),
+        Err(source) => Err(Error::Model { path: path.to_owned(), source }),
     }
-
-    fn geometric<T: fmt::Debug, F: Fn(&T, &T) -> bool>(points: &[T], f: &F) -> Self {
-        let mut verts = Vec::with_capacity(points.len());
-        for (i, a) in points.iter().enumerate() {
-            let mut adj = vec![];
-            for (j, b) in points[..i].iter().enumerate() {
-                if f(a, b) { adj.push(j); }
+}
+fn finite_helper(mut g: FiniteGraph, param: &str, count: &str, mode: FiniteMode) -> Result<(), Error> {
+    let param: Parameter = param.parse()?;
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err(ValueError::OutOfRange("count cannot be zero".to_owned()).into()),
+        Ok(n) if n > g.verts.len() => return Err(ValueError::OutOfRange("count cannot be larger than graph size".to_owned()).into()),
+        Ok(n) => n,
+        Err(_) => return Err(ValueError::Malformed(format!("failed to parse '{}' as positive integer", count)).into()),
+    };
+    let header = vec![("parameter", format!("{:?}", param).into()), ("vertices", g.verts.len().into()), ("size", count.into())];
+
+    let success = match mode {
+        FiniteMode::Search(opts) if opts.enumerate != Enumerate::First => {
+            finite_enumerate(&mut g, param, count, opts.engine, opts.enumerate == Enumerate::Unique, opts.symmetry);
+            return Ok(());
+        }
+        FiniteMode::Search(opts) => {
+            let (found, stats) = finite::search(&mut g, param, count, opts.engine, opts.symmetry);
+            finite_print_stats(&g, count, &stats);
+            found
+        }
+        FiniteMode::ExportCnf(path) => {
+            let cnf = finite::to_cnf(&g, param, count);
+            finite_export_cnf(&cnf, path)?;
+            if output::is_json() {
+                println!("{}", Json::object(header.into_iter().chain(vec![("cnf", path.into()), ("vars", cnf.var_count().into()), ("clauses", cnf.clauses().len().into())])));
+            }
+            return Ok(());
+        }
+        FiniteMode::CheckModel(path) => {
+            if !finite_read_model(&mut g, path)? {
+                if output::is_json() {
+                    println!("{}", Json::object(header.into_iter().chain(vec![("found", false.into())])));
+                }
+                else {
+                    println!("sat solver reported no solution");
+                }
+                return Ok(());
             }
-            for (j, b) in points[i + 1..].iter().enumerate() {
-                if f(a, b) { adj.push(i + 1 + j); }
+            // the cnf asks for exactly count detectors, so any other number means the model is for some other cnf (or corrupt)
+            if g.detectors.len() != count {
+                let msg = format!("model has {} detectors, but the cnf asks for exactly {}", g.detectors.len(), count);
+                return Err(Error::Model { path: path.to_owned(), source: cnf::ModelReadError::InvalidFormat(msg) });
             }
-            let open_adj = adj.clone();
-            adj.push(i);
-            verts.push(Vertex { open_adj, closed_adj: adj, label: format!("{:?}", a) });
+            finite::verify(&mut g, param)
         }
-        Self { verts, detectors: Default::default() }
-    }
-    fn path(size: usize) -> Self {
-        let mut vert_pos = Vec::with_capacity(size);
-        for i in 0..size as isize { vert_pos.push(i); }
-        Self::geometric(&vert_pos, &|a, b| (a - b).abs() <= 1)
-    }
-    fn cycle(size: usize) -> Self {
-        let mut g = Self::path(size);
-        g.verts[0].open_adj.push(size - 1);
-        g.verts[0].closed_adj.push(size - 1);
-        g.verts[size - 1].open_adj.push(0);
-        g.verts[size - 1].closed_adj.push(0);
-        g
+    };
+    if output::is_json() {
+        let mut fields = header;
+        match mode {
+            FiniteMode::CheckModel(_) => fields.extend(vec![("valid", success.into()), ("detectors", g.get_solution().into())]),
+            _ if success => fields.extend(vec![("found", true.into()), ("detectors", g.get_solution().into())]),
+            _ => fields.push(("found", false.into())),
+        }
+        println!("{}", Json::object(fields));
     }
-    fn ladder(length: usize) -> Self {
-        let mut vert_pos = Vec::with_capacity(length * 2);
-        for i in 0..length as isize { vert_pos.push((0isize, i)); }
-        for i in 0..length as isize { vert_pos.push((1isize, i)); }
-        Self::geometric(&vert_pos, &|a, b| (a.0 - b.0).abs() + (a.1 - b.1).abs() <= 1)
+    else if success {
+        println!("found solution:\n{:?}", g.get_solution());
     }
-    fn complete(size: usize) -> Self {
-        let mut vert_pos = Vec::with_capacity(size);
-        for i in 0..size as isize { vert_pos.push(i); }
-        Self::geometric(&vert_pos, &|_, _| true)
+    else if let FiniteMode::CheckModel(_) = mode {
+        println!("model is not a valid solution:\n{:?}", g.get_solution());
     }
-}
-
-fn parse_thresh(v: &str) -> f64 {
-    match v.parse::<f64>() {
-        Ok(v) if v > 0.0 && v <= 1.0 => v,
-        Ok(v) => crash!(2, "thresh {} was outside valid range (0, 1]", v),
-        Err(_) => crash!(2, "failed to parse '{}' as float", v),
+    else {
+        println!("no solution found");
     }
+    Ok(())
 }
-fn parse_exact(v: &str, max: usize) -> usize {
-    match v.parse::<usize>() {
-        Ok(v) if v <= max => v,
-        Ok(v) => crash!(2, "count {} exceeded max {}", v, max),
-        Err(_) => crash!(2, "failed to parse '{}' as uint", v),
+// the side notes on how a search of count detectors went
+fn finite_print_stats(g: &FiniteGraph, count: usize, stats: &SearchStats) {
+    if let Some(aut) = &stats.automorphisms {
+        info!("automorphism group has order {} ({} generators)", aut.order, aut.generators.len());
     }
-}
-fn parse_thresh_frac(v: &str) -> Share {
-    // first try to parse as rational
-    match v.parse::<Share>() {
-        Ok(v) if v > Share::zero() && v <= Share::one() => v,
-        Ok(v) => crash!(2, "thresh {} was outside valid range (0, 1]", v),
-        Err(_) => {
-            // on failure, if it doesn't start with '0.' it was incorrectly formatted rational
-            if !v.starts_with("0.") {
-                crash!(2, "failed to parse '{}' as rational", v)
-            }
-            // otherwise try to parse as exact decimal (0, 1)
-            match v[2..].parse::<Share>() {
-                Ok(n) if n > Share::zero() => {
-                    n / BigRational::from_integer(10.into()).pow(v.len() as i32 - 2)
-                }
-                _ => crash!(2, "failed to parse '{}' as rational", v),
-            }
-        }
+    if let Some(clauses) = stats.breaking_clauses {
+        info!("symmetry breaking added {} clauses", clauses);
     }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Parameter {
-    DOM, ODOM,
-    EDOM, EODOM,
-    LD, REDLD, DETLD, ERRLD,
-    IC, REDIC, DETIC, RSPIC, ERRIC,
-    OLD, REDOLD, DETOLD, RSPOLD, ERROLD,
-}
-impl FromStr for Parameter {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.to_lowercase().as_str() {
-            "dom" => Parameter::DOM,
-            "odom" => Parameter::ODOM,
-            "edom" => Parameter::EDOM,
-            "eodom" => Parameter::EODOM,
-            "ld" => Parameter::LD,
-            "red:ld" | "redld" => Parameter::REDLD,
-            "det:ld" | "detld" => Parameter::DETLD,
-            "err:ld" | "errld" => Parameter::ERRLD,
-            "ic" => Parameter::IC,
-            "red:ic" | "redic" => Parameter::REDIC,
-            "det:ic" | "detic" => Parameter::DETIC,
-            "rsp:ic" | "rspic" => Parameter::RSPIC,
-            "err:ic" | "erric" => Parameter::ERRIC,
-            "old" => Parameter::OLD,
-            "red:old" | "redold" => Parameter::REDOLD,
-            "det:old" | "detold" => Parameter::DETOLD,
-            "rsp:old" | "rspold" => Parameter::RSPOLD,
-            "err:old" | "errold" => Parameter::ERROLD,
-
-            _ => return Err(()),
-        })
+    if let Some(pruning) = &stats.pruning {
+        let total = util::binomial(g.verts.len(), count);
+        let percent = BigRational::new(BigInt::from(pruning.sets.clone()) * 100, BigInt::from(total.clone()));
+        info!("symmetry breaking pruned {} branches ({} of {} candidate sets, {:.2}%)", pruning.branches, pruning.sets, total, percent.to_f64().unwrap());
     }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Graph {
-    K, TRI, SQ, HEX, TMB,
-}
-impl FromStr for Graph {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.to_lowercase().as_str() {
-            "k" | "king" | "kings" => Graph::K,
-            "tri" => Graph::TRI,
-            "sq" | "square" | "grid" => Graph::SQ,
-            "hex" => Graph::HEX,
-            "tmb" => Graph::TMB,
-
-            _ => return Err(()),
-        })
+    if let Some(sat) = &stats.sat {
+        info!("sat solver: {}", sat);
     }
 }
-
-fn tess_helper_calc<T: Tessellation>(tess: &mut T, param: Parameter, graph: Graph, goal: &str) -> Option<usize> {
-    macro_rules! calc_thresh {
-        ($set:ident, $adj:ident) => {
-            tess.try_satisfy::<codesets::$set<(isize, isize)>, adj::$adj>(Goal::MeetOrBeat(parse_thresh(goal)))
+// prints every solution of the given size, or with unique only one per orbit under the automorphism group of the graph, and then the totals
+fn finite_enumerate(g: &mut FiniteGraph, param: Parameter, count: usize, engine: Engine, unique: bool, symmetry: bool) {
+    let mut found: usize = 0;
+    let res = finite::enumerate(g, param, count, engine, unique, symmetry, |verts, set, class_size| {
+        found += 1;
+        let mut labels: Vec<&str> = set.iter().map(|&i| verts[i].label.as_str()).collect();
+        labels.sort();
+        if output::is_json() {
+            let mut fields = vec![("solution", found.into())];
+            if unique {
+                fields.push(("class_size", class_size.into()));
+            }
+            fields.push(("detectors", labels.into()));
+            println!("{}", Json::object(fields));
+        }
+        else if unique {
+            println!("solution {} ({} in class):\n{:?}", found, class_size, labels);
         }
-    }
-    macro_rules! calc_exactly {
-        ($set:ident, $adj:ident) => {
-            tess.try_satisfy::<codesets::$set<(isize, isize)>, adj::$adj>(Goal::Exactly(parse_exact(goal, tess.size())))
+        else {
+            println!("solution {}:\n{:?}", found, labels);
         }
-    }
-    macro_rules! family {
-        ($open:ident, $closed:ident) => {
-            match param {
-                Parameter::DOM => calc_thresh!(DOM, $closed),
-                Parameter::ODOM => calc_thresh!(DOM, $open),
-                Parameter::EDOM => calc_exactly!(EDOM, $closed),
-                Parameter::EODOM => calc_exactly!(EDOM, $open),
-                Parameter::LD => calc_thresh!(LD, $open),
-                Parameter::REDLD => calc_thresh!(REDLD, $open),
-                Parameter::DETLD => calc_thresh!(DETLD, $open),
-                Parameter::ERRLD => calc_thresh!(ERRLD, $open),
-                Parameter::IC => calc_thresh!(OLD, $closed),
-                Parameter::REDIC => calc_thresh!(RED, $closed),
-                Parameter::DETIC => calc_thresh!(DET, $closed),
-                Parameter::RSPIC => calc_thresh!(RSP, $closed),
-                Parameter::ERRIC => calc_thresh!(ERR, $closed),
-                Parameter::OLD => calc_thresh!(OLD, $open),
-                Parameter::REDOLD => calc_thresh!(RED, $open),
-                Parameter::DETOLD => calc_thresh!(DET, $open),
-                Parameter::RSPOLD => calc_thresh!(RSP, $open),
-                Parameter::ERROLD => calc_thresh!(ERR, $open),
-            }
+    });
+    finite_print_stats(g, count, &res.stats);
+
+    if output::is_json() {
+        let mut fields = vec![("solutions", res.total.into())];
+        if unique {
+            fields.push(("classes", res.classes.into()));
         }
+        println!("{}", Json::object(fields));
     }
-
-    match graph {
-        Graph::K => family!(OpenKing, ClosedKing),
-        Graph::TRI => family!(OpenTri, ClosedTri),
-        Graph::SQ => family!(OpenGrid, ClosedGrid),
-        Graph::HEX => family!(OpenHex, ClosedHex),
-        Graph::TMB => family!(OpenTMB, ClosedTMB),
+    else if unique {
+        println!("found {} solutions in {} classes up to automorphism", res.total, res.classes);
     }
-}
-fn tess_helper_print<T: Tessellation>(tess: &T, min: usize) {
-    let n = tess.size();
-    let d = util::gcd(min, n);
-    println!("found a {}/{} ({}) solution:\n{}", (min / d), (n / d), (min as f64 / n as f64), tess);
-}
-fn tess_helper<T: Tessellation>(mut tess: T, param: &str, graph: &str, goal: &str) {
-    let param: Parameter = param.parse().unwrap_or_else(|_| crash!(2, "unknown parameter: {}", param));
-    let graph: Graph = graph.parse().unwrap_or_else(|_| crash!(2, "unknown graph: {}", graph));
-    
-    match tess_helper_calc(&mut tess, param, graph, goal) {
-        Some(min) => tess_helper_print(&tess, min),
-        None => println!("no solution found"),
+    else {
+        println!("found {} solutions", res.total);
     }
 }
-fn entropy_helper(big_geo: Geometry, entropy_size: &str, param: &str, graph: &str, goal: &str, threadc: &str) {
-    let param: Parameter = param.parse().unwrap_or_else(|_| crash!(2, "unknown parameter: {}", param));
-    let graph: Graph = graph.parse().unwrap_or_else(|_| crash!(2, "unknown graph: {}", graph));
-
-    let entropy_size = match entropy_size.parse::<usize>() {
-        Ok(v) if v <= big_geo.size() => v,
-        Ok(v) => crash!(2, "entropy size cannot exceed size of geometry (geo size {}, entropy size {})", big_geo.size(), v),
-        Err(_) => crash!(2, "failed to parse '{}' as positive integer", entropy_size),
+#[test]
+fn test_finite_check_model() {
+    let path = std::env::temp_dir().join(format!("old-set-model-{}.txt", std::process::id()));
+    let check = |model: &str| {
+        std::fs::write(&path, model).unwrap();
+        finite_helper(FiniteGraph::path(5), "dom", "2", FiniteMode::CheckModel(path.to_str().unwrap()))
     };
-    let cpus = num_cpus::get();
-    let threadc = match threadc.parse::<usize>() {
-        Ok(x) if x == 0 => crash!(2, "cannot use 0 threads"),
-        Ok(x) if x > cpus => crash!(2, "this system has only {} cores, but {} were requested", cpus, x),
-        Ok(x) => x,
-        Err(_) => crash!(2, "failed to parse '{}' as positive integer", threadc),
+    assert!(check("s SATISFIABLE\nv -1 2 -3 4 -5 0\n").is_ok());
+    assert!(check("s UNSATISFIABLE\n").is_ok());
+    assert!(matches!(check("s SATISFIABLE\nv -1 2 -3 4 5 0\n"), Err(Error::Model { .. })));
+    assert!(matches!(check("s SATISFIABLE\nv -1 2 -3 -4 -5 0\n"), Err(Error::Model { .. })));
+    std::fs::remove_file(&path).unwrap();
+}
+// prints the minimum solution size and the refutation of anything smaller, optionally saving it as a cnf and a drup proof
+fn finite_min_helper(mut g: FiniteGraph, param: &str, proof: Option<(&str, &str)>) -> Result<Option<usize>, Error> {
+    let param: Parameter = param.parse()?;
+    let Minimum { min, mut cnf, solver } = finite::find_min(&mut g, param, proof.is_some(), |size| info!("found solution of size {}", size));
+    info!("sat solver: {}", solver.stats());
+
+    let json = output::is_json();
+    let mut fields = vec![("parameter", format!("{:?}", param).into()), ("vertices", g.verts.len().into()), ("min", min.into())];
+    let infeasible = match min {
+        Some(min) => {
+            if json {
+                fields.push(("density", Json::rational(&BigRational::new(min.into(), g.verts.len().into()))));
+                fields.push(("detectors", g.get_solution().into()));
+            }
+            else {
+                println!("minimum solution has size {} ({}/{}):\n{:?}", min, min, g.verts.len(), g.get_solution());
+            }
+            if min == 0 { // nothing to prove
+                if json { println!("{}", Json::object(fields)); }
+                return Ok(Some(0));
+            }
+            format!("no solution of size {} or less exists", min - 1)
+        }
+        None => "no solution exists".to_owned(),
     };
-
-    let geos = big_geo.sub_geometries(entropy_size);
-    let done_geos: BTreeSet<_> = Default::default();
-    let data = Arc::new(Mutex::new((geos, done_geos, false))); // shared sync state for search
-    let goal = Arc::new(goal.to_owned()); // we need to own goal to share, using Arc to avoid multiple copies
-
-    let mut threads: Vec<_> = Vec::with_capacity(threadc);
-    for _ in 0..threadc {
-        let data = data.clone();
-        let goal = goal.clone();
-        threads.push(thread::spawn(move || {
-            loop {
-                let geo = {
-                    let mut data = data.lock().unwrap();
-                    if data.2 {
-                        break // if the solution flag was set, abort
-                    }
-                    match data.0.next() {
-                        Some(geo) => {
-                            if !data.1.insert(geo.to_string()) {
-                                continue // if we've already seen a similar shape, skip it
-                            }
-                            geo
-                        },
-                        None => break, // if there are no more geometries we're done
-                    }
-                };
-
-                // generate the tessellation structure
-                let mut tess = match GeometryTessellation::try_from(geo) {
-                    Ok(t) => t,
-                    Err(_) => continue, // on tessellation failure, just move on to next geometry
-                };
-
-                // on successful search, print result and terminate
-                if let Some(min) = tess_helper_calc(&mut tess, param, graph, &goal) {
-                    let mut data = data.lock().unwrap();
-
-                    // if solution flag has not been set, print solution and set it
-                    if !data.2 {
-                        tess_helper_print(&tess, min);
-                        data.2 = true;
-                    }
-                    break;
-                }
-            }
-        }));
+    if json {
+        fields.push(("infeasible", infeasible.as_str().into()));
     }
-    for thread in threads {
-        thread.join().unwrap();
+    else {
+        println!("{}", infeasible);
     }
-    let mut data = data.lock().unwrap(); // we no longer have running threads, so take ownership of the final data
 
-    // if we get to this point and there was no solution then we've exhausted all geometries and found no solutions
-    if !data.2 {
-        assert!(data.0.next().is_none()); // we should have processed all subgeometries
-        println!("no solution found (tested {} geometries)", data.1.len());
+    if let Some((cnf_path, proof_path)) = proof {
+        cnf.comment(format!("unsat: {}", infeasible));
+        finite_export_cnf(&cnf, cnf_path)?;
+        let mut f = io::BufWriter::new(File::create(proof_path).map_err(|e| Error::io("create proof file", proof_path, e))?);
+        solver.write_proof(&mut f).and_then(|_| f.flush()).map_err(|e| Error::io("write proof file", proof_path, e))?;
+        info!("wrote drup proof of unsatisfiability to {}", proof_path);
+        fields.push(("proof", Json::object(vec![("cnf", cnf_path.into()), ("drup", proof_path.into())])));
     }
-}
-fn theo_helper(param: &str, graph: &str, thresh: &str, strategy: TheoStrategy, mut pipe: Option<&mut dyn io::Write>) -> bool {
-    let param: Parameter = param.parse().unwrap_or_else(|_| crash!(2, "unknown parameter: {}", param));
-    let graph: Graph = graph.parse().unwrap_or_else(|_| crash!(2, "unknown graph: {}", graph));
-
-    let thresh = parse_thresh_frac(thresh);
-    if let Some(ref mut f) = pipe {
-        writeln!(f, "lower bound for {:?} set on {:?} graph - {:?} thresh {}", param, graph, strategy, thresh).unwrap();
+    if json {
+        println!("{}", Json::object(fields));
     }
-
-    macro_rules! calc {
-        ($set:ident, $adj:ident, $shadj:ident) => {
-            calc_lower_bound::<codesets::$set<(isize, isize)>, adj::$adj, adj::$shadj>(strategy, thresh, pipe)
-        }
-    }
-    macro_rules! family {
-        ($open:ident, $closed:ident) => {
-            match param {
-                Parameter::DOM => calc!(DOM, $closed, $closed),
-                Parameter::ODOM => calc!(DOM, $open, $open),
-                Parameter::EDOM | Parameter::EODOM => crash!(2, "lower bound does not currently support {:?}", param), 
-                Parameter::LD => calc!(LD, $open, $closed),       // important: this one uses open adj for loc codes but closed adj for share
-                Parameter::REDLD => calc!(REDLD, $open, $closed), // important: this one uses open adj for loc codes but closed adj for share
-                Parameter::DETLD => calc!(DETLD, $open, $closed), // important: this one uses open adj for loc codes but closed adj for share
-                Parameter::ERRLD => calc!(ERRLD, $open, $closed), // important: this one uses open adj for loc codes but closed adj for share
-                Parameter::IC => calc!(OLD, $closed, $closed),
-                Parameter::REDIC => calc!(RED, $closed, $closed),
-                Parameter::DETIC => calc!(DET, $closed, $closed),
-                Parameter::RSPIC => calc!(RSP, $closed, $closed),
-                Parameter::ERRIC => calc!(ERR, $closed, $closed),
-                Parameter::OLD => calc!(OLD, $open, $open),
-                Parameter::REDOLD => calc!(RED, $open, $open),
-                Parameter::DETOLD => calc!(DET, $open, $open),
-                Parameter::RSPOLD => calc!(RSP, $open, $open),
-                Parameter::ERROLD => calc!(ERR, $open, $open),
-            }
-        }
+    Ok(min)
+}
+fn smallest_helper(param: &str) -> Result<usize, Error> {
+    let param: Parameter = param.parse()?;
+    let (g, edges) = finite::smallest(param)?;
+    if output::is_json() {
+        println!("{}", Json::object(vec![("parameter", format!("{:?}", param).into()), ("vertices", g.verts.len().into()), ("edges", edges.into())]));
     }
-
-    match graph {
-        Graph::K => family!(OpenKing, ClosedKing),
-        Graph::TRI => family!(OpenTri, ClosedTri),
-        Graph::SQ => family!(OpenGrid, ClosedGrid),
-        Graph::HEX => family!(OpenHex, ClosedHex),
-        Graph::TMB => family!(OpenTMB, ClosedTMB),
+    else {
+        println!("found {} vertex solution with edges:\n{:?}", g.verts.len(), edges);
     }
+    Ok(g.verts.len())
 }
-fn auto_theo_helper(set: &str, graph: &str, strategy: TheoStrategy) {
-    let two = BigInt::from(2);
-    
-    // we know the value is in (0, 1], so start a binary search
-    let mut low = BigRational::zero();
-    let mut high = BigRational::one();
-    let mut thresh = &high - &low;
-    loop {
-        thresh /= &two;
-        let mid = (&low + &high) / &two;
-        let rat = util::rationalize(&mid, &thresh);
-        println!("search space: [{}, {}]\nprediction: {}", low, high, rat);
-        if theo_helper(set, graph, &mid.to_string(), strategy, None) {
-            low = mid;
-        }
-        else {
-            high = mid;
+
+fn batch_helper(exe: &str, job_path: &str, out_path: &str, threadc: &str) -> Result<(), Error> {
+    let jobs = batch::read_jobs(job_path, BufReader::new(File::open(job_path).map_err(|e| Error::io("open job file", job_path, e))?))?;
+    // catch mistakes in the job file before running anything, since a batch can take a long time
+    for job in jobs.iter() {
+        let checked = match COMMANDS.iter().find(|cmd| cmd.name == job.args[0]) {
+            Some(cmd) => cmd.parse(exe, &job.args[1..]).map(|_| ()),
+            None => Err(Error::Usage(format!("unknown command: {}", job.args[0]))),
+        };
+        if let Err(e) = checked {
+            return Err(Error::Usage(format!("job on line {} of {} is invalid: {}", job.line, job_path, e)));
         }
     }
-}
-fn finite_helper(mut g: FiniteGraph, param: &str, count: &str) {
-    let param: Parameter = param.parse().unwrap_or_else(|_| crash!(2, "unknown parameter: {}", param));
-    let count = match count.parse::<usize>() {
-        Ok(n) => {
-            if n == 0 { crash!(2, "count cannot be zero"); }
-            if n > g.verts.len() { crash!(2, "count cannot be larger than graph size"); }
-            n
-        }
-        Err(_) => crash!(2, "failed to parse '{}' as positive integer", count),
-    };
+    let threadc = parse_positive(threadc)?;
+    let exe_path = std::env::current_exe().map_err(|e| Error::io("locate", exe, e))?;
 
-    macro_rules! calc {
-        ($t:ident, $m:ident) => {
-            g.solver::<codesets::$t<usize>>().find_solution(count, AdjType::$m)
-        }
+    let start = std::time::Instant::now();
+    let finished = Mutex::new(0);
+    let results = batch::run_jobs(&exe_path, &jobs, threadc, |job, res| {
+        let mut finished = finished.lock().unwrap();
+        *finished += 1;
+        info!("[{}/{}] {} ({}, {:.2}s)", finished, jobs.len(), job.args.join(" "), if res.success() { "ok" } else { "failed" }, res.seconds);
+    })?;
+    let seconds = start.elapsed().as_secs_f64();
+
+    let mut f = io::BufWriter::new(File::create(out_path).map_err(|e| Error::io("create results file", out_path, e))?);
+    for (job, res) in jobs.iter().zip(results.iter()) {
+        writeln!(f, "{}", res.to_json(job)).map_err(|e| Error::io("write results file", out_path, e))?;
     }
+    f.flush().map_err(|e| Error::io("write results file", out_path, e))?;
 
-    let success = match param {
-        Parameter::DOM => calc!(DOM, Closed),
-        Parameter::ODOM => calc!(DOM, Open),
-        Parameter::EDOM => calc!(EDOM, Closed),
-        Parameter::EODOM => calc!(EDOM, Open),
-        Parameter::LD => calc!(LD, Open),
-        Parameter::REDLD => calc!(REDLD, Open),
-        Parameter::DETLD => calc!(DETLD, Open),
-        Parameter::ERRLD => calc!(ERRLD, Open),
-        Parameter::IC => calc!(OLD, Closed),
-        Parameter::REDIC => calc!(RED, Closed),
-        Parameter::DETIC => calc!(DET, Closed),
-        Parameter::RSPIC => calc!(RSP, Closed),
-        Parameter::ERRIC => calc!(ERR, Closed),
-        Parameter::OLD => calc!(OLD, Open),
-        Parameter::REDOLD => calc!(RED, Open),
-        Parameter::DETOLD => calc!(DET, Open),
-        Parameter::RSPOLD => calc!(RSP, Open),
-        Parameter::ERROLD => calc!(ERR, Open),
-    };
-    if success {
-        println!("found solution:\n{:?}", g.get_solution());
+    let failed = results.iter().filter(|res| !res.success()).count();
+    if output::is_json() {
+        println!("{}", Json::object(vec![("jobs", jobs.len().into()), ("failed", failed.into()), ("seconds", seconds.into()), ("results", out_path.into())]));
     }
     else {
-        println!("no solution found");
+        println!("ran {} jobs ({} failed) in {:.2}s - results written to {}", jobs.len(), failed, seconds, out_path);
     }
+    Ok(())
 }
-fn smallest_helper(param: &str) -> usize {
-    let param: Parameter = param.parse().unwrap_or_else(|_| crash!(2, "unknown parameter: {}", param));
-    fn test(param: Parameter, mut graph: FiniteGraph, edges: Vec<&Vec<usize>>) -> bool {
-        let vertex_count = graph.verts.len();
-        macro_rules! calc {
-            ($t:ident, $m:ident) => {
-                graph.solver::<codesets::$t<usize>>().find_solution(vertex_count, AdjType::$m)
-            }
-        }
-        let success = match param {
-            Parameter::DOM => calc!(DOM, Closed),
-            Parameter::ODOM => calc!(DOM, Open),
-            Parameter::EDOM => unimplemented!(),
-            Parameter::EODOM => unimplemented!(),
-            Parameter::LD => calc!(LD, Open),
-            Parameter::REDLD => calc!(REDLD, Open),
-            Parameter::DETLD => calc!(DETLD, Open),
-            Parameter::ERRLD => calc!(ERRLD, Open),
-            Parameter::IC => calc!(OLD, Closed),
-            Parameter::REDIC => calc!(RED, Closed),
-            Parameter::DETIC => calc!(DET, Closed),
-            Parameter::RSPIC => calc!(RSP, Closed),
-            Parameter::ERRIC => calc!(ERR, Closed),
-            Parameter::OLD => calc!(OLD, Open),
-            Parameter::REDOLD => calc!(RED, Open),
-            Parameter::DETOLD => calc!(DET, Open),
-            Parameter::RSPOLD => calc!(RSP, Open),
-            Parameter::ERROLD => calc!(ERR, Open),
-        };
-        if success {
-            println!("found {} vertex solution with edges:\n{:?}", vertex_count, edges);
+
+fn survey_helper(exe: &str, m: &Matches, threadc: &str) -> Result<(), Error> {
+    fn parse_list<T: std::str::FromStr<Err = Error> + Clone>(list: Option<&str>, all: &[T]) -> Result<Vec<T>, Error> {
+        match list {
+            Some(list) => list.split(',').map(str::parse).collect(),
+            None => Ok(all.to_vec()),
         }
-        success
     }
-    // we have to handle 1 vertex graph separately because following logic does combinations(2), which for a singleton graph is nothing
-    let singleton_graph = FiniteGraph { verts: vec![Vertex { label: 0.to_string(), open_adj: vec![], closed_adj: vec![0] }], detectors: Default::default() };
-    if test(param, singleton_graph, vec![]) { return 1; }
-    for vertex_count in 2.. {
-        let complete_edges: Vec<Vec<usize>> = (0..vertex_count).combinations(2).collect();
-        for edge_count in 0..=complete_edges.len() {
-            for edges in complete_edges.iter().combinations(edge_count) {
-                let mut vert_adjs = Vec::with_capacity(vertex_count);
-                for _ in 0..vertex_count {
-                    vert_adjs.push(Vec::with_capacity(vertex_count));
-                }
-                for edge in edges.iter() {
-                    let (a, b) = (edge[0], edge[1]);
-                    debug_assert_ne!(a, b); // sanity check
-                    vert_adjs[a].push(b);
-                    vert_adjs[b].push(a);
-                }
-                let verts = vert_adjs.into_iter().enumerate().map(|(i, mut adj)| {
-                    let open_adj = adj.clone();
-                    adj.push(i);
-                    Vertex { label: i.to_string(), open_adj, closed_adj: adj }
-                }).collect();
-                let graph = FiniteGraph { verts, detectors: Default::default() };
-                if test(param, graph, edges) { return vertex_count; }
-            }
-        }
+    let params = parse_list(m.value("--param"), &Parameter::ALL)?;
+    let graphs = parse_list(m.value("--graph"), &Graph::ALL)?;
+    if graphs.iter().any(|g| matches!(g, Graph::PERIODIC(_))) {
+        return Err(Error::Usage("survey only covers the built-in graphs".to_owned())); // the child processes couldn't name it
     }
-    panic!();
-}
-#[test]
-fn test_smallest() {
-    debug_assert_eq!(smallest_helper("dom"), 1);
-    debug_assert_eq!(smallest_helper("odom"), 2);
+    let strategy: TheoStrategy = m.value("--strategy").unwrap_or("dis").parse()?;
+    let opts = survey::SurveyOptions {
+        time_limit: std::time::Duration::from_secs(parse_positive(m.value("--time-limit").unwrap_or("60"))? as u64),
+        max_area: parse_positive(m.value("--max-area").unwrap_or("24"))?,
+        strategy: m.value("--strategy").unwrap_or("dis").to_owned(),
+    };
+    let style = match m.value("--table") {
+        None | Some("markdown") => survey::TableStyle::Markdown,
+        Some("latex") => survey::TableStyle::Latex,
+        Some(_) => return Err(Error::Usage("--table expects markdown or latex".to_owned())),
+    };
+    let witness_dir = m.value("--witnesses").unwrap_or("survey-witnesses");
+    let threadc = parse_positive(threadc)?;
+    let exe_path = std::env::current_exe().map_err(|e| Error::io("locate", exe, e))?;
+
+    let combos: Vec<(Parameter, Graph)> = params.iter().flat_map(|&p| graphs.iter().map(move |g| (p, g.clone()))).collect();
+    info!("surveying {} combinations with {:?} lower bounds and rectangles up to {} cells ({}s per search)", combos.len(), strategy, opts.max_area, opts.time_limit.as_secs());
+    let finished = Mutex::new(0);
+    let results = batch::parallel(&combos, threadc, |(param, graph)| -> Result<survey::Bounds, Error> {
+        let lower = survey::lower_bound(&exe_path, *param, graph, &opts)?;
+        let upper = survey::upper_bound(&exe_path, *param, graph, &opts)?;
+        Ok(survey::Bounds { lower, upper })
+    }, |(param, graph), res| {
+        let mut finished = finished.lock().unwrap();
+        *finished += 1;
+        if let Ok(b) = res {
+            let lower = b.lower.as_ref().map(|l| l.to_string()).unwrap_or_else(|| "?".to_owned());
+            let upper = b.upper.as_ref().map(|u| u.density.to_string()).unwrap_or_else(|| "?".to_owned());
+            info!("[{}/{}] {:?} on {:?}: {} - {}", finished, combos.len(), param, graph, lower, upper);
+        }
+    });
+    let results = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+    // write out the witnesses first so the table never links to something that isn't there
+    let witness_path = |param: Parameter, graph: &Graph| format!("{}/{}-{}.txt", witness_dir, format!("{:?}", param).to_lowercase(), format!("{:?}", graph).to_lowercase());
+    if results.iter().any(|b| b.upper.is_some()) {
+        std::fs::create_dir_all(witness_dir).map_err(|e| Error::io("create witness directory", witness_dir, e))?;
+    }
+    for ((param, graph), bounds) in combos.iter().zip(results.iter()) {
+        if let Some(w) = &bounds.upper {
+            let path = witness_path(*param, graph);
+            std::fs::write(&path, w.describe(*param, graph)).map_err(|e| Error::io("write witness file", &path, e))?;
+        }
+    }
+
+    if output::is_json() {
+        for ((param, graph), bounds) in combos.iter().zip(results.iter()) {
+            println!("{}", Json::object(vec![
+                ("parameter", format!("{:?}", param).into()),
+                ("graph", format!("{:?}", graph).into()),
+                ("lower", bounds.lower.as_ref().map(Json::rational).into()),
+                ("upper", bounds.upper.as_ref().map(|w| Json::rational(&w.density)).into()),
+                ("witness", bounds.upper.as_ref().map(|_| witness_path(*param, graph)).into()),
+            ]));
+        }
+        return Ok(());
+    }
+    let cell = |param: Parameter, graph: &Graph| {
+        let i = combos.iter().position(|(p, g)| *p == param && g == graph).unwrap();
+        (results[i].lower.clone(), results[i].upper.as_ref().map(|w| (w.density.clone(), witness_path(param, graph))))
+    };
+    let table = survey::table(&params, &graphs, &cell, style);
+    match m.value("--out") {
+        Some(path) => std::fs::write(path, table).map_err(|e| Error::io("write table file", path, e))?,
+        None => print!("{}", table),
+    }
+    Ok(())
 }
 
-fn parse_positive(v: &str) -> usize {
+fn parse_positive(v: &str) -> Result<usize, ValueError> {
     match v.parse::<usize>() {
-        Ok(v) if v > 0 => v,
-        _ => crash!(2, "failed to parse '{}' as positive integer", v),
+        Ok(v) if v > 0 => Ok(v),
+        _ => Err(ValueError::Malformed(format!("failed to parse '{}' as positive integer", v))),
     }
 }
 
+const PARAM: Opt = Opt { name: "--param", values: &["set-type"], required: true, help: "the kind of set to find, like dom, ic, red:ld or det:old" };
+const GRAPH: Opt = Opt { name: "--graph", values: &["graph"], required: true, help: "the infinite graph: k (king), tri, sq, hex, tmb, an archimedean lattice: sns, kag, rth, trh, etri, tsq, ttrh, snh, file:<path> for a periodic graph file, or any of these ^r for its distance-r power (e.g. sq^2)" };
+const LATTICE: Opt = Opt { name: "--graph", values: &["lattice"], required: true, help: "the 3d lattice: sc (simple cubic), kc (king cube), fcc or bcc" };
+const CYLINDER: Opt = Opt { name: "--cylinder", values: &[], required: false, help: "wrap the rows around into a cylinder instead of a strip" };
+const THRESH: Opt = Opt { name: "--thresh", values: &["thresh"], required: true, help: "the density to meet or beat in (0, 1] (an exact detector count for edom and eodom)" };
+const BOUND: Opt = Opt { name: "--thresh", values: &["thresh"], required: true, help: "the lower bound to try to prove in (0, 1], as a decimal or a fraction like 2/5" };
+const COUNT: Opt = Opt { name: "--count", values: &["set-size"], required: true, help: "the number of detectors to look for" };
+const SAT: Opt = Opt { name: "--sat", values: &[], required: false, help: "search with the sat solver instead of plain recursion" };
+const ALL: Opt = Opt { name: "--all", values: &[], required: false, help: "report every solution instead of the first one" };
+const UNIQUE: Opt = Opt { name: "--unique", values: &[], required: false, help: "report one solution per symmetry class" };
+const SYMMETRY: Opt = Opt { name: "--symmetry", values: &[], required: false, help: "only search for solutions that are canonical under the graph's automorphisms" };
+const CNF: Opt = Opt { name: "--cnf", values: &["out-file"], required: false, help: "write the problem as a dimacs cnf instead of solving it" };
+const MODEL: Opt = Opt { name: "--model", values: &["sat-output-file"], required: false, help: "check and print a model found by an external sat solver" };
+const CHECKPOINT: Opt = Opt { name: "--checkpoint", values: &["file"], required: false, help: "periodically save progress to this file" };
+const RESUME: Opt = Opt { name: "--resume", values: &["file"], required: false, help: "pick up from a saved checkpoint (and keep saving there unless --checkpoint is given)" };
+const THREADS: Opt = Opt { name: "--threads", values: &["threads"], required: false, help: "the number of threads to search with (defaults to every core)" };
+const THEO_THREADS: Opt = Opt { name: "--threads", values: &["threads"], required: false, help: "the number of threads to search with (defaults to every core, or 1 with --checkpoint or --resume)" };
+const TESS_THREADS: Opt = Opt { name: "--threads", values: &["threads"], required: false, help: "the number of threads for the recursive search of a single solution (defaults to every core)" };
+const STRATEGY: Opt = Opt { name: "--strategy", values: &["strategy"], required: false, help: "how shares are spread: trivial (default), avg, dis, dis-weight-excess or dis-weight-share" };
+const JOBS: Opt = Opt { name: "--threads", values: &["threads"], required: false, help: "the number of jobs to run at once (defaults to every core)" };
+const OUT: Opt = Opt { name: "--out", values: &["results-file"], required: true, help: "where to write the results, one json line per job" };
+const PARAMS: Opt = Opt { name: "--param", values: &["set-types"], required: false, help: "comma-separated set types to survey (defaults to all of them)" };
+const GRAPHS: Opt = Opt { name: "--graph", values: &["graphs"], required: false, help: "comma-separated graphs to survey (defaults to all of them)" };
+const TIME_LIMIT: Opt = Opt { name: "--time-limit", values: &["seconds"], required: false, help: "how long each lower and upper bound search can take (defaults to 60)" };
+const MAX_AREA: Opt = Opt { name: "--max-area", values: &["cells"], required: false, help: "the largest rectangle to look for upper bounds in (defaults to 24)" };
+const SURVEY_STRATEGY: Opt = Opt { name: "--strategy", values: &["strategy"], required: false, help: "the auto-theo strategy for lower bounds (defaults to dis)" };
+const TABLE: Opt = Opt { name: "--table", values: &["style"], required: false, help: "markdown (default) or latex" };
+const TABLE_OUT: Opt = Opt { name: "--out", values: &["table-file"], required: false, help: "where to write the table (defaults to stdout)" };
+const WITNESSES: Opt = Opt { name: "--witnesses", values: &["dir"], required: false, help: "where to write the upper bound tilings the table links to (defaults to survey-witnesses)" };
+const MAX_ITERATIONS: Opt = Opt { name: "--max-iterations", values: &["count"], required: false, help: "stop after this many searches" };
+const MIN_WIDTH: Opt = Opt { name: "--min-width", values: &["width"], required: false, help: "stop once the search space is at most this wide, like 1/1000" };
+const AUTO_TIME_LIMIT: Opt = Opt { name: "--time-limit", values: &["seconds"], required: false, help: "stop starting new searches after this long" };
+const MAX_DENOM: Opt = Opt { name: "--max-denom", values: &["denom"], required: false, help: "stop by snapping to the best fraction with at most this denominator (checked exactly)" };
+const AUTO_SEARCH: Opt = Opt { name: "--search", values: &["search"], required: false, help: "bisect (default) or stern-brocot, which tries small denominators first" };
+const CERT: Opt = Opt { name: "--cert", values: &["out-file"], required: false, help: "if the bound holds, write a certificate of it that verify-cert can check (trivial, avg and dis only)" };
+const PROOF: Opt = Opt { name: "--proof", values: &["cnf-out-file", "drup-out-file"], required: false, help: "save the final refutation as a cnf and a drup proof" };
+
+const FINITE_OPTS: &[Opt] = &[PARAM, COUNT, SAT, ALL, UNIQUE, SYMMETRY, CNF, MODEL];
+const TESS_OPTS: &[Opt] = &[PARAM, GRAPH, THRESH, SAT, ALL, UNIQUE, TESS_THREADS];
+const ENTROPY_OPTS: &[Opt] = &[PARAM, GRAPH, THRESH, THREADS, CHECKPOINT, RESUME];
+
+const COMMANDS: &[Command] = &[
+    Command { name: "finite", about: "search for a set of the given size on a finite graph", positional: &[("graph-file", "a graph given as whitespace-separated edges a:b")], opts: FINITE_OPTS },
+    Command { name: "finite-path", about: "search for a set of the given size on a path", positional: &[("size", "the number of vertices")], opts: FINITE_OPTS },
+    Command { name: "finite-cycle", about: "search for a set of the given size on a cycle", positional: &[("size", "the number of vertices")], opts: FINITE_OPTS },
+    Command { name: "finite-ladder", about: "search for a set of the given size on a ladder", positional: &[("length", "the number of rungs")], opts: FINITE_OPTS },
+    Command { name: "finite-complete", about: "search for a set of the given size on a complete graph", positional: &[("size", "the number of vertices")], opts: FINITE_OPTS },
+    Command { name: "finite-min", about: "find the minimum set size on a finite graph with the sat solver", positional: &[("graph-file", "a graph given as whitespace-separated edges a:b")], opts: &[PARAM, PROOF] },
+    Command { name: "smallest", about: "find the smallest graph that has a set of this type", positional: &[], opts: &[PARAM] },
+    Command { name: "theo", about: "try to prove a lower bound on the density of a set on an infinite graph", positional: &[], opts: &[PARAM, GRAPH, BOUND, STRATEGY, THEO_THREADS, CHECKPOINT, RESUME, CERT] },
+    Command { name: "cube-theo", about: "try to prove a lower bound on the density of a set on a 3d lattice", positional: &[], opts: &[PARAM, LATTICE, BOUND, STRATEGY, THREADS] },
+    Command { name: "verify-cert", about: "check a certificate written by theo --cert without rerunning the search", positional: &[("cert-file", "the certificate to check")], opts: &[] },
+    Command { name: "auto-theo", about: "binary search for the best lower bound theo can prove (runs until stopped unless given limits)", positional: &[], opts: &[PARAM, GRAPH, STRATEGY, THREADS, AUTO_SEARCH, MAX_ITERATIONS, MIN_WIDTH, AUTO_TIME_LIMIT, MAX_DENOM] },
+    Command { name: "rect", about: "search for a periodic solution tiled by a rectangle", positional: &[("rows", "rectangle height (at least 2)"), ("cols", "rectangle width (at least 2)")], opts: TESS_OPTS },
+    Command { name: "geo", about: "search for a periodic solution tiled by a custom shape", positional: &[("geometry-file", "the tile shape, with a non-space character for each vertex")], opts: TESS_OPTS },
+    Command { name: "box", about: "search for a periodic solution on a 3d lattice tiled by a box (with the sat solver)", positional: &[("rows", "box height"), ("cols", "box width"), ("layers", "box depth")], opts: &[PARAM, LATTICE, THRESH] },
+    Command { name: "strip", about: "find the exact optimal density on an infinite strip (or cylinder) of a graph", positional: &[("width", "the number of rows")], opts: &[PARAM, GRAPH, CYLINDER] },
+    Command { name: "entropy-rect", about: "search every sub-shape of a rectangle with this many vertices", positional: &[("rows", "rectangle height"), ("cols", "rectangle width"), ("entropy-size", "the size of the sub-shapes")], opts: ENTROPY_OPTS },
+    Command { name: "entropy-geo", about: "search every sub-shape of a custom shape with this many vertices", positional: &[("geometry-file", "the shape, with a non-space character for each vertex"), ("entropy-size", "the size of the sub-shapes")], opts: ENTROPY_OPTS },
+    Command { name: "survey", about: "find lower and upper bounds for every set type on every graph and make a table of them", positional: &[], opts: &[PARAMS, GRAPHS, TIME_LIMIT, MAX_AREA, SURVEY_STRATEGY, TABLE, TABLE_OUT, WITNESSES, JOBS] },
+    Command { name: "batch", about: "run every command in a job file (one per line) in parallel and collect the results", positional: &[("job-file", "one command per line, like: theo --param ld --graph hex --thresh 1/3")], opts: &[OUT, JOBS] },
+];
+fn usage(exe: &str) -> String {
+    let mut s = format!("usage: {} [command] ... (--format json)\n\ncommands:\n", exe);
+    for cmd in COMMANDS {
+        s += &format!("  {:<18}{}\n", cmd.name, cmd.about);
+    }
+    s += &format!("\nthe output format (text by default) applies to every command\nsee {} [command] --help for the options of each command", exe);
+    s
+}
+#[test]
+fn test_commands() {
+    // every command needs its own name and the --help usage lines should be parseable by eye
+    for (i, cmd) in COMMANDS.iter().enumerate() {
+        assert!(COMMANDS[..i].iter().all(|other| other.name != cmd.name));
+        assert!(cmd.help("old-set").starts_with(cmd.about));
+        assert!(usage("old-set").contains(cmd.name));
+    }
+    let args = |s: &str| s.split_whitespace().map(str::to_owned).collect::<Vec<_>>();
+    assert!(matches!(run(args("old-set theo --param ld --graph hex")), Err(Error::Usage(_))));
+    assert!(matches!(run(args("old-set rect 4 4 --param dom --graph sq --thresh 1 --symmetry")), Err(Error::Usage(_))));
+    assert!(matches!(run(args("old-set theo --param ld --graph hex --thresh 1/3 --strategy fancy")), Err(Error::UnknownStrategy(_))));
+    assert!(matches!(run(args("old-set theo --param ld --graph hex --thresh 1/3 --threads 4 --checkpoint ld.txt")), Err(Error::Usage(_))));
+    assert!(matches!(run(args("old-set theo --param ld --graph hex --thresh 1/3 --strategy dis-weight-share --cert ld.cert")), Err(Error::Usage(_))));
+    assert!(matches!(run(args("old-set finite-path 5 --param dom --count 2 --cnf a.cnf --model a.out")), Err(Error::Usage(_))));
+    assert!(matches!(run(args("old-set bogus")), Err(Error::Usage(_))));
+}
+
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    if let Err(e) = run(std::env::args().collect()) {
+        // bad command lines exit with 1, everything that went wrong after that exits with 2
+        let code = if let Error::Usage(_) = e { 1 } else { 2 };
+        crash!(code, "{}", e);
+    }
+}
+fn run(mut args: Vec<String>) -> Result<(), Error> {
+    // the output format applies to every command, so take it out before looking at the rest
+    if let Some(i) = args.iter().position(|arg| arg == "--format") {
+        match args.get(i + 1).map(String::as_str) {
+            Some("json") => output::set_json(true),
+            Some("text") => output::set_json(false),
+            _ => return Err(Error::Usage("--format expects json or text".to_owned())),
+        }
+        args.drain(i..i + 2);
+    }
+    let exe = args[0].as_str();
 
-    let parse_dim = |val: &str| {
-        match val.parse::<usize>() {
-            Ok(v) if v > 0 => v,
-            Ok(_) => crash!(2, "dimension cannot be zero"),
-            Err(_) => crash!(2, "failed to parse '{}' as positive int", val),
+    let name = match args.get(1).map(String::as_str) {
+        None => return Err(Error::Usage(usage(exe))),
+        Some("help") | Some("--help") => {
+            match args.get(2).and_then(|name| COMMANDS.iter().find(|cmd| cmd.name == name)) {
+                Some(cmd) => println!("{}", cmd.help(exe)),
+                None => println!("{}", usage(exe)),
+            }
+            return Ok(());
         }
+        Some(name) => name,
     };
-    let get_geometry = |path: &str| {
-        match Geometry::with_shape(path) {
-            Ok(g) => g,
-            Err(e) => match e {
-                GeometryWithShapeError::FileOpenFailure => crash!(2, "failed to open tessellation file {}", args[2]),
-                GeometryWithShapeError::InvalidFormat(msg) => crash!(2, "file {} was invalid format: {}", args[2], msg),
-            }
+    let cmd = match COMMANDS.iter().find(|cmd| cmd.name == name) {
+        Some(cmd) => cmd,
+        None => return Err(Error::Usage(format!("unknown command: {}\n{}", name, usage(exe)))),
+    };
+    let m = match cmd.parse(exe, &args[2..])? {
+        Some(m) => m,
+        None => {
+            println!("{}", cmd.help(exe));
+            return Ok(());
         }
     };
 
-    match args.get(1).map(String::as_str) {
-        Some("finite") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} finite [graph-file] [set-type] [set-size]", args[0]);
-            }
-            let graph_path = &args[2];
-            let g = match FiniteGraph::with_shape(graph_path) {
-                Ok(g) => g,
-                Err(e) => match e {
-                    GraphLoadError::FileOpenFailure => crash!(2, "failed to open graph file {}", graph_path),
-                    GraphLoadError::InvalidFormat(msg) => crash!(2, "file {} was invalid format: {}", graph_path, msg),
-                }
-            };
-            finite_helper(g, &args[3], &args[4]);
-        }
-        Some("finite-path") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} finite-path [size] [set-type] [set-size]", args[0]);
-            }
-            let size = parse_positive(&args[2]);
-            finite_helper(FiniteGraph::path(size), &args[3], &args[4]);
-        }
-        Some("finite-cycle") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} finite-cycle [size] [set-type] [set-size]", args[0]);
-            }
-            let size = parse_positive(&args[2]);
-            finite_helper(FiniteGraph::cycle(size), &args[3], &args[4]);
-        }
-        Some("finite-ladder") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} finite-ladder [length] [set-type] [set-size]", args[0]);
-            }
-            let length = parse_positive(&args[2]);
-            finite_helper(FiniteGraph::ladder(length), &args[3], &args[4]);
-        }
-        Some("finite-complete") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} finite-complete [size] [set-type] [set-size]", args[0]);
-            }
-            let size = parse_positive(&args[2]);
-            finite_helper(FiniteGraph::complete(size), &args[3], &args[4]);
-        }
-        Some("smallest") => {
-            if args.len() != 3 {
-                crash!(1, "usage: {} smallest [set-type]", args[0]);
-            }
-            smallest_helper(&args[2]);
-        }
-        Some("auto-theo") => {
-            if args.len() != 4 {
-                crash!(1, "usage: {} auto-theo [set-type] [graph]", args[0]);
-            }
-            auto_theo_helper(&args[2], &args[3], TheoStrategy::Trivial);
-        }
-        Some("auto-theo-avg") => {
-            if args.len() != 4 {
-                crash!(1, "usage: {} auto-theo-avg [set-type] [graph]", args[0]);
-            }
-            auto_theo_helper(&args[2], &args[3], TheoStrategy::Avg);
-        }
-        Some("auto-theo-dis") => {
-            if args.len() != 4 {
-                crash!(1, "usage: {} auto-theo-dis [set-type] [graph]", args[0]);
-            }
-            auto_theo_helper(&args[2], &args[3], TheoStrategy::Dis);
-        }
-        Some("auto-theo-dis-weight-excess") => {
-            if args.len() != 4 {
-                crash!(1, "usage: {} auto-theo-dis-weight-excess [set-type] [graph]", args[0]);
-            }
-            auto_theo_helper(&args[2], &args[3], TheoStrategy::DisWeightExcess);
-        }
-        Some("auto-theo-dis-weight-share") => {
-            if args.len() != 4 {
-                crash!(1, "usage: {} auto-theo-dis-weight-share [set-type] [graph]", args[0]);
-            }
-            auto_theo_helper(&args[2], &args[3], TheoStrategy::DisWeightShare);
-        }
-        Some("theo") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} theo [set-type] [graph] [thresh]", args[0]);
-            }
-            theo_helper(&args[2], &args[3], &args[4], TheoStrategy::Trivial, Some(&mut io::stdout()));
-        }
-        Some("theo-avg") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} theo-avg [set-type] [graph] [thresh]", args[0]);
-            }
-            theo_helper(&args[2], &args[3], &args[4], TheoStrategy::Avg, Some(&mut io::stdout()));
+    let parse_dim = |val: &str| {
+        match val.parse::<usize>() {
+            Ok(v) if v > 0 => Ok(v),
+            Ok(_) => Err(ValueError::OutOfRange("dimension cannot be zero".to_owned())),
+            Err(_) => Err(ValueError::Malformed(format!("failed to parse '{}' as positive int", val))),
         }
-        Some("theo-dis") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} theo-dis [set-type] [graph] [thresh]", args[0]);
+    };
+    let threads = num_cpus::get().to_string();
+
+    match cmd.name {
+        "finite" | "finite-path" | "finite-cycle" | "finite-ladder" | "finite-complete" => {
+            let g = match cmd.name {
+                "finite" => FiniteGraph::with_shape(m.arg(0))?,
+                "finite-path" => FiniteGraph::path(parse_positive(m.arg(0))?),
+                "finite-cycle" => FiniteGraph::cycle(parse_positive(m.arg(0))?),
+                "finite-ladder" => FiniteGraph::ladder(parse_positive(m.arg(0))?),
+                _ => FiniteGraph::complete(parse_positive(m.arg(0))?),
+            };
+            finite_helper(g, m.get("--param"), m.get("--count"), parse_finite_mode(&m)?)?;
+        }
+        "finite-min" => {
+            let g = FiniteGraph::with_shape(m.arg(0))?;
+            let proof = m.values("--proof").map(|v| (v[0], v[1]));
+            finite_min_helper(g, m.get("--param"), proof)?;
+        }
+        "smallest" => {
+            smallest_helper(m.get("--param"))?;
+        }
+        "theo" => {
+            let strategy = m.value("--strategy").unwrap_or("trivial").parse()?;
+            let checkpoint = parse_checkpoint_options(&m);
+            // the parallel search has no single place to save, so checkpointing is serial only
+            let checkpointing = m.flag("--checkpoint") || m.flag("--resume");
+            let threads = match m.value("--threads").map(parse_positive).transpose()? {
+                Some(t) if t > 1 && checkpointing => return Err(Error::Usage("--checkpoint and --resume only work with --threads 1".to_owned())),
+                Some(t) => t,
+                None if checkpointing => 1,
+                None => num_cpus::get(),
+            };
+            let cert = m.value("--cert");
+            // a resumed search doesn't see the configurations from before the checkpoint
+            if cert.is_some() && m.flag("--resume") {
+                return Err(Error::Usage("--cert can't be used with --resume".to_owned()));
+            }
+            theo_helper(m.get("--param"), m.get("--graph"), m.get("--thresh"), strategy, Some(&mut io::stdout()), Some(&checkpoint), cert, threads)?;
+        }
+        "cube-theo" => {
+            let strategy = m.value("--strategy").unwrap_or("trivial").parse()?;
+            let threads = parse_positive(m.value("--threads").unwrap_or(&threads))?;
+            cube_theo_helper(m.get("--param"), m.get("--graph"), m.get("--thresh"), strategy, threads)?;
+        }
+        "verify-cert" => {
+            verify_cert_helper(m.arg(0))?;
+        }
+        "auto-theo" => {
+            let strategy = m.value("--strategy").unwrap_or("trivial").parse()?;
+            let limits = AutoTheoLimits {
+                max_iterations: m.value("--max-iterations").map(parse_positive).transpose()?,
+                min_width: m.value("--min-width").map(params::parse_thresh_frac).transpose()?,
+                time_limit: m.value("--time-limit").map(|t| parse_positive(t).map(|t| std::time::Duration::from_secs(t as u64))).transpose()?,
+                max_denom: m.value("--max-denom").map(|d| parse_positive(d).map(BigInt::from)).transpose()?,
+            };
+            let search = match m.value("--search") {
+                None | Some("bisect") => AutoTheoSearch::Bisect,
+                Some("stern-brocot") => AutoTheoSearch::SternBrocot,
+                Some(_) => return Err(Error::Usage("--search expects bisect or stern-brocot".to_owned())),
+            };
+            let threads = parse_positive(m.value("--threads").unwrap_or(&threads))?;
+            auto_theo_helper(m.get("--param"), m.get("--graph"), strategy, search, &limits, threads)?;
+        }
+        "rect" | "geo" => {
+            let geo = if cmd.name == "rect" {
+                let rows: usize = parse_dim(m.arg(0))?;
+                let cols: usize = parse_dim(m.arg(1))?;
+                if rows < 2 || cols < 2 {
+                    return Err(ValueError::OutOfRange("1xn and nx1 are not supported to avoid branch conditions".to_owned()).into());
+                }
+                Geometry::rectangle(rows, cols)
             }
-            theo_helper(&args[2], &args[3], &args[4], TheoStrategy::Dis, Some(&mut io::stdout()));
-        }
-        Some("theo-dis-weight-excess") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} theo-dis-weight-excess [set-type] [graph] [thresh]", args[0]);
+            else {
+                Geometry::with_shape(m.arg(0))?
+            };
+            let tess = GeometryTessellation::try_from(geo)?;
+            if cmd.name == "geo" {
+                info!("loaded geometry: (size {})\n{}\nunique tilings: {}", tess.geometry().size(), tess.geometry(), tess.tilings().count());
+                for (i, basis) in tess.tilings().enumerate() {
+                    info!("tiling {}: {:?} {:?}", i + 1, basis[0], basis[1]);
+                }
+                info!("");
             }
-            theo_helper(&args[2], &args[3], &args[4], TheoStrategy::DisWeightExcess, Some(&mut io::stdout()));
+            let opts = parse_search_options(&m)?;
+            let threads = parse_positive(m.value("--threads").unwrap_or(&threads))?;
+            tess_helper(tess, m.get("--param"), m.get("--graph"), m.get("--thresh"), opts.engine, opts.enumerate, threads)?;
         }
-        Some("theo-dis-weight-share") => {
-            if args.len() != 5 {
-                crash!(1, "usage: {} theo-dis-weight-share [set-type] [graph] [thresh]", args[0]);
-            }
-            theo_helper(&args[2], &args[3], &args[4], TheoStrategy::DisWeightShare, Some(&mut io::stdout()));
+        "box" => {
+            let dims = (parse_dim(m.arg(0))? as isize, parse_dim(m.arg(1))? as isize, parse_dim(m.arg(2))? as isize);
+            box_helper(dims, m.get("--param"), m.get("--graph"), m.get("--thresh"))?;
         }
-        Some("rect") => {
-            if args.len() != 7 {
-                crash!(1, "usage: {} rect [rows] [cols] [set-type] [graph] [thresh]", args[0]);
-            }
-            let rows: usize = parse_dim(&args[2]);
-            let cols: usize = parse_dim(&args[3]);
-            if rows < 2 || cols < 2 {
-                crash!(2, "1xn and nx1 are not supported to avoid branch conditions");
-            }
-            let tess = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();// RectTessellation::new(rows, cols);
-            tess_helper(tess, &args[4], &args[5], &args[6]);
+        "strip" => {
+            strip_helper(parse_dim(m.arg(0))?, m.get("--param"), m.get("--graph"), m.flag("--cylinder"))?;
         }
-        Some("geo") => {
-            if args.len() != 6 {
-                crash!(1, "usage: {} geo [geometry-file] [set-type] [graph] [thresh]", args[0]);
+        "entropy-rect" | "entropy-geo" => {
+            let (big_geo, entropy_size) = if cmd.name == "entropy-rect" {
+                (Geometry::rectangle(parse_dim(m.arg(0))?, parse_dim(m.arg(1))?), m.arg(2))
             }
-            let geo = get_geometry(&args[2]);
-            let tess = match GeometryTessellation::try_from(geo) {
-                Ok(t) => t,
-                Err(e) => {
-                    match e {
-                        TessellationFailure::NoValidTessellations => crash!(2, "file {} had no valid tessellations", args[2]),
-                    }
-                }
+            else {
+                (Geometry::with_shape(m.arg(0))?, m.arg(1))
             };
-            println!("loaded geometry: (size {})\n{}\nunique tilings: {}", tess.size(), tess.geo, tess.tessellation_maps.len());
-            for (i, (_, a, b)) in tess.tessellation_maps.iter().enumerate() {
-                println!("tiling {}: {:?} {:?}", i + 1, a, b);
-            }
-            println!();
-            tess_helper(tess, &args[3], &args[4], &args[5])
+            let checkpoint = parse_checkpoint_options(&m);
+            entropy_helper(big_geo, entropy_size, m.get("--param"), m.get("--graph"), m.get("--thresh"), m.value("--threads").unwrap_or(&threads), &checkpoint)?;
         }
-        Some("entropy-rect") => {
-            if args.len() != 9 {
-                crash!(1, "usage: {} entropy-rect [rows] [cols] [entropy-size] [set-type] [graph] [thresh] [threads]", args[0]);
-            }
-            let rows: usize = parse_dim(&args[2]);
-            let cols: usize = parse_dim(&args[3]);
-            let big_geo = Geometry::rectangle(rows, cols);
-            entropy_helper(big_geo, &args[4], &args[5], &args[6], &args[7], &args[8]);
+        "survey" => {
+            survey_helper(exe, &m, m.value("--threads").unwrap_or(&threads))?;
         }
-        Some("entropy-geo") => {
-            if args.len() != 8 {
-                crash!(1, "usage: {} entropy-geo [geometry-file] [entropy-size] [set-type] [graph] [thresh] [threads]", args[0]);
-            }
-            let big_geo = get_geometry(&args[2]);
-            entropy_helper(big_geo, &args[3], &args[4], &args[5], &args[6], &args[7]);
+        "batch" => {
+            batch_helper(exe, m.arg(0), m.get("--out"), m.value("--threads").unwrap_or(&threads))?;
         }
-        _ => crash!(1, "usage: {} [finite|rect|geo|entropy-rect|entropy-geo|theo|theo-avg|theo-dis|auto-theo|auto-theo-avg|auto-theo-dis]", args[0]),
+        _ => unreachable!(), // every command in the table is handled above
     };
+    Ok(())
 }
 
-#[test]
-fn test_theo_hex_works() {
-    assert!(theo_helper("ld", "hex", "1/3", TheoStrategy::Dis, None));
-    assert!(theo_helper("det:ld", "hex", "3/5", TheoStrategy::Dis, None));
-    assert!(theo_helper("red:ic", "hex", "4/7", TheoStrategy::Dis, None));
-    assert!(theo_helper("det:ic", "hex", "12/17", TheoStrategy::Dis, None));
-    assert!(theo_helper("err:ic", "hex", "5/6", TheoStrategy::Dis, None));
-    assert!(theo_helper("old", "hex", "1/2", TheoStrategy::Dis, None));
-}
-
-#[test]
-fn test_theo_tmb_works() {
-    assert!(theo_helper("red:ic", "tmb", "4/9", TheoStrategy::Dis, None));
-    assert!(theo_helper("det:ic", "tmb", "3/5", TheoStrategy::Dis, None));
-    assert!(theo_helper("err:ic", "tmb", "12/19", TheoStrategy::Dis, None));
-    assert!(theo_helper("det:ld", "tmb", "3/5", TheoStrategy::Dis, None));
-}
-#[test]
-fn test_theo_tmb_not_works() {
-    assert!(!theo_helper("red:ic", "tmb", "0.44444444444444444444444444444444444444444444444444444444444444444444444444444444444444444444445", TheoStrategy::Dis, None));
-}
\ No newline at end of file
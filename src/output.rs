@@ -0,0 +1,247 @@
+// machine-readable output: with --format json, every result is printed as a one-line json object instead of text,
+// and everything else (progress, solver stats, etc.) goes to stderr so stdout can be parsed line by line.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use num::BigRational;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+pub fn set_json(json: bool) {
+    JSON.store(json, Ordering::Relaxed);
+}
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+    Raw(String), // already-serialized json, like a result line from another old-set process
+}
+impl Json {
+    pub fn object<I: IntoIterator<Item = (&'static str, Json)>>(fields: I) -> Self {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+    }
+    // rationals are written as exact strings like "3/7" (integers have no denominator), since they can get arbitrarily big
+    pub fn rational(val: &BigRational) -> Self {
+        Json::String(val.to_string())
+    }
+}
+
+// a small json reader, for the results other old-set processes print (numbers are kept as their source text)
+impl Json {
+    pub fn parse(s: &str) -> Option<Json> {
+        let mut chars = s.trim().chars().peekable();
+        let v = parse_value(&mut chars)?;
+        if chars.next().is_some() { None } else { Some(v) }
+    }
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(v) | Json::Number(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+fn skip_space(chars: &mut Chars) {
+    while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) { chars.next(); }
+}
+fn parse_word(chars: &mut Chars, word: &str, v: Json) -> Option<Json> {
+    for w in word.chars() {
+        if chars.next()? != w { return None; }
+    }
+    Some(v)
+}
+fn parse_string(chars: &mut Chars) -> Option<String> {
+    if chars.next()? != '"' { return None; }
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => s.push(match chars.next()? {
+                'n' => '\n', 'r' => '\r', 't' => '\t', 'b' => '\u{8}', 'f' => '\u{c}',
+                'u' => std::char::from_u32(u32::from_str_radix(&(0..4).map(|_| chars.next()).collect::<Option<String>>()?, 16).ok()?)?,
+                c => c,
+            }),
+            c => s.push(c),
+        }
+    }
+}
+fn parse_value(chars: &mut Chars) -> Option<Json> {
+    skip_space(chars);
+    let v = match *chars.peek()? {
+        'n' => parse_word(chars, "null", Json::Null)?,
+        't' => parse_word(chars, "true", Json::Bool(true))?,
+        'f' => parse_word(chars, "false", Json::Bool(false))?,
+        '"' => Json::String(parse_string(chars)?),
+        '[' | '{' => {
+            let object = chars.next()? == '{';
+            let mut items = vec![];
+            skip_space(chars);
+            if chars.peek() == Some(&if object { '}' } else { ']' }) {
+                chars.next();
+            }
+            else {
+                loop {
+                    skip_space(chars);
+                    let key = if object {
+                        let key = parse_string(chars)?;
+                        skip_space(chars);
+                        if chars.next()? != ':' { return None; }
+                        key
+                    } else { String::new() };
+                    items.push((key, parse_value(chars)?));
+                    match chars.next()? {
+                        ',' => (),
+                        '}' if object => break,
+                        ']' if !object => break,
+                        _ => return None,
+                    }
+                }
+            }
+            if object { Json::Object(items) } else { Json::Array(items.into_iter().map(|(_, v)| v).collect()) }
+        }
+        c if c == '-' || c.is_ascii_digit() => {
+            let mut num = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_digit() || "+-.eE".contains(c)) { break; }
+                num.push(c);
+                chars.next();
+            }
+            num.parse::<f64>().ok()?;
+            Json::Number(num)
+        }
+        _ => return None,
+    };
+    skip_space(chars);
+    Some(v)
+}
+#[test]
+fn test_json_parse() {
+    let v = Json::object(vec![
+        ("name", "a \"b\"\n\\c\u{1}".into()),
+        ("point", (1isize, -2isize).into()),
+        ("list", vec![true, false].into()),
+        ("missing", Option::<usize>::None.into()),
+        ("float", 0.25.into()),
+        ("empty", Json::object(vec![])),
+        ("none", Json::Array(vec![])),
+    ]);
+    assert_eq!(Json::parse(&v.to_string()), Some(v.clone()));
+    assert_eq!(Json::parse(" { \"a\" : [ 1 , 2 ] } ").unwrap().get("a").and_then(Json::as_array).map(|a| a.len()), Some(2));
+    assert_eq!(v.get("name").and_then(Json::as_str), Some("a \"b\"\n\\c\u{1}"));
+    for bad in &["", "{", "[1,]", "{\"a\" 1}", "tru", "1 2", "\"abc", "-"] {
+        assert_eq!(Json::parse(bad), None);
+    }
+}
+
+impl From<bool> for Json {
+    fn from(v: bool) -> Self { Json::Bool(v) }
+}
+impl From<usize> for Json {
+    fn from(v: usize) -> Self { Json::Number(v.to_string()) }
+}
+impl From<isize> for Json {
+    fn from(v: isize) -> Self { Json::Number(v.to_string()) }
+}
+impl From<f64> for Json {
+    fn from(v: f64) -> Self {
+        if v.is_finite() { Json::Number(v.to_string()) } else { Json::Null }
+    }
+}
+impl From<&str> for Json {
+    fn from(v: &str) -> Self { Json::String(v.to_owned()) }
+}
+impl From<String> for Json {
+    fn from(v: String) -> Self { Json::String(v) }
+}
+impl<A: Into<Json>, B: Into<Json>> From<(A, B)> for Json {
+    fn from((a, b): (A, B)) -> Self { Json::Array(vec![a.into(), b.into()]) }
+}
+impl<A: Into<Json>, B: Into<Json>, C: Into<Json>> From<(A, B, C)> for Json {
+    fn from((a, b, c): (A, B, C)) -> Self { Json::Array(vec![a.into(), b.into(), c.into()]) }
+}
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self { Json::Array(v.into_iter().map(Into::into).collect()) }
+}
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(v: Option<T>) -> Self { v.map(Into::into).unwrap_or(Json::Null) }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(v) => write!(f, "{}", v),
+            Json::Number(v) => write!(f, "{}", v),
+            Json::String(v) => write_str(f, v),
+            Json::Raw(v) => write!(f, "{}", v),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 { write!(f, ",")?; }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i != 0 { write!(f, ",")?; }
+                    write_str(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+#[test]
+fn test_json_display() {
+    let v = Json::object(vec![
+        ("name", "a \"b\"\n\\c".into()),
+        ("point", (1isize, -2isize).into()),
+        ("list", vec![true, false].into()),
+        ("missing", Option::<usize>::None.into()),
+        ("nan", f64::NAN.into()),
+        ("density", Json::rational(&BigRational::new(6.into(), 14.into()))),
+        ("empty", Json::object(vec![])),
+        ("raw", Json::Raw(r#"{"found":true}"#.to_owned())),
+    ]);
+    assert_eq!(v.to_string(), r#"{"name":"a \"b\"\n\\c","point":[1,-2],"list":[true,false],"missing":null,"nan":null,"density":"3/7","empty":{},"raw":{"found":true}}"#);
+    assert_eq!(Json::from("\u{1}").to_string(), r#""\u0001""#);
+}
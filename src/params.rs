@@ -0,0 +1,288 @@
+// the set types and infinite graphs searches are run on, and parsing for the values they're searched for
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use num::BigRational;
+use num::traits::{Zero, One};
+
+use crate::Error;
+use crate::periodic::PeriodicGraph;
+
+#[derive(Debug)]
+pub enum ValueError {
+    OutOfRange(String),
+    Malformed(String),
+}
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueError::OutOfRange(msg) | ValueError::Malformed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+pub fn parse_thresh(v: &str) -> Result<f64, ValueError> {
+    match v.parse::<f64>() {
+        Ok(v) if v > 0.0 && v <= 1.0 => Ok(v),
+        Ok(v) => Err(ValueError::OutOfRange(format!("thresh {} was outside valid range (0, 1]", v))),
+        Err(_) => Err(ValueError::Malformed(format!("failed to parse '{}' as float", v))),
+    }
+}
+pub fn parse_exact(v: &str, max: usize) -> Result<usize, ValueError> {
+    match v.parse::<usize>() {
+        Ok(v) if v <= max => Ok(v),
+        Ok(v) => Err(ValueError::OutOfRange(format!("count {} exceeded max {}", v, max))),
+        Err(_) => Err(ValueError::Malformed(format!("failed to parse '{}' as uint", v))),
+    }
+}
+pub fn parse_thresh_frac(v: &str) -> Result<BigRational, ValueError> {
+    let malformed = || ValueError::Malformed(format!("failed to parse '{}' as rational", v));
+
+    // first try to parse as rational
+    match v.parse::<BigRational>() {
+        Ok(v) if v > BigRational::zero() && v <= BigRational::one() => Ok(v),
+        Ok(v) => Err(ValueError::OutOfRange(format!("thresh {} was outside valid range (0, 1]", v))),
+        Err(_) => {
+            // on failure, if it doesn't start with '0.' it was incorrectly formatted rational
+            if !v.starts_with("0.") {
+                return Err(malformed());
+            }
+            // otherwise try to parse as exact decimal (0, 1)
+            match v[2..].parse::<BigRational>() {
+                Ok(n) if n > BigRational::zero() => {
+                    Ok(n / BigRational::from_integer(10.into()).pow(v.len() as i32 - 2))
+                }
+                _ => Err(malformed()),
+            }
+        }
+    }
+}
+#[test]
+fn test_parse_thresh_frac() {
+    assert_eq!(parse_thresh_frac("2/5").unwrap(), BigRational::new(2.into(), 5.into()));
+    assert_eq!(parse_thresh_frac("0.375").unwrap(), BigRational::new(3.into(), 8.into()));
+    assert!(matches!(parse_thresh_frac("3/2"), Err(ValueError::OutOfRange(_))));
+    assert!(matches!(parse_thresh_frac("0.x"), Err(ValueError::Malformed(_))));
+    assert!(matches!(parse_thresh_frac("1.5"), Err(ValueError::Malformed(_))));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    DOM, ODOM,
+    EDOM, EODOM,
+    LD, REDLD, DETLD, ERRLD,
+    IC, REDIC, DETIC, RSPIC, ERRIC,
+    OLD, REDOLD, DETOLD, RSPOLD, ERROLD,
+}
+impl Parameter {
+    pub const ALL: [Parameter; 18] = [
+        Parameter::DOM, Parameter::ODOM,
+        Parameter::EDOM, Parameter::EODOM,
+        Parameter::LD, Parameter::REDLD, Parameter::DETLD, Parameter::ERRLD,
+        Parameter::IC, Parameter::REDIC, Parameter::DETIC, Parameter::RSPIC, Parameter::ERRIC,
+        Parameter::OLD, Parameter::REDOLD, Parameter::DETOLD, Parameter::RSPOLD, Parameter::ERROLD,
+    ];
+}
+impl FromStr for Parameter {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "dom" => Parameter::DOM,
+            "odom" => Parameter::ODOM,
+            "edom" => Parameter::EDOM,
+            "eodom" => Parameter::EODOM,
+            "ld" => Parameter::LD,
+            "red:ld" | "redld" => Parameter::REDLD,
+            "det:ld" | "detld" => Parameter::DETLD,
+            "err:ld" | "errld" => Parameter::ERRLD,
+            "ic" => Parameter::IC,
+            "red:ic" | "redic" => Parameter::REDIC,
+            "det:ic" | "detic" => Parameter::DETIC,
+            "rsp:ic" | "rspic" => Parameter::RSPIC,
+            "err:ic" | "erric" => Parameter::ERRIC,
+            "old" => Parameter::OLD,
+            "red:old" | "redold" => Parameter::REDOLD,
+            "det:old" | "detold" => Parameter::DETOLD,
+            "rsp:old" | "rspold" => Parameter::RSPOLD,
+            "err:old" | "errold" => Parameter::ERROLD,
+
+            _ => return Err(Error::UnknownParameter(s.to_owned())),
+        })
+    }
+}
+
+// expands $calc!(set, adj) with the code set type param denotes and whichever of the open and closed adjacencies it's over.
+// the share form expands $calc!(set, adj, share_adj) with the adjacency theo computes shares over too, and has no sets for EDOM and EODOM.
+macro_rules! param_family {
+    (share $param:expr, $calc:ident, $open:expr, $closed:expr) => {
+        match $param {
+            $crate::params::Parameter::DOM => $calc!(DOM, $closed, $closed),
+            $crate::params::Parameter::ODOM => $calc!(DOM, $open, $open),
+            param @ ($crate::params::Parameter::EDOM | $crate::params::Parameter::EODOM) => Err($crate::Error::Unsupported { param, search: "lower bound" }),
+            $crate::params::Parameter::LD => $calc!(LD, $open, $closed), // important: the LD sets use open adj for loc codes but closed adj for share
+            $crate::params::Parameter::REDLD => $calc!(REDLD, $open, $closed),
+            $crate::params::Parameter::DETLD => $calc!(DETLD, $open, $closed),
+            $crate::params::Parameter::ERRLD => $calc!(ERRLD, $open, $closed),
+            $crate::params::Parameter::IC => $calc!(OLD, $closed, $closed),
+            $crate::params::Parameter::REDIC => $calc!(RED, $closed, $closed),
+            $crate::params::Parameter::DETIC => $calc!(DET, $closed, $closed),
+            $crate::params::Parameter::RSPIC => $calc!(RSP, $closed, $closed),
+            $crate::params::Parameter::ERRIC => $calc!(ERR, $closed, $closed),
+            $crate::params::Parameter::OLD => $calc!(OLD, $open, $open),
+            $crate::params::Parameter::REDOLD => $calc!(RED, $open, $open),
+            $crate::params::Parameter::DETOLD => $calc!(DET, $open, $open),
+            $crate::params::Parameter::RSPOLD => $calc!(RSP, $open, $open),
+            $crate::params::Parameter::ERROLD => $calc!(ERR, $open, $open),
+        }
+    };
+    ($param:expr, $calc:ident, $open:expr, $closed:expr) => {
+        match $param {
+            $crate::params::Parameter::DOM => $calc!(DOM, $closed),
+            $crate::params::Parameter::ODOM => $calc!(DOM, $open),
+            $crate::params::Parameter::EDOM => $calc!(EDOM, $closed),
+            $crate::params::Parameter::EODOM => $calc!(EDOM, $open),
+            $crate::params::Parameter::LD => $calc!(LD, $open),
+            $crate::params::Parameter::REDLD => $calc!(REDLD, $open),
+            $crate::params::Parameter::DETLD => $calc!(DETLD, $open),
+            $crate::params::Parameter::ERRLD => $calc!(ERRLD, $open),
+            $crate::params::Parameter::IC => $calc!(OLD, $closed),
+            $crate::params::Parameter::REDIC => $calc!(RED, $closed),
+            $crate::params::Parameter::DETIC => $calc!(DET, $closed),
+            $crate::params::Parameter::RSPIC => $calc!(RSP, $closed),
+            $crate::params::Parameter::ERRIC => $calc!(ERR, $closed),
+            $crate::params::Parameter::OLD => $calc!(OLD, $open),
+            $crate::params::Parameter::REDOLD => $calc!(RED, $open),
+            $crate::params::Parameter::DETOLD => $calc!(DET, $open),
+            $crate::params::Parameter::RSPOLD => $calc!(RSP, $open),
+            $crate::params::Parameter::ERROLD => $calc!(ERR, $open),
+        }
+    };
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum Graph {
+    K, TRI, SQ, HEX, TMB,
+    SNS, KAG, RTH, TRH, ETRI, TSQ, TTRH, SNH,
+    PERIODIC(Arc<PeriodicGraph>), // a periodic graph loaded from a file or built as a power (see periodic.rs) - not part of ALL
+}
+// just the names, which is what gets printed (a periodic graph's edges are spelled out separately where they matter, see periodic::spell_out)
+impl fmt::Debug for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Graph::K => "K",
+            Graph::TRI => "TRI",
+            Graph::SQ => "SQ",
+            Graph::HEX => "HEX",
+            Graph::TMB => "TMB",
+            Graph::SNS => "SNS",
+            Graph::KAG => "KAG",
+            Graph::RTH => "RTH",
+            Graph::TRH => "TRH",
+            Graph::ETRI => "ETRI",
+            Graph::TSQ => "TSQ",
+            Graph::TTRH => "TTRH",
+            Graph::SNH => "SNH",
+            Graph::PERIODIC(_) => "PERIODIC",
+        })
+    }
+}
+impl Graph {
+    pub const ALL: [Graph; 13] = [
+        Graph::K, Graph::TRI, Graph::SQ, Graph::HEX, Graph::TMB,
+        Graph::SNS, Graph::KAG, Graph::RTH, Graph::TRH, Graph::ETRI, Graph::TSQ, Graph::TTRH, Graph::SNH,
+    ];
+
+    // the furthest any neighbor can be in either coordinate (see adj::AdjacentIterator::reach)
+    pub fn reach(&self) -> isize {
+        match self {
+            Graph::PERIODIC(g) => g.reach(),
+            _ => 1,
+        }
+    }
+    // the graph with the given name (or alias), ignoring case
+    fn from_name(s: &str) -> Option<Self> {
+        Some(match s.to_lowercase().as_str() {
+            "k" | "king" | "kings" => Graph::K,
+            "tri" => Graph::TRI,
+            "sq" | "square" | "grid" => Graph::SQ,
+            "hex" => Graph::HEX,
+            "tmb" => Graph::TMB,
+            "sns" | "snub-square" | "3.3.4.3.4" => Graph::SNS,
+            "kag" | "kagome" | "trihex" | "3.6.3.6" => Graph::KAG,
+            "rth" | "rhombitrihex" | "3.4.6.4" => Graph::RTH,
+            "trh" | "truncated-hex" | "3.12.12" => Graph::TRH,
+            "etri" | "elongated-tri" | "3.3.3.4.4" => Graph::ETRI,
+            "tsq" | "truncated-square" | "4.8.8" => Graph::TSQ,
+            "ttrh" | "truncated-trihex" | "4.6.12" => Graph::TTRH,
+            "snh" | "snub-hex" | "3.3.3.3.6" => Graph::SNH,
+
+            _ => return None,
+        })
+    }
+}
+impl FromStr for Graph {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "<graph>^r" is the distance-r power of a named or loaded graph
+        if let Some((base, r)) = s.rsplit_once('^') {
+            if let Ok(r) = r.parse::<usize>() {
+                if r == 0 {
+                    return Err(Error::UnknownGraph(s.to_owned()));
+                }
+                let base = match base.get(..5) {
+                    Some(p) if p.eq_ignore_ascii_case("file:") => PeriodicGraph::with_shape(&base[5..])?,
+                    _ => PeriodicGraph::builtin(&Self::from_name(base).ok_or_else(|| Error::UnknownGraph(s.to_owned()))?),
+                };
+                return Ok(Graph::PERIODIC(Arc::new(base.power(r))));
+            }
+        }
+        // "file:path" loads a periodic graph - the path keeps its case
+        if s.get(..5).is_some_and(|p| p.eq_ignore_ascii_case("file:")) {
+            return Ok(Graph::PERIODIC(Arc::new(PeriodicGraph::with_shape(&s[5..])?)));
+        }
+        Self::from_name(s).ok_or_else(|| Error::UnknownGraph(s.to_owned()))
+    }
+}
+#[test]
+fn test_parse_names() {
+    // the debug names are what gets printed, so they need to parse back to the same thing
+    for param in Parameter::ALL.iter() {
+        assert_eq!(format!("{:?}", format!("{:?}", param).parse::<Parameter>().unwrap()), format!("{:?}", param));
+    }
+    for graph in Graph::ALL.iter() {
+        assert_eq!(format!("{:?}", format!("{:?}", graph).parse::<Graph>().unwrap()), format!("{:?}", graph));
+    }
+}
+
+// evaluates body with open and closed bound to the open and closed adjacent iterators of graph (the searches pick their code set type in body).
+// the second form leaves the periodic graphs out, handing them to other instead.
+macro_rules! graph_family {
+    (@with $o:expr, $c:expr, |$open:ident, $closed:ident| $body:expr) => {
+        { let $open = $o; let $closed = $c; $body }
+    };
+    ($graph:expr, |$open:ident, $closed:ident| $body:expr, $periodic:pat => $other:expr) => {
+        match $graph {
+            $crate::params::Graph::K => graph_family!(@with $crate::adj::OpenKing::default(), $crate::adj::ClosedKing::default(), |$open, $closed| $body),
+            $crate::params::Graph::TRI => graph_family!(@with $crate::adj::OpenTri::default(), $crate::adj::ClosedTri::default(), |$open, $closed| $body),
+            $crate::params::Graph::SQ => graph_family!(@with $crate::adj::OpenGrid::default(), $crate::adj::ClosedGrid::default(), |$open, $closed| $body),
+            $crate::params::Graph::HEX => graph_family!(@with $crate::adj::OpenHex::default(), $crate::adj::ClosedHex::default(), |$open, $closed| $body),
+            $crate::params::Graph::TMB => graph_family!(@with $crate::adj::OpenTMB::default(), $crate::adj::ClosedTMB::default(), |$open, $closed| $body),
+            $crate::params::Graph::SNS => graph_family!(@with $crate::adj::OpenSNS::default(), $crate::adj::ClosedSNS::default(), |$open, $closed| $body),
+            $crate::params::Graph::KAG => graph_family!(@with $crate::adj::OpenKAG::default(), $crate::adj::ClosedKAG::default(), |$open, $closed| $body),
+            $crate::params::Graph::RTH => graph_family!(@with $crate::adj::OpenRTH::default(), $crate::adj::ClosedRTH::default(), |$open, $closed| $body),
+            $crate::params::Graph::TRH => graph_family!(@with $crate::adj::OpenTRH::default(), $crate::adj::ClosedTRH::default(), |$open, $closed| $body),
+            $crate::params::Graph::ETRI => graph_family!(@with $crate::adj::OpenETRI::default(), $crate::adj::ClosedETRI::default(), |$open, $closed| $body),
+            $crate::params::Graph::TSQ => graph_family!(@with $crate::adj::OpenTSQ::default(), $crate::adj::ClosedTSQ::default(), |$open, $closed| $body),
+            $crate::params::Graph::TTRH => graph_family!(@with $crate::adj::OpenTTRH::default(), $crate::adj::ClosedTTRH::default(), |$open, $closed| $body),
+            $crate::params::Graph::SNH => graph_family!(@with $crate::adj::OpenSNH::default(), $crate::adj::ClosedSNH::default(), |$open, $closed| $body),
+            $periodic => $other,
+        }
+    };
+    ($graph:expr, |$open:ident, $closed:ident| $body:expr) => {
+        graph_family!($graph, |$open, $closed| $body, $crate::params::Graph::PERIODIC(g) => {
+            graph_family!(@with $crate::adj::OpenPeriodic::new(g.clone()), $crate::adj::ClosedPeriodic::new(g.clone()), |$open, $closed| $body)
+        })
+    };
+}
@@ -1,45 +1,42 @@
+use crate::point::Point;
+
+// a map over the points of a box (a rectangle, or a cuboid in 3d), stored densely in sorted point order
 #[derive(Clone)]
-pub struct PointMap<T> {
+pub struct PointMap<T, P: Point = (isize, isize)> {
     data: Vec<Option<T>>,
 
-    top: isize,
-    bottom: isize,
-    left: isize,
-    right: isize,
-
-    cols: isize,
+    lo: P, // the corners of the box
+    hi: P,
+    strides: P, // how far apart in data consecutive values of each coordinate are
 }
-impl<T> Default for PointMap<T> {
+impl<T, P: Point> Default for PointMap<T, P> {
     fn default() -> Self {
         Self {
             data: Default::default(),
 
-            top: 0,
-            bottom: -1, // top > bottom denotes empty
-            left: 0,
-            right: -1, // left > right denotes empty
+            lo: P::origin(),
+            hi: P::from_fn(|_| -1), // lo > hi denotes empty
 
-            cols: 0, // no columns for empty
+            strides: P::origin(),
         }
     }
 }
-impl<T> PointMap<T> {
-    pub fn with_bounds(a: (isize, isize), b: (isize, isize)) -> Self {
-        let mut v = Self {
-            data: vec![],
-            top: 0, bottom: 0, left: 0, right: 0,
-            cols: 0,
-        };
+impl<T, P: Point> PointMap<T, P> {
+    pub fn with_bounds(a: P, b: P) -> Self {
+        let mut v = Self::default();
         v.set_bounds(a, b);
         v
     }
-    pub fn set_bounds(&mut self, a: (isize, isize), b: (isize, isize)) {
-        let (top, bottom) = if a.0 <= b.0 { (a.0, b.0) } else { (b.0, a.0) };
-        let (left, right) = if a.1 <= b.1 { (a.1, b.1) } else { (b.1, a.1) };
+    pub fn set_bounds(&mut self, a: P, b: P) {
+        let lo = P::from_fn(|i| a.coord(i).min(b.coord(i)));
+        let hi = P::from_fn(|i| a.coord(i).max(b.coord(i)));
 
-        let rows = bottom - top + 1;
-        let cols = right - left + 1;
-        let size = (rows * cols) as usize;
+        let mut strides = vec![0; P::DIM];
+        let mut size = 1;
+        for i in (0..P::DIM).rev() {
+            strides[i] = size as isize;
+            size *= (hi.coord(i) - lo.coord(i) + 1) as usize;
+        }
 
         self.data.clear();
         self.data.reserve(size);
@@ -47,12 +44,9 @@ impl<T> PointMap<T> {
             self.data.push(None);
         }
 
-        self.top = top;
-        self.bottom = bottom;
-        self.left = left;
-        self.right = right;
-        
-        self.cols = cols;
+        self.lo = lo;
+        self.hi = hi;
+        self.strides = P::from_fn(|i| strides[i]);
     }
     pub fn clear(&mut self) {
         for i in self.data.iter_mut() {
@@ -60,39 +54,43 @@ impl<T> PointMap<T> {
         }
     }
 
-    fn get_pos(&self, val: (isize, isize)) -> usize {
-        assert!(val.0 >= self.top && val.0 <= self.bottom && val.1 >= self.left && val.1 <= self.right);
-        ((val.0 - self.top) * self.cols + (val.1 - self.left)) as usize
+    fn get_pos(&self, val: P) -> usize {
+        assert!((0..P::DIM).all(|i| val.coord(i) >= self.lo.coord(i) && val.coord(i) <= self.hi.coord(i)));
+        val.sub(self.lo).dot(self.strides) as usize
     }
-    pub fn insert(&mut self, key: (isize, isize), value: T) {
+    // the point stored at pos in data (the inverse of get_pos)
+    fn point_at(lo: P, hi: P, strides: P, pos: usize) -> P {
+        P::from_fn(|i| lo.coord(i) + pos as isize / strides.coord(i) % (hi.coord(i) - lo.coord(i) + 1))
+    }
+    pub fn insert(&mut self, key: P, value: T) {
         let pos = self.get_pos(key);
         self.data[pos] = Some(value);
     }
-    pub fn remove(&mut self, key: &(isize, isize)) {
+    pub fn remove(&mut self, key: &P) {
         let pos = self.get_pos(*key);
         self.data[pos] = None;
     }
-    pub fn contains_key(&self, key: &(isize, isize)) -> bool {
+    pub fn contains_key(&self, key: &P) -> bool {
         let pos = self.get_pos(*key);
         self.data[pos].is_some()
     }
-    pub fn get(&self, key: &(isize, isize)) -> Option<&T> {
+    pub fn get(&self, key: &P) -> Option<&T> {
         let pos = self.get_pos(*key);
         self.data[pos].as_ref()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = ((isize, isize), &T)> + Clone {
-        let (top, bottom, left, right) = (self.top, self.bottom, self.left, self.right);
-        (top..=bottom).flat_map(move |r| (left..=right).map(move |c| (r, c))).zip(self.data.iter()).filter_map(|(k, v)| Some((k, v.as_ref()?)))
+    pub fn iter(&self) -> impl Iterator<Item = (P, &T)> + Clone {
+        let (lo, hi, strides) = (self.lo, self.hi, self.strides);
+        self.data.iter().enumerate().filter_map(move |(i, v)| Some((Self::point_at(lo, hi, strides, i), v.as_ref()?)))
     }
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((isize, isize), &mut T)> {
-        let (top, bottom, left, right) = (self.top, self.bottom, self.left, self.right);
-        (top..=bottom).flat_map(move |r| (left..=right).map(move |c| (r, c))).zip(self.data.iter_mut()).filter_map(|(k, v)| Some((k, v.as_mut()?)))
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (P, &mut T)> {
+        let (lo, hi, strides) = (self.lo, self.hi, self.strides);
+        self.data.iter_mut().enumerate().filter_map(move |(i, v)| Some((Self::point_at(lo, hi, strides, i), v.as_mut()?)))
     }
 }
-impl<T> Extend<((isize, isize), T)> for PointMap<T> {
+impl<T, P: Point> Extend<(P, T)> for PointMap<T, P> {
     fn extend<I>(&mut self, iter: I)
-    where I: IntoIterator<Item = ((isize, isize), T)>
+    where I: IntoIterator<Item = (P, T)>
     {
         for (k, v) in iter {
             self.insert(k, v);
@@ -101,37 +99,37 @@ impl<T> Extend<((isize, isize), T)> for PointMap<T> {
 }
 
 #[derive(Clone, Default)]
-pub struct PointSet {
-    w: PointMap<()>,
+pub struct PointSet<P: Point = (isize, isize)> {
+    w: PointMap<(), P>,
 }
-impl PointSet {
-    pub fn with_bounds(a: (isize, isize), b: (isize, isize)) -> Self {
+impl<P: Point> PointSet<P> {
+    pub fn with_bounds(a: P, b: P) -> Self {
         Self { w: PointMap::with_bounds(a, b) }
     }
-    pub fn set_bounds(&mut self, a: (isize, isize), b: (isize, isize)) {
+    pub fn set_bounds(&mut self, a: P, b: P) {
         self.w.set_bounds(a, b)
     }
     pub fn clear(&mut self) {
         self.w.clear()
     }
 
-    pub fn insert(&mut self, value: (isize, isize)) {
+    pub fn insert(&mut self, value: P) {
         self.w.insert(value, ())
     }
-    pub fn remove(&mut self, value: &(isize, isize)) {
+    pub fn remove(&mut self, value: &P) {
         self.w.remove(value)
     }
-    pub fn contains(&self, value: &(isize, isize)) -> bool {
+    pub fn contains(&self, value: &P) -> bool {
         self.w.contains_key(value)
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = (isize, isize)> + '_ + Clone {
+    pub fn iter(&self) -> impl Iterator<Item = P> + '_ + Clone {
         self.w.iter().map(|(k, _)| k)
     }
 }
-impl Extend<(isize, isize)> for PointSet {
+impl<P: Point> Extend<P> for PointSet<P> {
     fn extend<I>(&mut self, iter: I)
-    where I: IntoIterator<Item = (isize, isize)>
+    where I: IntoIterator<Item = P>
     {
         for x in iter {
             self.insert(x);
@@ -222,4 +220,16 @@ fn test_point_set() {
     assert!(s.contains(&(1, 1)));
     assert!(s.contains(&(1, 2)));
     assert_eq!(s.iter().collect::<Vec<_>>(), vec![(1, 0), (1, 1), (1, 2)]);
-}
\ No newline at end of file
+}#[test]
+fn test_point_set_3d() {
+    let mut s = PointSet::<(isize, isize, isize)>::with_bounds((1, -2, 0), (-1, 2, 3));
+    let all = crate::point::Point::range((-1, -2, 0), (1, 2, 3));
+    for (i, &p) in all.iter().enumerate() {
+        assert_eq!(s.w.get_pos(p), i);
+    }
+    s.extend(vec![(1, 2, 3), (-1, -2, 0), (0, 0, 1)]);
+    assert!(s.contains(&(0, 0, 1)) && !s.contains(&(0, 1, 0)));
+    assert_eq!(s.iter().collect::<Vec<_>>(), vec![(-1, -2, 0), (0, 0, 1), (1, 2, 3)]);
+    s.remove(&(0, 0, 1));
+    assert_eq!(s.iter().collect::<Vec<_>>(), vec![(-1, -2, 0), (1, 2, 3)]);
+}
@@ -0,0 +1,270 @@
+// periodic graphs loaded from a file instead of written into adj as iterators, so new infinite graphs can be studied without recompiling.
+//
+// a graph is a rows x cols block of points (each its own class) repeated over the plane, given by its edges:
+//
+//     # the truncated square lattice (4.8.8)
+//     period 2 2
+//     0,0:0,-1 0,0:1,0 0,0:1,1
+//     0,1:1,1 0,1:-1,0
+//     1,0:1,-1
+//
+// the first line (other than comments and blank lines) gives the block size, and everything after is whitespace-separated edges "r,c:r,c".
+// every edge is repeated by the period, so each only needs to be given once (from either end, with either end outside the block).
+// edges can be any length (e.g. "period 1 1" with a few long edges is a custom stencil on the grid), but the searches pad and bound
+// everything by the longest one, so the further the graph reaches the slower they get.
+//
+// the same representation also gives distance-r powers of any periodic graph (see PeriodicGraph::power).
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::{adj, util, Error};
+use crate::adj::AdjacentIterator;
+use crate::params::Graph;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodicGraph {
+    rows: isize,
+    cols: isize,
+    offsets: Vec<Vec<(isize, isize)>>, // sorted neighbor offsets of each class
+    classes: Vec<(isize, isize)>,
+    reach: isize,
+}
+impl PeriodicGraph {
+    fn from_offsets(rows: isize, cols: isize, offsets: Vec<Vec<(isize, isize)>>) -> Self {
+        // a graph with no edges still needs some padding to be searched
+        let reach = offsets.iter().flatten().map(|d| d.0.abs().max(d.1.abs())).max().unwrap_or(1);
+        Self {
+            rows, cols, offsets, reach,
+            classes: (0..rows).flat_map(|r| (0..cols).map(move |c| (r, c))).collect(),
+        }
+    }
+    pub fn with_shape(path: &str) -> Result<Self, Error> {
+        let f = File::open(path).map_err(|e| Error::io("open periodic graph file", path, e))?;
+        Self::read_shape(path, BufReader::new(f))
+    }
+    // reads a graph in the format described at the top of this file - path is only for error messages
+    fn read_shape<R: BufRead>(path: &str, f: R) -> Result<Self, Error> {
+        let format = |line: usize, col: usize, msg| Error::Format { path: path.to_owned(), line, col, msg };
+        let parse_point = |tok: &str| -> Option<(isize, isize)> {
+            let mut parts = tok.split(',');
+            let p = (parts.next()?.parse().ok()?, parts.next()?.parse().ok()?);
+            if parts.next().is_some() { None } else { Some(p) }
+        };
+
+        let mut period: Option<(isize, isize)> = None;
+        let mut adj: Vec<BTreeSet<(isize, isize)>> = vec![];
+        let mut lines = 0;
+        for (row, line) in f.lines().enumerate() {
+            let line = line.map_err(|e| Error::io("read periodic graph file", path, e))?;
+            lines = row + 1;
+            let line = match line.find('#') {
+                Some(p) => &line[..p],
+                None => &line[..],
+            };
+            let mut tokens = util::tokens(line);
+            let (rows, cols) = match period {
+                Some(p) => p,
+                None => {
+                    let (col, first) = match tokens.next() {
+                        Some(t) => t,
+                        None => continue,
+                    };
+                    if first != "period" {
+                        return Err(format(row + 1, col, "expected the period (\"period rows cols\") before any edges"));
+                    }
+                    let mut dim = || match tokens.next() {
+                        Some((col, tok)) => match tok.parse::<isize>() {
+                            Ok(v) if v > 0 => Ok(v),
+                            _ => Err(format(row + 1, col, "expected a positive period")),
+                        },
+                        None => Err(format(row + 1, line.chars().count() + 1, "expected a positive period")),
+                    };
+                    let p = (dim()?, dim()?);
+                    if let Some((col, _)) = tokens.next() {
+                        return Err(format(row + 1, col, "unexpected token after the period"));
+                    }
+                    period = Some(p);
+                    adj = vec![Default::default(); (p.0 * p.1) as usize];
+                    continue;
+                }
+            };
+            let class = |p: (isize, isize)| util::modulus(p.0, rows) * cols as usize + util::modulus(p.1, cols);
+            for (col, tok) in tokens {
+                let p = match tok.find(':') {
+                    Some(x) => x,
+                    None => return Err(format(row + 1, col, "encountered token without a ':' separator")),
+                };
+                let (a, b) = match (parse_point(&tok[..p]), parse_point(&tok[p + 1..])) {
+                    (Some(a), Some(b)) => (a, b),
+                    _ => return Err(format(row + 1, col, "expected an edge between two points \"r,c:r,c\"")),
+                };
+                let d = (b.0 - a.0, b.1 - a.1);
+                if d == (0, 0) {
+                    return Err(format(row + 1, col, "encountered reflexive connection"));
+                }
+                adj[class(a)].insert(d);
+                adj[class(b)].insert((-d.0, -d.1));
+            }
+        }
+        let (rows, cols) = match period {
+            Some(p) => p,
+            None => return Err(format(lines + 1, 1, "graph has no period")),
+        };
+
+        Ok(Self::from_offsets(rows, cols, adj.into_iter().map(|a| a.into_iter().collect()).collect()))
+    }
+    // reads the neighbors of a built-in graph off its iterator, using the smallest block its classes repeat over
+    pub fn from_adj<Adj: AdjacentIterator<Point = (isize, isize)>>(adj: &Adj) -> Self {
+        let repeats = |rows: isize, cols: isize| {
+            (-12..12).all(|r| (-12..12).all(|c| adj.class((r, c)) == adj.class((util::modulus(r, rows) as isize, util::modulus(c, cols) as isize))))
+        };
+        let mut blocks: Vec<(isize, isize)> = (1..=12).flat_map(|r| (1..=12).map(move |c| (r, c))).collect();
+        blocks.sort_by_key(|&(r, c)| (r * c, r));
+        let (rows, cols) = blocks.into_iter().find(|&(r, c)| repeats(r, c)).expect("built-in graphs repeat within 12x12");
+        let offsets = (0..rows).flat_map(|r| (0..cols).map(move |c| (r, c))).map(|(r, c)| adj.open().at((r, c)).map(|p| (p.0 - r, p.1 - c)).collect()).collect();
+        Self::from_offsets(rows, cols, offsets)
+    }
+    // the periodic form of one of the named graphs
+    pub fn builtin(graph: &Graph) -> Self {
+        match graph {
+            Graph::K => Self::from_adj(&adj::OpenKing::default()),
+            Graph::TRI => Self::from_adj(&adj::OpenTri::default()),
+            Graph::SQ => Self::from_adj(&adj::OpenGrid::default()),
+            Graph::HEX => Self::from_adj(&adj::OpenHex::default()),
+            Graph::TMB => Self::from_adj(&adj::OpenTMB::default()),
+            Graph::SNS => Self::from_adj(&adj::OpenSNS::default()),
+            Graph::KAG => Self::from_adj(&adj::OpenKAG::default()),
+            Graph::RTH => Self::from_adj(&adj::OpenRTH::default()),
+            Graph::TRH => Self::from_adj(&adj::OpenTRH::default()),
+            Graph::ETRI => Self::from_adj(&adj::OpenETRI::default()),
+            Graph::TSQ => Self::from_adj(&adj::OpenTSQ::default()),
+            Graph::TTRH => Self::from_adj(&adj::OpenTTRH::default()),
+            Graph::SNH => Self::from_adj(&adj::OpenSNH::default()),
+            Graph::PERIODIC(g) => (**g).clone(),
+        }
+    }
+    // the distance-r power: points are adjacent if they're within r steps of one another.
+    // e.g. r-identifying codes on the square grid are identifying codes on the power, whose closed neighborhoods are radius-r diamonds.
+    pub fn power(&self, r: usize) -> Self {
+        let offsets = self.classes.iter().map(|&start| {
+            let mut seen: HashSet<(isize, isize)> = Some(start).into_iter().collect();
+            let mut frontier = vec![start];
+            for _ in 0..r {
+                frontier = frontier.iter().flat_map(|&p| self.adjacent(self.class(p.0, p.1)).iter().map(move |d| (p.0 + d.0, p.1 + d.1))).filter(|p| seen.insert(*p)).collect();
+            }
+            let mut ball: Vec<_> = seen.into_iter().filter(|&p| p != start).map(|p| (p.0 - start.0, p.1 - start.1)).collect();
+            ball.sort_unstable();
+            ball
+        }).collect();
+        Self::from_offsets(self.rows, self.cols, offsets)
+    }
+    pub fn class(&self, row: isize, col: isize) -> usize {
+        util::modulus(row, self.rows) * self.cols as usize + util::modulus(col, self.cols)
+    }
+    pub fn adjacent(&self, class: usize) -> &[(isize, isize)] {
+        &self.offsets[class]
+    }
+    pub fn classes(&self) -> &[(isize, isize)] {
+        &self.classes
+    }
+    pub fn reach(&self) -> isize {
+        self.reach
+    }
+}
+// writes the graph back out in the file format (every edge from the block, once)
+impl fmt::Display for PeriodicGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "period {} {}", self.rows, self.cols)?;
+        for (&(r, c), offsets) in self.classes.iter().zip(&self.offsets) {
+            let edges: Vec<String> = offsets.iter().filter(|&&d| d > (0, 0)).map(|d| format!("{},{}:{},{}", r, c, r + d.0, c + d.1)).collect();
+            if !edges.is_empty() {
+                writeln!(f, "{}", edges.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// a checkpoint's search description, spelling out the periodic graph if that's what it's on (its name alone says nothing)
+pub fn spell_out(search: String, graph: &Graph) -> String {
+    match graph {
+        Graph::PERIODIC(g) => format!("{}\n{}", search, g),
+        _ => search,
+    }
+}
+#[test]
+fn test_periodic_read() {
+    use crate::adj::{AdjacentIterator, OpenTSQ};
+
+    let tsq = OpenTSQ::default();
+    let g = PeriodicGraph::read_shape("test", &b"# comment\n\nperiod 2 2 # the block\n0,0:0,-1 0,0:1,0 0,0:1,1\n0,1:1,1 0,1:-1,0 0,1:0,2\n1,0:1,-1\n"[..]).unwrap();
+    assert_eq!(g.classes(), tsq.classes());
+    for (class, &(r, c)) in g.classes().iter().enumerate() {
+        let offsets: Vec<_> = tsq.at((r, c)).map(|p| (p.0 - r, p.1 - c)).collect();
+        assert_eq!(g.adjacent(class), &offsets[..]);
+    }
+    assert_eq!(g.class(-3, 4), tsq.class((-3, 4)));
+    assert_eq!(PeriodicGraph::read_shape("test", g.to_string().as_bytes()).unwrap(), g);
+    assert_eq!(g.reach(), 1);
+    assert_eq!(PeriodicGraph::from_adj(&tsq), g);
+
+    // edges can reach further than a king's move
+    let g = PeriodicGraph::read_shape("test", &b"period 1 1\n0,0:2,1 0,0:0,-1\n"[..]).unwrap();
+    assert_eq!(g.adjacent(0), &[(-2, -1), (0, -1), (0, 1), (2, 1)]);
+    assert_eq!(g.reach(), 2);
+
+    let err = |text: &[u8]| match PeriodicGraph::read_shape("test", text) {
+        Err(Error::Format { line, col, msg, .. }) => (line, col, msg),
+        _ => panic!(),
+    };
+    assert_eq!(err(b"0,0:0,1\n"), (1, 1, "expected the period (\"period rows cols\") before any edges"));
+    assert_eq!(err(b"period 2 0\n"), (1, 10, "expected a positive period"));
+    assert_eq!(err(b"period 2\n"), (1, 9, "expected a positive period"));
+    assert_eq!(err(b"period 1 1 1\n"), (1, 12, "unexpected token after the period"));
+    assert_eq!(err(b"period 1 1\n0,0:0,1 0,0-1,0\n"), (2, 9, "encountered token without a ':' separator"));
+    assert_eq!(err(b"period 1 1\n0,0:0,1,2\n"), (2, 1, "expected an edge between two points \"r,c:r,c\""));
+    assert_eq!(err(b"period 1 1\n  1,1:1,1\n"), (2, 3, "encountered reflexive connection"));
+    assert_eq!(err(b"# nothing\n"), (2, 1, "graph has no period"));
+}
+#[test]
+fn test_periodic_graph() {
+    use crate::adj::{self, AdjacentIterator};
+    use crate::params::{Graph, Parameter};
+    use crate::tessellation::{self, Geometry, GeometryTessellation};
+    use crate::Engine;
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+
+    let graph: Graph = "file:example-lattices/truncated-square.txt".parse().unwrap();
+    let g = match &graph {
+        Graph::PERIODIC(g) => g.clone(),
+        _ => panic!(),
+    };
+    assert_eq!(*g, PeriodicGraph::builtin(&Graph::TSQ));
+    assert!(matches!("file:example-lattices/missing.txt".parse::<Graph>(), Err(Error::Io { .. })));
+
+    // graphs are values, so several can be in use at once
+    let grid: Graph = "file:example-lattices/grid.txt".parse().unwrap();
+    assert_eq!(grid, Graph::PERIODIC(Arc::new(PeriodicGraph::builtin(&Graph::SQ))));
+    assert_ne!(grid, graph);
+
+    let (open, closed) = (adj::OpenPeriodic::new(g.clone()), adj::ClosedPeriodic::new(g));
+    for r in -4..4 {
+        for c in -4..4 {
+            assert!(open.at((r, c)).eq(adj::OpenTSQ::default().at((r, c))));
+            assert!(closed.at((r, c)).eq(adj::ClosedTSQ::default().at((r, c))));
+        }
+    }
+    for param in &[Parameter::DOM, Parameter::IC, Parameter::LD] {
+        for goal in &["0.3", "0.5"] {
+            let mut a = GeometryTessellation::try_from(Geometry::rectangle(2, 2)).unwrap();
+            let mut b = GeometryTessellation::try_from(Geometry::rectangle(2, 2)).unwrap();
+            let a = tessellation::solve(&mut a, *param, &graph, goal, Engine::Recursive, 1).unwrap();
+            let b = tessellation::solve(&mut b, *param, &Graph::TSQ, goal, Engine::Recursive, 1).unwrap();
+            assert_eq!(a, b, "{:?} {}", param, goal);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// points of the infinite graphs: (row, col) pairs on the planar ones and (row, col, layer) triples on the cubic lattices.
+// the tessellation and discharging searches are written against Point so they work in either dimension.
+
+use std::fmt;
+use std::hash::Hash;
+
+pub type Point2 = (isize, isize);
+pub type Point3 = (isize, isize, isize);
+
+// tuples order lexicographically, which is the order everything iterates points in
+pub trait Point: Copy + Ord + Hash + fmt::Debug + Send + Sync + 'static {
+    const DIM: usize;
+
+    fn coord(self, i: usize) -> isize;
+    fn from_fn<F: FnMut(usize) -> isize>(f: F) -> Self;
+
+    fn origin() -> Self {
+        Self::from_fn(|_| 0)
+    }
+    fn add(self, other: Self) -> Self {
+        Self::from_fn(|i| self.coord(i) + other.coord(i))
+    }
+    fn sub(self, other: Self) -> Self {
+        Self::from_fn(|i| self.coord(i) - other.coord(i))
+    }
+    fn scale(self, k: isize) -> Self {
+        Self::from_fn(|i| k * self.coord(i))
+    }
+    fn dot(self, other: Self) -> isize {
+        (0..Self::DIM).map(|i| self.coord(i) * other.coord(i)).sum()
+    }
+    fn coords(self) -> Vec<isize> {
+        (0..Self::DIM).map(|i| self.coord(i)).collect()
+    }
+    // every point between lo and hi (inclusive in every coordinate), in sorted order
+    fn range(lo: Self, hi: Self) -> Vec<Self> {
+        let mut v = vec![];
+        if (0..Self::DIM).all(|i| lo.coord(i) <= hi.coord(i)) {
+            let mut c = lo.coords();
+            loop {
+                v.push(Self::from_fn(|i| c[i]));
+                // count up like an odometer, last coordinate fastest
+                let mut i = Self::DIM;
+                loop {
+                    if i == 0 {
+                        return v;
+                    }
+                    i -= 1;
+                    if c[i] < hi.coord(i) {
+                        c[i] += 1;
+                        break;
+                    }
+                    c[i] = lo.coord(i);
+                }
+            }
+        }
+        v
+    }
+    // the offsets of a king's move (every coordinate changes by at most 1), in sorted order
+    fn king_moves() -> Vec<Self> {
+        Self::range(Self::from_fn(|_| -1), Self::from_fn(|_| 1)).into_iter().filter(|&d| d != Self::origin()).collect()
+    }
+}
+impl Point for Point2 {
+    const DIM: usize = 2;
+
+    fn coord(self, i: usize) -> isize {
+        match i {
+            0 => self.0,
+            1 => self.1,
+            _ => panic!("coordinate {} of a 2d point", i),
+        }
+    }
+    fn from_fn<F: FnMut(usize) -> isize>(mut f: F) -> Self {
+        (f(0), f(1))
+    }
+}
+impl Point for Point3 {
+    const DIM: usize = 3;
+
+    fn coord(self, i: usize) -> isize {
+        match i {
+            0 => self.0,
+            1 => self.1,
+            2 => self.2,
+            _ => panic!("coordinate {} of a 3d point", i),
+        }
+    }
+    fn from_fn<F: FnMut(usize) -> isize>(mut f: F) -> Self {
+        (f(0), f(1), f(2))
+    }
+}
+#[test]
+fn test_point() {
+    assert_eq!(Point2::range((0, -1), (1, 0)), vec![(0, -1), (0, 0), (1, -1), (1, 0)]);
+    assert_eq!(Point2::range((0, 0), (-1, 0)), vec![]);
+    assert_eq!(Point3::range((0, 0, 0), (1, 1, 1)).len(), 8);
+    assert!(Point3::range((-2, 0, 1), (1, 2, 3)).windows(2).all(|w| w[0] < w[1]));
+
+    assert_eq!(Point2::king_moves(), vec![(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)]);
+    assert_eq!(Point3::king_moves().len(), 26);
+
+    assert_eq!((1, 2, 3).add((3, 2, 1)).sub((1, 1, 1)), (3, 3, 3));
+    assert_eq!((1, -2).scale(3).dot((1, 1)), -3);
+}
@@ -0,0 +1,702 @@
+use std::{fmt, mem};
+use std::io::{self, Write};
+
+use crate::cnf::{Cnf, Lit};
+
+// internal literal representation: 2 * var + 1 if negated (vars are 0-based here, unlike dimacs)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct L(u32);
+impl L {
+    fn from_dimacs(x: Lit) -> Self {
+        L(2 * (x.unsigned_abs() - 1) + (x < 0) as u32)
+    }
+    fn var(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+    fn negated(self) -> bool {
+        self.0 & 1 != 0
+    }
+    fn neg(self) -> Self {
+        L(self.0 ^ 1)
+    }
+    fn idx(self) -> usize {
+        self.0 as usize
+    }
+    fn to_dimacs(self) -> Lit {
+        let v = self.var() as Lit + 1;
+        if self.negated() { -v } else { v }
+    }
+}
+
+struct Clause {
+    lits: Vec<L>,
+    learnt: bool,
+    deleted: bool,
+    activity: f64,
+}
+
+// indexed max-heap of variables ordered by activity
+#[derive(Default)]
+struct VarHeap {
+    heap: Vec<usize>,
+    index: Vec<Option<usize>>,
+}
+impl VarHeap {
+    fn contains(&self, v: usize) -> bool {
+        self.index[v].is_some()
+    }
+    fn up(&mut self, mut i: usize, act: &[f64]) {
+        let v = self.heap[i];
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if act[self.heap[parent]] >= act[v] { break; }
+            self.heap[i] = self.heap[parent];
+            self.index[self.heap[i]] = Some(i);
+            i = parent;
+        }
+        self.heap[i] = v;
+        self.index[v] = Some(i);
+    }
+    fn down(&mut self, mut i: usize, act: &[f64]) {
+        let v = self.heap[i];
+        loop {
+            let mut child = 2 * i + 1;
+            if child >= self.heap.len() { break; }
+            if child + 1 < self.heap.len() && act[self.heap[child + 1]] > act[self.heap[child]] {
+                child += 1;
+            }
+            if act[self.heap[child]] <= act[v] { break; }
+            self.heap[i] = self.heap[child];
+            self.index[self.heap[i]] = Some(i);
+            i = child;
+        }
+        self.heap[i] = v;
+        self.index[v] = Some(i);
+    }
+    fn insert(&mut self, v: usize, act: &[f64]) {
+        if self.index.len() <= v {
+            self.index.resize(v + 1, None);
+        }
+        if self.contains(v) { return; }
+        self.heap.push(v);
+        self.up(self.heap.len() - 1, act);
+    }
+    fn bumped(&mut self, v: usize, act: &[f64]) {
+        if let Some(i) = self.index[v] {
+            self.up(i, act);
+        }
+    }
+    fn pop(&mut self, act: &[f64]) -> Option<usize> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.index[top] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.down(0, act);
+        }
+        Some(top)
+    }
+}
+
+// finite subsequences of the luby sequence (1, 1, 2, 1, 1, 2, 4, ...) used for restart intervals
+fn luby(mut x: u64) -> u64 {
+    let (mut size, mut seq) = (1, 0);
+    while size < x + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != x {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        x %= size;
+    }
+    1 << seq
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Stats {
+    pub decisions: u64,
+    pub conflicts: u64,
+    pub propagations: u64,
+    pub restarts: u64,
+}
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} decisions, {} conflicts, {} propagations, {} restarts", self.decisions, self.conflicts, self.propagations, self.restarts)
+    }
+}
+
+// a conflict-driven clause learning sat solver (two watched literals, 1uip learning, vsids, luby restarts).
+// clauses can be added between calls to solve, so the same solver can be used to enumerate models.
+#[derive(Default)]
+pub struct Solver {
+    clauses: Vec<Clause>,
+    learnts: Vec<usize>,
+    watches: Vec<Vec<usize>>,
+
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<L>,
+    trail_lim: Vec<usize>,
+    qhead: usize,
+
+    activity: Vec<f64>,
+    var_inc: f64,
+    cla_inc: f64,
+    heap: VarHeap,
+    phase: Vec<bool>,
+    seen: Vec<bool>,
+
+    max_learnts: f64,
+    unsat: bool,
+    stats: Stats,
+
+    // drup proof log (learnt clauses and deletions), only recorded if enabled
+    proof: Option<Vec<(bool, Vec<Lit>)>>,
+}
+impl Solver {
+    pub fn new(vars: usize) -> Self {
+        let mut s = Self { var_inc: 1.0, cla_inc: 1.0, ..Default::default() };
+        s.reserve_vars(vars);
+        s
+    }
+    pub fn from_cnf(cnf: &Cnf) -> Self {
+        let mut s = Self::new(cnf.var_count());
+        for c in cnf.clauses() {
+            s.add_clause(c);
+        }
+        s
+    }
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+    // starts recording a drup proof, which after an unsat result refutes every clause added to the solver
+    pub fn enable_proof(&mut self) {
+        if self.proof.is_none() {
+            self.proof = Some(vec![]);
+        }
+    }
+    pub fn write_proof(&self, f: &mut dyn Write) -> io::Result<()> {
+        for (deleted, lits) in self.proof.iter().flatten() {
+            if *deleted {
+                write!(f, "d ")?;
+            }
+            for x in lits {
+                write!(f, "{} ", x)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+    fn log_proof(&mut self, deleted: bool, lits: &[L]) {
+        if let Some(proof) = &mut self.proof {
+            proof.push((deleted, lits.iter().map(|p| p.to_dimacs()).collect()));
+        }
+    }
+    fn set_unsat(&mut self) {
+        if !self.unsat {
+            self.unsat = true;
+            self.log_proof(false, &[]);
+        }
+    }
+    fn reserve_vars(&mut self, vars: usize) {
+        while self.values.len() < vars {
+            let v = self.values.len();
+            self.values.push(None);
+            self.levels.push(0);
+            self.reasons.push(None);
+            self.activity.push(0.0);
+            self.phase.push(false);
+            self.seen.push(false);
+            self.watches.push(vec![]);
+            self.watches.push(vec![]);
+            self.heap.insert(v, &self.activity);
+        }
+    }
+
+    fn value(&self, p: L) -> Option<bool> {
+        self.values[p.var()].map(|x| x != p.negated())
+    }
+    fn decision_level(&self) -> usize {
+        self.trail_lim.len()
+    }
+    fn enqueue(&mut self, p: L, reason: Option<usize>) {
+        debug_assert!(self.value(p).is_none());
+        self.values[p.var()] = Some(!p.negated());
+        self.levels[p.var()] = self.decision_level();
+        self.reasons[p.var()] = reason;
+        self.trail.push(p);
+    }
+    fn backtrack(&mut self, level: usize) {
+        if self.decision_level() <= level { return; }
+        let lim = self.trail_lim[level];
+        for p in self.trail.drain(lim..).rev() {
+            let v = p.var();
+            self.values[v] = None;
+            self.reasons[v] = None;
+            self.phase[v] = !p.negated();
+            self.heap.insert(v, &self.activity);
+        }
+        self.trail_lim.truncate(level);
+        self.qhead = self.trail.len();
+    }
+
+    // adds a clause (dimacs literals). returns false if the formula is now known to be unsatisfiable.
+    pub fn add_clause(&mut self, clause: &[Lit]) -> bool {
+        if self.unsat { return false; }
+        self.backtrack(0);
+        if let Some(m) = clause.iter().map(|x| x.unsigned_abs() as usize).max() {
+            self.reserve_vars(m);
+        }
+
+        // drop false and duplicate literals, and skip the clause entirely if it's already satisfied
+        let mut lits: Vec<L> = clause.iter().map(|&x| L::from_dimacs(x)).collect();
+        lits.sort_by_key(|p| p.0);
+        lits.dedup();
+        if lits.windows(2).any(|w| w[0] == w[1].neg()) { return true; }
+        if lits.iter().any(|&p| self.value(p) == Some(true)) { return true; }
+        lits.retain(|&p| self.value(p).is_none());
+
+        match lits.len() {
+            0 => self.set_unsat(),
+            1 => {
+                self.enqueue(lits[0], None);
+                if self.propagate().is_some() {
+                    self.set_unsat();
+                }
+            }
+            _ => { self.attach(lits, false); }
+        }
+        !self.unsat
+    }
+    fn attach(&mut self, lits: Vec<L>, learnt: bool) -> usize {
+        let ci = self.clauses.len();
+        self.watches[lits[0].idx()].push(ci);
+        self.watches[lits[1].idx()].push(ci);
+        self.clauses.push(Clause { lits, learnt, deleted: false, activity: 0.0 });
+        if learnt {
+            self.learnts.push(ci);
+        }
+        ci
+    }
+
+    // unit propagation over the trail - returns the index of a conflicting clause, if any
+    fn propagate(&mut self) -> Option<usize> {
+        while self.qhead < self.trail.len() {
+            let p = self.trail[self.qhead];
+            self.qhead += 1;
+            self.stats.propagations += 1;
+            let false_lit = p.neg();
+
+            let mut ws = mem::take(&mut self.watches[false_lit.idx()]);
+            let (mut i, mut j) = (0, 0);
+            let mut conflict = None;
+            'next_clause: while i < ws.len() {
+                let ci = ws[i];
+                i += 1;
+                if self.clauses[ci].deleted { continue; } // lazily drop watches of deleted clauses
+
+                // make sure the false literal is in position 1
+                let lits = &mut self.clauses[ci].lits;
+                if lits[0] == false_lit {
+                    lits.swap(0, 1);
+                }
+                let first = lits[0];
+                let first_value = self.values[first.var()].map(|x| x != first.negated());
+                if first_value == Some(true) {
+                    ws[j] = ci;
+                    j += 1;
+                    continue;
+                }
+
+                // look for a new literal to watch
+                for k in 2..lits.len() {
+                    let q = lits[k];
+                    if self.values[q.var()].map(|x| x != q.negated()) != Some(false) {
+                        lits.swap(1, k);
+                        self.watches[q.idx()].push(ci);
+                        continue 'next_clause;
+                    }
+                }
+
+                // otherwise the clause is unit or conflicting
+                ws[j] = ci;
+                j += 1;
+                if first_value == Some(false) {
+                    conflict = Some(ci);
+                    while i < ws.len() {
+                        ws[j] = ws[i];
+                        i += 1;
+                        j += 1;
+                    }
+                }
+                else {
+                    self.enqueue(first, Some(ci));
+                }
+            }
+            ws.truncate(j);
+            self.watches[false_lit.idx()] = ws;
+
+            if conflict.is_some() {
+                self.qhead = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump_var(&mut self, v: usize) {
+        self.activity[v] += self.var_inc;
+        if self.activity[v] > 1e100 {
+            for a in self.activity.iter_mut() {
+                *a *= 1e-100;
+            }
+            self.var_inc *= 1e-100;
+        }
+        self.heap.bumped(v, &self.activity);
+    }
+    fn bump_clause(&mut self, ci: usize) {
+        self.clauses[ci].activity += self.cla_inc;
+        if self.clauses[ci].activity > 1e20 {
+            for &l in self.learnts.iter() {
+                self.clauses[l].activity *= 1e-20;
+            }
+            self.cla_inc *= 1e-20;
+        }
+    }
+
+    // first uip conflict analysis - returns the learnt clause (asserting literal first) and the level to backtrack to
+    fn analyze(&mut self, mut confl: usize) -> (Vec<L>, usize) {
+        let mut learnt = vec![L(0)];
+        let mut path_count = 0;
+        let mut p: Option<L> = None;
+        let mut index = self.trail.len();
+
+        loop {
+            if self.clauses[confl].learnt {
+                self.bump_clause(confl);
+            }
+            let start = if p.is_none() { 0 } else { 1 };
+            for k in start..self.clauses[confl].lits.len() {
+                let q = self.clauses[confl].lits[k];
+                let v = q.var();
+                if !self.seen[v] && self.levels[v] > 0 {
+                    self.bump_var(v);
+                    self.seen[v] = true;
+                    if self.levels[v] >= self.decision_level() {
+                        path_count += 1;
+                    }
+                    else {
+                        learnt.push(q);
+                    }
+                }
+            }
+
+            // select the next literal on the trail to expand
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var()] { break; }
+            }
+            let next = self.trail[index];
+            self.seen[next.var()] = false;
+            path_count -= 1;
+            p = Some(next);
+            if path_count == 0 { break; }
+            confl = self.reasons[next.var()].unwrap();
+        }
+        learnt[0] = p.unwrap().neg();
+
+        // drop literals implied by the rest of the clause (local minimization)
+        let mut keep = vec![learnt[0]];
+        for &q in &learnt[1..] {
+            let redundant = match self.reasons[q.var()] {
+                None => false,
+                Some(r) => self.clauses[r].lits[1..].iter().all(|x| self.seen[x.var()] || self.levels[x.var()] == 0),
+            };
+            if !redundant {
+                keep.push(q);
+            }
+        }
+        for q in &learnt[1..] {
+            self.seen[q.var()] = false;
+        }
+        let mut learnt = keep;
+
+        // find the backtrack level and put a literal from it in the second watch position
+        let mut bt = 0;
+        if learnt.len() > 1 {
+            let mut max_i = 1;
+            for k in 2..learnt.len() {
+                if self.levels[learnt[k].var()] > self.levels[learnt[max_i].var()] {
+                    max_i = k;
+                }
+            }
+            learnt.swap(1, max_i);
+            bt = self.levels[learnt[1].var()];
+        }
+        (learnt, bt)
+    }
+
+    fn locked(&self, ci: usize) -> bool {
+        let p = self.clauses[ci].lits[0];
+        self.reasons[p.var()] == Some(ci) && self.value(p) == Some(true)
+    }
+    // removes the less active half of the learnt clauses
+    fn reduce_db(&mut self) {
+        let mut learnts = mem::take(&mut self.learnts);
+        learnts.sort_by(|&a, &b| self.clauses[a].activity.partial_cmp(&self.clauses[b].activity).unwrap());
+        let half = learnts.len() / 2;
+        let mut kept = Vec::with_capacity(learnts.len());
+        for (i, &ci) in learnts.iter().enumerate() {
+            if i < half && self.clauses[ci].lits.len() > 2 && !self.locked(ci) {
+                let lits = mem::take(&mut self.clauses[ci].lits);
+                self.log_proof(true, &lits);
+                self.clauses[ci].deleted = true;
+            }
+            else {
+                kept.push(ci);
+            }
+        }
+        self.learnts = kept;
+    }
+
+    fn pick_branch_lit(&mut self) -> Option<L> {
+        while let Some(v) = self.heap.pop(&self.activity) {
+            if self.values[v].is_none() {
+                return Some(L(2 * v as u32 + !self.phase[v] as u32));
+            }
+        }
+        None
+    }
+
+    // runs cdcl until a model is found, unsatisfiability is proven, or the conflict budget is exhausted
+    fn search(&mut self, budget: u64) -> Option<bool> {
+        let mut conflicts = 0;
+        loop {
+            if let Some(confl) = self.propagate() {
+                self.stats.conflicts += 1;
+                conflicts += 1;
+                if self.decision_level() == 0 {
+                    return Some(false);
+                }
+                let (learnt, bt) = self.analyze(confl);
+                self.log_proof(false, &learnt);
+                self.backtrack(bt);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                }
+                else {
+                    let p = learnt[0];
+                    let ci = self.attach(learnt, true);
+                    self.bump_clause(ci);
+                    self.enqueue(p, Some(ci));
+                }
+                self.var_inc /= 0.95;
+                self.cla_inc /= 0.999;
+            }
+            else {
+                if conflicts >= budget {
+                    self.backtrack(0);
+                    return None;
+                }
+                if self.learnts.len() as f64 - self.trail.len() as f64 >= self.max_learnts {
+                    self.reduce_db();
+                }
+                match self.pick_branch_lit() {
+                    None => return Some(true),
+                    Some(p) => {
+                        self.stats.decisions += 1;
+                        self.trail_lim.push(self.trail.len());
+                        self.enqueue(p, None);
+                    }
+                }
+            }
+        }
+    }
+
+    // returns a model (value of each variable) if the formula is satisfiable, otherwise None
+    pub fn solve(&mut self) -> Option<Vec<bool>> {
+        if self.unsat { return None; }
+        self.backtrack(0);
+        if self.propagate().is_some() {
+            self.set_unsat();
+            return None;
+        }
+        self.max_learnts = (self.clauses.len() as f64 / 3.0).max(1000.0);
+        let mut restarts = 0;
+        loop {
+            match self.search(100 * luby(restarts)) {
+                Some(true) => return Some(self.values.iter().map(|x| x.unwrap()).collect()),
+                Some(false) => {
+                    self.set_unsat();
+                    return None;
+                }
+                None => {
+                    restarts += 1;
+                    self.stats.restarts += 1;
+                    self.max_learnts *= 1.1;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_luby() {
+    let seq: Vec<u64> = (0..15).map(luby).collect();
+    assert_eq!(seq, &[1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+}
+
+#[cfg(test)]
+fn satisfies(clauses: &[Vec<Lit>], model: &[bool]) -> bool {
+    clauses.iter().all(|c| c.iter().any(|&x| model[x.unsigned_abs() as usize - 1] == (x > 0)))
+}
+
+#[test]
+fn test_small_instances() {
+    let mut s = Solver::new(3);
+    assert!(s.solve().is_some());
+    assert!(s.add_clause(&[1, 2]));
+    assert!(s.add_clause(&[-1]));
+    assert_eq!(s.solve().map(|m| m[1]), Some(true));
+    assert!(!s.add_clause(&[-2]));
+    assert_eq!(s.solve(), None);
+
+    let mut s = Solver::new(1);
+    assert!(!s.add_clause(&[]));
+    assert_eq!(s.solve(), None);
+}
+
+#[test]
+fn test_pigeonhole() {
+    // n + 1 pigeons in n holes is unsat, n in n is sat
+    for n in 1..6 {
+        for &(pigeons, expected) in &[(n, true), (n + 1, false)] {
+            let var = |p: usize, h: usize| (p * n + h + 1) as Lit;
+            let mut cnf = Cnf::with_vars(pigeons * n);
+            for p in 0..pigeons {
+                cnf.add_clause((0..n).map(|h| var(p, h)));
+            }
+            for h in 0..n {
+                for a in 0..pigeons {
+                    for b in a + 1..pigeons {
+                        cnf.add_clause(vec![-var(a, h), -var(b, h)]);
+                    }
+                }
+            }
+            let res = Solver::from_cnf(&cnf).solve();
+            assert_eq!(res.is_some(), expected);
+            if let Some(model) = res {
+                assert!(satisfies(cnf.clauses(), &model));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn rup(clauses: &[Vec<Lit>], clause: &[Lit]) -> bool {
+    // assume the negation of the clause and look for a conflict by naive unit propagation
+    let mut assign: Vec<Lit> = clause.iter().map(|x| -x).collect();
+    loop {
+        let mut changed = false;
+        for c in clauses {
+            if c.iter().any(|x| assign.contains(x)) { continue; }
+            let free: Vec<Lit> = c.iter().copied().filter(|x| !assign.contains(&-x)).collect();
+            match free.len() {
+                0 => return true,
+                1 => {
+                    assign.push(free[0]);
+                    changed = true;
+                }
+                _ => (),
+            }
+        }
+        if !changed { return false; }
+    }
+}
+#[cfg(test)]
+fn check_drup(clauses: &[Vec<Lit>], proof: &[(bool, Vec<Lit>)]) -> bool {
+    let mut clauses = clauses.to_vec();
+    for (deleted, lits) in proof {
+        if *deleted {
+            let mut key = lits.clone();
+            key.sort();
+            let pos = clauses.iter().position(|c| {
+                let mut c = c.clone();
+                c.sort();
+                c == key
+            });
+            clauses.swap_remove(pos.unwrap());
+        }
+        else {
+            if !rup(&clauses, lits) { return false; }
+            if lits.is_empty() { return true; }
+            clauses.push(lits.clone());
+        }
+    }
+    false
+}
+
+#[test]
+fn test_drup_proof() {
+    for n in 2..6 {
+        let var = |p: usize, h: usize| (p * n + h + 1) as Lit;
+        let mut cnf = Cnf::with_vars((n + 1) * n);
+        for p in 0..n + 1 {
+            cnf.add_clause((0..n).map(|h| var(p, h)));
+        }
+        for h in 0..n {
+            for a in 0..n + 1 {
+                for b in a + 1..n + 1 {
+                    cnf.add_clause(vec![-var(a, h), -var(b, h)]);
+                }
+            }
+        }
+        let mut s = Solver::new(cnf.var_count());
+        s.enable_proof();
+        for c in cnf.clauses() {
+            s.add_clause(c);
+        }
+        assert_eq!(s.solve(), None);
+        assert!(check_drup(cnf.clauses(), s.proof.as_ref().unwrap()));
+
+        let mut buf = vec![];
+        s.write_proof(&mut buf).unwrap();
+        assert!(String::from_utf8(buf).unwrap().ends_with("\n0\n"));
+    }
+}
+
+#[test]
+fn test_random_3sat() {
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut rand = move |n: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % n
+    };
+    for _ in 0..200 {
+        let vars = 10;
+        let clauses: Vec<Vec<Lit>> = (0..43).map(|_| (0..3).map(|_| {
+            let v = rand(vars) as Lit + 1;
+            if rand(2) == 0 { v } else { -v }
+        }).collect()).collect();
+        let expected = (0..1u32 << vars).any(|bits| {
+            let model: Vec<bool> = (0..vars).map(|i| bits & (1 << i) != 0).collect();
+            satisfies(&clauses, &model)
+        });
+
+        let mut s = Solver::new(vars as usize);
+        for c in clauses.iter() {
+            s.add_clause(c);
+        }
+        match s.solve() {
+            Some(model) => {
+                assert!(expected);
+                assert!(satisfies(&clauses, &model));
+            }
+            None => assert!(!expected),
+        }
+    }
+}
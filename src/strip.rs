@@ -0,0 +1,215 @@
+// infinite strips (rows 0..width of one of the infinite graphs, unbounded along the columns) and cylinders (the same, with the rows wrapping around).
+//
+// a detector set on a strip is a sequence of columns, and every requirement on it involves points within 4 * reach columns of one another
+// (two points whose codes have to differ share a detector, so both their neighborhoods are within 2 * reach columns of it).
+// so the valid sets are exactly the walks through a graph whose states are runs of 4 * reach columns, with a step for every valid
+// run of one more column - a transfer matrix over column states - and the optimal density is its minimum mean weight cycle,
+// weighing each step by the detectors in the column it adds. the state graph is exponential in the width, so this is for narrow strips.
+
+use std::collections::HashMap;
+
+use num::BigRational;
+
+use crate::{codesets, Error};
+use crate::adj::AdjacentIterator;
+use crate::codesets::LOC;
+use crate::params::{Graph, Parameter};
+
+// enumerates every run of columns that meets the requirements on all the points it decides completely
+struct WindowSearch<'a, Codes> {
+    width: isize,
+    reach: isize,
+    window: usize,
+    neighbors: &'a [Vec<Vec<(isize, isize)>>], // for each column phase and row, the (row, column offset) of everything it's adjacent to
+    phase: usize,
+    cols: Vec<u32>, // a bit for each row that's a detector
+    codes: Codes,
+}
+impl<Codes> WindowSearch<'_, Codes>
+where Codes: codesets::Set<Item = (isize, isize)>
+{
+    fn is_detector(&self, p: (isize, isize)) -> bool {
+        self.cols[p.1 as usize] >> p.0 & 1 != 0
+    }
+    fn search<F: FnMut(&[u32])>(&mut self, f: &mut F) {
+        if self.cols.len() == self.window {
+            f(&self.cols);
+            return;
+        }
+        for col in 0..1u32 << self.width {
+            self.cols.push(col);
+
+            // the points reach columns back now have everything they're adjacent to decided
+            let j = self.cols.len() as isize - 1 - self.reach;
+            let mut pushed = 0;
+            let mut ok = true;
+            if j >= self.reach {
+                let neighbors = &self.neighbors[(self.phase + j as usize) % self.neighbors.len()];
+                for r in 0..self.width {
+                    let code = neighbors[r as usize].iter().map(|&(q, d)| (q, j + d)).filter(|&q| self.is_detector(q)).collect();
+                    if !self.codes.push(LOC::new((r, j), self.is_detector((r, j)), code)) {
+                        ok = false;
+                        break;
+                    }
+                    pushed += 1;
+                }
+            }
+            if ok {
+                self.search(f);
+            }
+            for _ in 0..pushed {
+                self.codes.pop();
+            }
+            self.cols.pop();
+        }
+    }
+}
+
+// the minimum mean weight of a cycle as (total weight, length), or none if there are no cycles.
+// this is karp's algorithm with every vertex as a start, which redoes the walks rather than keeping a table of them so it only needs linear space.
+fn min_mean_cycle(n: usize, edges: &[(usize, usize, i64)]) -> Option<(i64, i64)> {
+    // the least weight of a walk one edge longer than the ones in d, ending at each vertex
+    let step = |d: &[Option<i64>]| {
+        let mut next = vec![None; n];
+        for &(a, b, w) in edges {
+            if let Some(x) = d[a] {
+                if next[b].map_or(true, |y| x + w < y) {
+                    next[b] = Some(x + w);
+                }
+            }
+        }
+        next
+    };
+    let mut last = vec![Some(0); n];
+    for _ in 0..n {
+        last = step(&last);
+    }
+
+    // the answer is the least (over vertices) of the greatest (over shorter walks k) of (d_n - d_k) / (n - k)
+    let mut best: Vec<Option<(i64, i64)>> = vec![None; n];
+    let mut walks = vec![Some(0); n];
+    for k in 0..n {
+        for v in 0..n {
+            if let (Some(a), Some(b)) = (last[v], walks[v]) {
+                let mean = (a - b, (n - k) as i64);
+                if best[v].map_or(true, |x| mean.0 * x.1 > x.0 * mean.1) {
+                    best[v] = Some(mean);
+                }
+            }
+        }
+        walks = step(&walks);
+    }
+    best.into_iter().flatten().min_by(|a, b| (a.0 * b.1).cmp(&(b.0 * a.1)))
+}
+
+// the most column states (as a power of 2) a strip can have, which also keeps the rows of a column well inside a u32
+const MAX_STATE_BITS: usize = 13;
+
+fn calc_min_density<Codes, Adj>(adj: &Adj, width: isize, cylinder: bool) -> Result<Option<BigRational>, Error>
+where Codes: codesets::Set<Item = (isize, isize)>, Adj: AdjacentIterator<Point = (isize, isize)>
+{
+    // classes are the points up to translation, so some shift of at most that many columns lands every point back on its own class
+    let classes = adj.classes();
+    let repeats = |dr: isize, dc: isize| {
+        (-12..12).all(|r| (-12..12).all(|c| adj.class((r + dr, c + dc)) == adj.class((r, c)))) && classes.iter().all(|&(r, c)| adj.class((r + dr, c + dc)) == adj.class((r, c)))
+    };
+    let period = match (1..=classes.len() as isize).find(|&p| repeats(0, p)) {
+        Some(p) => p,
+        None => return Err(Error::Usage("the graph doesn't repeat along its columns".to_owned())),
+    };
+    if cylinder && !repeats(width, 0) {
+        return Err(Error::Usage(format!("a width {} cylinder doesn't line up with the rows of the graph", width)));
+    }
+
+    // the states are runs of window - 1 columns at each phase of the period, and the search is quadratic in how many there can be
+    let reach = adj.reach();
+    let window = 4 * reach as usize + 1;
+    let bits = width as usize * (window - 1);
+    if bits > MAX_STATE_BITS || (period as usize) << bits > 1 << MAX_STATE_BITS {
+        return Err(Error::Usage(format!("a width {} strip would have up to {} x 2^{} states, more than the 2^{} this can handle", width, period, bits, MAX_STATE_BITS)));
+    }
+
+    // what each point is adjacent to once the rows outside the strip are dropped (or wrapped around)
+    let mut neighbors = vec![vec![vec![]; width as usize]; period as usize];
+    for c in 0..period {
+        for r in 0..width {
+            let mut v: Vec<(isize, isize)> = adj.at((r, c)).filter(|p| cylinder || (0..width).contains(&p.0)).map(|p| (p.0.rem_euclid(width), p.1 - c)).collect();
+            v.sort_unstable();
+            // on a cylinder that's too narrow, different neighbors (or a neighbor and the point itself) would become the same point
+            if cylinder {
+                let mut closed: Vec<_> = adj.closed().at((r, c)).map(|p| (p.0.rem_euclid(width), p.1)).collect();
+                closed.sort_unstable();
+                closed.dedup();
+                if closed.len() != adj.closed().at((r, c)).count() {
+                    return Err(Error::Usage(format!("a width {} cylinder is too narrow to wrap the graph around", width)));
+                }
+            }
+            neighbors[c as usize][r as usize] = v;
+        }
+    }
+
+    // every valid run of window columns is a step from the state of its first window - 1 columns to the state of its last window - 1
+    let mut states: HashMap<(usize, Vec<u32>), usize> = Default::default();
+    let mut edges = vec![];
+    for phase in 0..period as usize {
+        let mut search = WindowSearch::<Codes> { width, reach, window, neighbors: &neighbors, phase, cols: Vec::with_capacity(window), codes: Default::default() };
+        search.search(&mut |cols: &[u32]| {
+            let count = states.len();
+            let from = *states.entry((phase, cols[..window - 1].to_vec())).or_insert(count);
+            let count = states.len();
+            let to = *states.entry(((phase + 1) % period as usize, cols[1..].to_vec())).or_insert(count);
+            edges.push((from, to, cols[window - 1].count_ones() as i64));
+        });
+    }
+
+    Ok(min_mean_cycle(states.len(), &edges).map(|(weight, len)| BigRational::new(weight.into(), (len * width as i64).into())))
+}
+// the exact minimum density of a set on a strip (or cylinder) of the given width, or none if no set exists
+pub fn min_density(param: Parameter, graph: &Graph, width: usize, cylinder: bool) -> Result<Option<BigRational>, Error> {
+    if width == 0 {
+        return Err(Error::Usage("strips have to be at least 1 row wide".to_owned()));
+    }
+    let width = width as isize;
+    macro_rules! calc {
+        ($set:ident, $adj:expr) => {
+            calc_min_density::<codesets::$set<(isize, isize)>, _>(&$adj, width, cylinder)
+        }
+    }
+    graph_family!(graph, |open, closed| param_family!(param, calc, open, closed))
+}
+#[test]
+fn test_min_mean_cycle() {
+    assert_eq!(min_mean_cycle(3, &[(0, 1, 1), (1, 2, 1)]), None);
+    // a 2-cycle of mean 3/2 and a 3-cycle of mean 4/3 sharing vertex 0
+    let (w, l) = min_mean_cycle(4, &[(0, 1, 1), (1, 0, 2), (0, 2, 0), (2, 3, 1), (3, 0, 3)]).unwrap();
+    assert_eq!(w * 3, l * 4);
+}
+#[test]
+fn test_strips() {
+    let density = |param, graph, width, cylinder| min_density(param, &graph, width, cylinder).unwrap().map(|x| x.to_string());
+    let some = |s: &str| Some(s.to_owned());
+
+    // the infinite path, which has well known optimal densities
+    assert_eq!(density(Parameter::DOM, Graph::SQ, 1, false), some("1/3"));
+    assert_eq!(density(Parameter::LD, Graph::SQ, 1, false), some("2/5"));
+    assert_eq!(density(Parameter::IC, Graph::SQ, 1, false), some("1/2"));
+    assert_eq!(density(Parameter::OLD, Graph::SQ, 1, false), some("2/3"));
+    assert_eq!(density(Parameter::EDOM, Graph::K, 1, false), some("1/3"));
+    assert_eq!(density(Parameter::ERRIC, Graph::SQ, 1, false), None);
+
+    // a detector on a width-3 king cylinder dominates three whole columns, and on a width-2 king strip it dominates six points
+    assert_eq!(density(Parameter::DOM, Graph::K, 3, true), some("1/9"));
+    assert_eq!(density(Parameter::DOM, Graph::K, 2, false), some("1/6"));
+    assert_eq!(density(Parameter::EDOM, Graph::SQ, 3, true), None);
+    // the ladder
+    assert_eq!(density(Parameter::DOM, Graph::SQ, 2, false), some("1/4"));
+    assert_eq!(density(Parameter::IC, Graph::SQ, 2, false), some("3/7"));
+
+    // the hexagonal grid alternates along its columns, and a narrow cylinder would fold neighbors onto each other
+    assert!(density(Parameter::DOM, Graph::HEX, 2, false).is_some());
+    assert!(matches!(min_density(Parameter::DOM, &Graph::SQ, 2, true), Err(Error::Usage(_))));
+    assert!(matches!(min_density(Parameter::DOM, &Graph::SQ, 0, false), Err(Error::Usage(_))));
+    // too many column states (4 columns of 4 rows is already 2^16 of them)
+    assert!(matches!(min_density(Parameter::DOM, &Graph::SQ, 4, false), Err(Error::Usage(_))));
+    assert!(matches!(min_density(Parameter::DOM, &Graph::K, 5, true), Err(Error::Usage(_))));
+}
@@ -0,0 +1,234 @@
+// the survey: the best bounds we can find for each set type on each infinite graph, as a markdown or latex table.
+// lower bounds come from auto-theo, and upper bounds from rectangular tilings (rect) and then from every other shape up to the same size
+// (entropy-rect), keeping whichever is better. each search is run as its own process (killed when out of time),
+// and every upper bound links to a witness file with the tiling that achieves it.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use num::{BigInt, BigRational, ToPrimitive};
+use num::traits::Zero;
+
+use old_set::Error;
+use old_set::params::{Graph, Parameter};
+use old_set::tessellation::Geometry;
+
+use crate::batch;
+use crate::output::Json;
+
+pub struct SurveyOptions {
+    pub time_limit: Duration, // for each of the lower and upper bound searches on each combination
+    pub max_area: usize, // the largest rectangle to try for upper bounds
+    pub strategy: String, // the auto-theo strategy
+}
+
+pub struct Witness {
+    pub density: BigRational,
+    pub rows: usize,
+    pub cols: usize,
+    pub entropy_size: Option<usize>, // the size of the shapes entropy-rect looked at inside the rectangle, or none if it's from rect
+    pub thresh: String, // what the search was given to find it
+    pub result: Json, // what the search printed
+}
+impl Witness {
+    // the witness file contents: how to get the tiling again, followed by the tiling itself
+    pub fn describe(&self, param: Parameter, graph: &Graph) -> String {
+        let point = |j: &Json| -> Option<(isize, isize)> {
+            match j.as_array()? {
+                [a, b] => Some((a.as_str()?.parse().ok()?, b.as_str()?.parse().ok()?)),
+                _ => None,
+            }
+        };
+        let points = |key: &str| -> Vec<(isize, isize)> {
+            self.result.get(key).and_then(Json::as_array).unwrap_or(&[]).iter().filter_map(point).collect()
+        };
+        let shape: BTreeSet<_> = points("shape").into_iter().collect();
+        let basis = points("basis");
+
+        let (mut s, command) = match self.entropy_size {
+            None => (format!("{:?} on {:?}: density {} from a {}x{} rectangle\n", param, graph, self.density, self.rows, self.cols), format!("rect {} {}", self.rows, self.cols)),
+            Some(size) => (format!("{:?} on {:?}: density {} from a {} vertex shape in a {}x{} rectangle\n", param, graph, self.density, size, self.rows, self.cols), format!("entropy-rect {} {} {}", self.rows, self.cols, size)),
+        };
+        writeln!(s, "reproduce with: old-set {} --param {:?} --graph {:?} --thresh {}\n", command, param, graph, self.thresh).unwrap();
+        if !shape.is_empty() {
+            write!(s, "{}", Geometry::for_printing(&shape, points("detectors").into_iter())).unwrap();
+        }
+        if let [a, b] = basis.as_slice() {
+            writeln!(s, "basis: {:?} {:?}", a, b).unwrap();
+        }
+        s
+    }
+}
+#[test]
+fn test_witness_describe() {
+    let witness = |entropy_size| Witness { density: BigRational::new(1.into(), 3.into()), rows: 2, cols: 4, entropy_size, thresh: "0.5".to_owned(), result: Json::object(vec![("found", true.into())]) };
+    let first = |w: Witness| w.describe(Parameter::DOM, &Graph::SQ).lines().take(2).map(str::to_owned).collect::<Vec<_>>();
+    assert_eq!(first(witness(None)), vec!["DOM on SQ: density 1/3 from a 2x4 rectangle", "reproduce with: old-set rect 2 4 --param DOM --graph SQ --thresh 0.5"]);
+    assert_eq!(first(witness(Some(6))), vec!["DOM on SQ: density 1/3 from a 6 vertex shape in a 2x4 rectangle", "reproduce with: old-set entropy-rect 2 4 6 --param DOM --graph SQ --thresh 0.5"]);
+}
+pub struct Bounds {
+    pub lower: Option<BigRational>,
+    pub upper: Option<Witness>,
+}
+
+// the last thing auto-theo reported as proven before it ran out of time (if it got anywhere at all).
+// it walks the stern-brocot tree so the bounds in the table come out as small fractions instead of huge binary ones,
+// and sticks to one thread since the survey already runs a process per core.
+pub fn lower_bound(exe: &Path, param: Parameter, graph: &Graph, opts: &SurveyOptions) -> Result<Option<BigRational>, Error> {
+    let args = vec!["auto-theo".to_owned(), "--param".to_owned(), format!("{:?}", param), "--graph".to_owned(), format!("{:?}", graph), "--strategy".to_owned(), opts.strategy.clone(), "--search".to_owned(), "stern-brocot".to_owned(), "--threads".to_owned(), "1".to_owned()];
+    let res = batch::run_process(exe, &args, Some(opts.time_limit))?;
+    // that's the final bound if it stopped in time, and otherwise the low end of the last search space it reported
+    let last = res.stdout.lines().rev().find_map(Json::parse);
+    let low = last.and_then(|j| j.get("lower_bound").or_else(|| j.get("low"))?.as_str()?.parse::<BigRational>().ok());
+    Ok(low.filter(|low| !low.is_zero()))
+}
+
+// rectangles with both sides at least 2, smallest first
+fn rectangles(max_area: usize) -> Vec<(usize, usize)> {
+    (4..=max_area).flat_map(|area| (2..=area / 2).filter(move |rows| area % rows == 0).map(move |rows| (rows, area / rows))).collect()
+}
+#[test]
+fn test_rectangles() {
+    assert_eq!(rectangles(9), vec![(2, 2), (2, 3), (3, 2), (2, 4), (4, 2), (3, 3)]);
+}
+
+// lowers the best density so far as far as the given search (rect or entropy-rect, with the tile size n) can take it.
+// returns false if it ran out of time.
+fn improve(exe: &Path, param: Parameter, graph: &Graph, search: &[String], (rows, cols, entropy_size): (usize, usize, Option<usize>), deadline: Instant, best: &mut Option<Witness>) -> Result<bool, Error> {
+    let exact = matches!(param, Parameter::EDOM | Parameter::EODOM); // these take a detector count instead of a density
+    let n = entropy_size.unwrap_or(rows * cols);
+    // only look for strictly better densities than we already have
+    let mut k = match best {
+        None => n,
+        Some(w) => {
+            let (numer, denom) = (w.density.numer(), w.density.denom());
+            let ceil: BigInt = (BigInt::from(n) * numer + denom - 1) / denom;
+            ceil.to_usize().unwrap() - 1
+        }
+    };
+    while k > 0 {
+        let remaining = match deadline.checked_duration_since(Instant::now()) {
+            Some(t) if !t.is_zero() => t,
+            _ => return Ok(false),
+        };
+        // the search uses floor(n * thresh) detectors, so aim for the middle to stay clear of rounding
+        let thresh = if exact { k.to_string() } else { ((k as f64 + 0.5) / n as f64).min(1.0).to_string() };
+        let mut args = search.to_vec();
+        args.extend(vec!["--param".to_owned(), format!("{:?}", param), "--graph".to_owned(), format!("{:?}", graph), "--thresh".to_owned(), thresh.clone(), "--threads".to_owned(), "1".to_owned()]);
+        let res = batch::run_process(exe, &args, Some(remaining))?;
+        if res.timed_out {
+            return Ok(false);
+        }
+        let result = match res.stdout.lines().rev().find_map(Json::parse) {
+            Some(j) if j.get("found") == Some(&Json::Bool(true)) => j,
+            _ => break,
+        };
+        *best = Some(Witness { density: BigRational::new(k.into(), n.into()), rows, cols, entropy_size, thresh, result });
+        k -= 1;
+    }
+    Ok(true)
+}
+
+// the lowest density tiling over every rectangle up to the max area, and then over every other shape up to that size (until running out of time).
+// the rectangles get the first half of the time, and the other shapes get whatever is left.
+pub fn upper_bound(exe: &Path, param: Parameter, graph: &Graph, opts: &SurveyOptions) -> Result<Option<Witness>, Error> {
+    let start = Instant::now();
+    let deadline = start + opts.time_limit;
+    let mut best: Option<Witness> = None;
+    for (rows, cols) in rectangles(opts.max_area) {
+        let search = vec!["rect".to_owned(), rows.to_string(), cols.to_string()];
+        if !improve(exe, param, graph, &search, (rows, cols, None), start + opts.time_limit / 2, &mut best)? {
+            break;
+        }
+    }
+    // shapes of every size, each inside the squarest rectangle with room to spare (so it holds more than just the n vertex rectangles)
+    for n in 4..=opts.max_area {
+        let rows = (2..).take_while(|r| r * r <= n).last().unwrap_or(2);
+        let cols = n / rows + 1;
+        let search = vec!["entropy-rect".to_owned(), rows.to_string(), cols.to_string(), n.to_string()];
+        if !improve(exe, param, graph, &search, (rows, cols, Some(n)), deadline, &mut best)? {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    Markdown,
+    Latex,
+}
+// the lower bound and the upper bound with a link to its witness
+pub type Cell = (Option<BigRational>, Option<(BigRational, String)>);
+// rows are set types and columns are graphs, in the order given - each cell is "lower - upper", bold if they match
+pub fn table(params: &[Parameter], graphs: &[Graph], cell: &dyn Fn(Parameter, &Graph) -> Cell, style: TableStyle) -> String {
+    let mut s = String::new();
+    match style {
+        TableStyle::Markdown => {
+            writeln!(s, "| |{}", graphs.iter().map(|g| format!(" {:?} |", g)).collect::<String>()).unwrap();
+            writeln!(s, "|---|{}", graphs.iter().map(|_| "---|").collect::<String>()).unwrap();
+        }
+        TableStyle::Latex => {
+            writeln!(s, "\\begin{{tabular}}{{l|{}}}", "c".repeat(graphs.len())).unwrap();
+            writeln!(s, "{} \\\\\n\\hline", graphs.iter().map(|g| format!(" & {:?}", g)).collect::<String>()).unwrap();
+        }
+    }
+    for &param in params {
+        let cells: Vec<String> = graphs.iter().map(|graph| {
+            let (lower, upper) = cell(param, graph);
+            let tight = match (&lower, &upper) {
+                (Some(l), Some((u, _))) => l == u,
+                _ => false,
+            };
+            let lower = match (style, lower) {
+                (_, None) => "?".to_owned(),
+                (TableStyle::Markdown, Some(l)) => l.to_string(),
+                (TableStyle::Latex, Some(l)) => format!("${}$", l),
+            };
+            match (style, upper) {
+                (TableStyle::Markdown, None) => format!("{} - ?", lower),
+                (TableStyle::Markdown, Some((u, link))) if tight => format!("[**{}**]({})", u, link),
+                (TableStyle::Markdown, Some((u, link))) => format!("{} - [{}]({})", lower, u, link),
+                (TableStyle::Latex, None) => format!("{} -- ?", lower),
+                (TableStyle::Latex, Some((u, link))) if tight => format!("\\href{{{}}}{{$\\mathbf{{{}}}$}}", link, u),
+                (TableStyle::Latex, Some((u, link))) => format!("{} -- \\href{{{}}}{{${}$}}", lower, link, u),
+            }
+        }).collect();
+        match style {
+            TableStyle::Markdown => writeln!(s, "| {:?} |{}", param, cells.iter().map(|c| format!(" {} |", c)).collect::<String>()).unwrap(),
+            TableStyle::Latex => writeln!(s, "{:?}{} \\\\", param, cells.iter().map(|c| format!(" & {}", c)).collect::<String>()).unwrap(),
+        }
+    }
+    if style == TableStyle::Latex {
+        writeln!(s, "\\end{{tabular}}").unwrap();
+    }
+    s
+}
+#[test]
+fn test_table() {
+    let cell = |param: Parameter, graph: &Graph| -> Cell {
+        match (param, graph) {
+            (Parameter::LD, Graph::HEX) => (Some(BigRational::new(1.into(), 3.into())), Some((BigRational::new(1.into(), 3.into()), "w/ld-hex.txt".to_owned()))),
+            (Parameter::LD, _) => (Some(BigRational::new(1.into(), 4.into())), Some((BigRational::new(3.into(), 8.into()), "w/ld-sq.txt".to_owned()))),
+            _ => (None, None),
+        }
+    };
+    let params = [Parameter::LD, Parameter::EDOM];
+    let graphs = [Graph::HEX, Graph::SQ];
+    assert_eq!(table(&params, &graphs, &cell, TableStyle::Markdown), "\
+| | HEX | SQ |
+|---|---|---|
+| LD | [**1/3**](w/ld-hex.txt) | 1/4 - [3/8](w/ld-sq.txt) |
+| EDOM | ? - ? | ? - ? |
+");
+    assert_eq!(table(&params, &graphs, &cell, TableStyle::Latex), "\
+\\begin{tabular}{l|cc}
+ & HEX & SQ \\\\
+\\hline
+LD & \\href{w/ld-hex.txt}{$\\mathbf{1/3}$} & $1/4$ -- \\href{w/ld-sq.txt}{$3/8$} \\\\
+EDOM & ? -- ? & ? -- ? \\\\
+\\end{tabular}
+");
+}
@@ -0,0 +1,280 @@
+use std::collections::{HashSet, VecDeque};
+
+use num::BigUint;
+use num::traits::One;
+
+// a permutation of 0..n (maps i to perm[i])
+pub type Perm = Vec<usize>;
+
+// a generating set of the automorphism group of a graph, along with the order of the group
+pub struct Automorphisms {
+    pub generators: Vec<Perm>,
+    pub order: BigUint,
+}
+
+// splits cells until every vertex in a cell sees the same number of neighbors in each cell (an equitable partition).
+// colors are dense (0..k) and their order only depends on the structure of the graph, so refinement commutes with isomorphism.
+fn refine(adj: &[Vec<usize>], colors: &mut [usize]) {
+    let mut count = cell_count(colors);
+    loop {
+        let keys: Vec<(usize, Vec<usize>)> = (0..adj.len()).map(|v| {
+            let mut neighbor_colors: Vec<usize> = adj[v].iter().map(|&u| colors[u]).collect();
+            neighbor_colors.sort_unstable();
+            (colors[v], neighbor_colors)
+        }).collect();
+        let mut distinct: Vec<&(usize, Vec<usize>)> = keys.iter().collect();
+        distinct.sort();
+        distinct.dedup();
+        if distinct.len() == count { return; } // no cell was split, so nothing can change anymore
+        count = distinct.len();
+        for (c, key) in colors.iter_mut().zip(keys.iter()) {
+            *c = distinct.binary_search(&key).unwrap();
+        }
+    }
+}
+// gives v its own cell (placed just before the rest of its old cell) and refines the result
+fn individualize(adj: &[Vec<usize>], colors: &[usize], v: usize) -> Vec<usize> {
+    let mut res: Vec<usize> = colors.iter().enumerate().map(|(u, &c)| if c > colors[v] || (c == colors[v] && u != v) { c + 1 } else { c }).collect();
+    refine(adj, &mut res);
+    res
+}
+// the first non-singleton cell (in color order), or None if the partition is discrete
+fn target_cell(colors: &[usize]) -> Option<Vec<usize>> {
+    let mut sizes = vec![0; colors.len()];
+    for &c in colors {
+        sizes[c] += 1;
+    }
+    let target = sizes.iter().position(|&s| s > 1)?;
+    Some((0..colors.len()).filter(|&u| colors[u] == target).collect())
+}
+
+pub fn is_automorphism(adj: &[Vec<usize>], perm: &[usize]) -> bool {
+    // adjacency lists are sorted by the caller, so we can binary search
+    (0..adj.len()).all(|v| adj[v].len() == adj[perm[v]].len() && adj[v].iter().all(|&u| adj[perm[v]].binary_search(&perm[u]).is_ok()))
+}
+
+// the vertices reachable from v under the given permutations
+pub fn orbit(perms: &[Perm], v: usize) -> Vec<usize> {
+    let mut res = vec![v];
+    let mut pos = 0;
+    while pos < res.len() {
+        let u = res[pos];
+        pos += 1;
+        for p in perms {
+            if !res.contains(&p[u]) {
+                res.push(p[u]);
+            }
+        }
+    }
+    res
+}
+
+fn cell_count(colors: &[usize]) -> usize {
+    colors.iter().copied().max().map(|x| x + 1).unwrap_or(0)
+}
+// searches the subtree rooted at the given partition for a leaf equivalent to the reference leaf (the last of refs).
+// refs holds the partitions along the reference path at the same depths, which lets us skip subtrees that can't match.
+fn find_automorphism(adj: &[Vec<usize>], colors: Vec<usize>, refs: &[&[usize]]) -> Option<Perm> {
+    if cell_count(&colors) != cell_count(refs[0]) { return None; }
+    match target_cell(&colors) {
+        None => {
+            // both partitions are discrete, so matching up colors gives the only candidate permutation
+            let mut by_color = vec![0; colors.len()];
+            for (u, &c) in colors.iter().enumerate() {
+                by_color[c] = u;
+            }
+            let perm: Perm = refs[0].iter().map(|&c| by_color[c]).collect();
+            if is_automorphism(adj, &perm) { Some(perm) } else { None }
+        }
+        Some(cell) => cell.into_iter().find_map(|w| find_automorphism(adj, individualize(adj, &colors, w), &refs[1..])),
+    }
+}
+
+// computes generators for the automorphism group of a graph given as (sorted) adjacency lists.
+// this is the usual individualization-refinement search: we walk one path down to a discrete partition,
+// then for each level look for automorphisms mapping the chosen vertex to every other vertex of its cell
+// that isn't already known to be in the same orbit (which also gives the group order by orbit-stabilizer).
+pub fn automorphisms(adj: &[Vec<usize>]) -> Automorphisms {
+    let mut colors = vec![0; adj.len()];
+    refine(adj, &mut colors);
+
+    let mut path = vec![];
+    while let Some(cell) = target_cell(&colors) {
+        let next = individualize(adj, &colors, cell[0]);
+        path.push((colors, cell));
+        colors = next;
+    }
+    let mut refs: Vec<&[usize]> = path.iter().map(|(c, _)| c.as_slice()).collect();
+    refs.push(&colors);
+
+    let mut generators = vec![];
+    let mut order = BigUint::one();
+    for (depth, (colors, cell)) in path.iter().enumerate().rev() {
+        let v = cell[0];
+        for &w in &cell[1..] {
+            if orbit(&generators, v).contains(&w) { continue; }
+            if let Some(perm) = find_automorphism(adj, individualize(adj, colors, w), &refs[depth + 1..]) {
+                generators.push(perm);
+            }
+        }
+        order *= BigUint::from(orbit(&generators, v).len());
+    }
+    Automorphisms { generators, order }
+}
+#[cfg(test)]
+fn cycle(n: usize) -> Vec<Vec<usize>> {
+    (0..n).map(|i| {
+        let mut adj = vec![(i + 1) % n, (i + n - 1) % n];
+        adj.sort();
+        adj
+    }).collect()
+}
+#[test]
+fn test_automorphisms() {
+    for n in 3..10 {
+        let aut = automorphisms(&cycle(n));
+        assert_eq!(aut.order, BigUint::from(2 * n));
+        assert!(aut.generators.iter().all(|p| is_automorphism(&cycle(n), p)));
+    }
+    for n in 1..7 {
+        let complete: Vec<Vec<usize>> = (0..n).map(|i| (0..n).filter(|&j| j != i).collect()).collect();
+        assert_eq!(automorphisms(&complete).order, BigUint::from((1..=n).product::<usize>()));
+    }
+
+    // a path has only the reflection, and the petersen graph has 120 automorphisms
+    let path: Vec<Vec<usize>> = (0..5).map(|i: usize| (i.saturating_sub(1)..(i + 2).min(5)).filter(|&j| j != i).collect()).collect();
+    assert_eq!(automorphisms(&path).order, BigUint::from(2u32));
+    let mut petersen: Vec<Vec<usize>> = vec![vec![]; 10];
+    for i in 0..5 {
+        for &(a, b) in &[(i, (i + 1) % 5), (i, i + 5), (i + 5, (i + 2) % 5 + 5)] {
+            petersen[a].push(b);
+            petersen[b].push(a);
+        }
+    }
+    for adj in petersen.iter_mut() {
+        adj.sort();
+    }
+    assert_eq!(automorphisms(&petersen).order, BigUint::from(120u32));
+}
+
+// the given permutations along with their inverses (which are just as valid for symmetry breaking)
+pub fn with_inverses(perms: &[Perm]) -> Vec<Perm> {
+    let mut res = perms.to_vec();
+    for p in perms {
+        let mut inv = vec![0; p.len()];
+        for (i, &x) in p.iter().enumerate() {
+            inv[x] = i;
+        }
+        if !res.contains(&inv) {
+            res.push(inv);
+        }
+    }
+    res
+}
+
+// every element of the group generated by the given permutations (besides the identity), or None if there are more than limit.
+// using the whole group makes symmetry breaking exact, so we prefer it for small groups.
+pub fn group_elements(gens: &[Perm], limit: usize) -> Option<Vec<Perm>> {
+    let n = match gens.first() {
+        Some(p) => p.len(),
+        None => return Some(vec![]),
+    };
+    let identity: Perm = (0..n).collect();
+    let mut seen: HashSet<Perm> = Default::default();
+    let mut res = vec![identity.clone()];
+    seen.insert(identity);
+    let mut pos = 0;
+    while pos < res.len() {
+        for g in gens {
+            let p: Perm = res[pos].iter().map(|&x| g[x]).collect();
+            if seen.insert(p.clone()) {
+                if res.len() >= limit { return None; }
+                res.push(p);
+            }
+        }
+        pos += 1;
+    }
+    res.remove(0);
+    Some(res)
+}
+#[test]
+fn test_group_elements() {
+    assert_eq!(group_elements(&automorphisms(&cycle(7)).generators, 100).unwrap().len(), 13);
+    assert!(group_elements(&automorphisms(&cycle(7)).generators, 10).is_none());
+    assert_eq!(group_elements(&[], 10), Some(vec![]));
+}
+
+// the group elements to use for symmetry breaking: the whole group if it's small, otherwise the generators and their inverses
+pub fn breaking_perms(gens: &[Perm]) -> Vec<Perm> {
+    group_elements(gens, 1024).unwrap_or_else(|| with_inverses(gens))
+}
+
+// checks if a partial assignment (known for vertices below decided) could still be completed to the lex-leader of its orbit
+// (the largest characteristic vector, with vertex 0 most significant) as far as the given group elements can tell.
+// this never rejects a true lex-leader, so keeping only the candidates still leaves one representative of every orbit.
+pub fn may_be_lex_leader<F: Fn(usize) -> bool>(perms: &[Perm], decided: usize, value: F) -> bool {
+    perms.iter().all(|p| {
+        // compare against the image (value(p[0]), value(p[1]), ...) until we find a difference or run out of information
+        for (u, &image) in p[..decided].iter().enumerate() {
+            if image >= decided { return true; }
+            match (value(u), value(image)) {
+                (false, true) => return false,
+                (true, false) => return true,
+                _ => (),
+            }
+        }
+        true
+    })
+}
+#[test]
+fn test_may_be_lex_leader() {
+    let perms = with_inverses(&automorphisms(&cycle(6)).generators);
+    let leaders: Vec<u32> = (0..64u32).filter(|set| may_be_lex_leader(&perms, 6, |v| set & (1 << v) != 0)).collect();
+    // every orbit keeps a representative
+    for set in 0..64u32 {
+        let members: Vec<usize> = (0..6).filter(|v| set & (1 << v) != 0).collect();
+        let orbit = set_orbit(&perms, &members);
+        assert!(orbit.iter().any(|o| leaders.contains(&o.iter().map(|v| 1 << v).sum())));
+    }
+    // partial assignments can be rejected early: 0 out, 1 in loses to a rotation that maps 1 to 0
+    assert!(!may_be_lex_leader(&perms, 2, |v| v == 1));
+    assert!(may_be_lex_leader(&perms, 2, |v| v == 0));
+}
+
+// statistics on how much of a subset search was skipped by symmetry breaking
+#[derive(Default)]
+pub struct Pruning {
+    pub branches: usize,
+    pub sets: BigUint,
+}
+
+// all images of a vertex set under the group generated by the given permutations (as sorted vectors, the set itself included)
+pub fn set_orbit(perms: &[Perm], set: &[usize]) -> Vec<Vec<usize>> {
+    let mut start = set.to_vec();
+    start.sort_unstable();
+    let mut seen: HashSet<Vec<usize>> = Default::default();
+    let mut queue: VecDeque<Vec<usize>> = Default::default();
+    seen.insert(start.clone());
+    queue.push_back(start);
+    let mut res = vec![];
+    while let Some(s) = queue.pop_front() {
+        for p in perms {
+            let mut image: Vec<usize> = s.iter().map(|&x| p[x]).collect();
+            image.sort_unstable();
+            if seen.insert(image.clone()) {
+                queue.push_back(image);
+            }
+        }
+        res.push(s);
+    }
+    res
+}
+#[test]
+fn test_set_orbit() {
+    let aut = automorphisms(&cycle(6));
+    assert_eq!(set_orbit(&aut.generators, &[0]).len(), 6);
+    assert_eq!(set_orbit(&aut.generators, &[0, 3]).len(), 3);
+    assert_eq!(set_orbit(&aut.generators, &[0, 1]).len(), 6);
+    assert_eq!(set_orbit(&aut.generators, &[0, 2, 4]).len(), 2);
+    assert_eq!(set_orbit(&[], &[2, 1]), vec![vec![1, 2]]);
+}
@@ -0,0 +1,1204 @@
+// periodic tilings of the infinite graphs: a finite tile (the geometry) is repeated over a lattice and searched for a detector set that works everywhere.
+// the tiles are sets of points in any dimension - the planar graphs use (row, col) tiles and the cubic lattices (see cubic.rs) use (row, col, layer) ones.
+
+use std::collections::{BTreeSet, BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::iter;
+use std::io::{BufRead, BufReader};
+use std::fs::File;
+use std::mem;
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use itertools::Itertools;
+
+use crate::{adj, checkpoint, codesets, cnf, periodic, sat, symmetry, util, Engine, Error, SearchCommand};
+use crate::adj::AdjacentIterator;
+use crate::checkpoint::CheckpointError;
+use crate::codesets::LOC;
+use crate::params::{self, Parameter, Graph, ValueError};
+use crate::point::{Point, Point2};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Goal {
+    MeetOrBeat(f64),
+    Exactly(usize),
+}
+impl Goal {
+    pub fn get_value(&self, total_size: usize) -> usize {
+        match self {
+            Goal::MeetOrBeat(v) => (total_size as f64 * v).floor() as usize,
+            Goal::Exactly(v) => *v,
+        }
+    }
+}
+
+trait Solver {
+    type Point: Point;
+    fn is_old<Adj>(&mut self, adj: &Adj) -> bool where Adj: adj::AdjacentIterator<Point = Self::Point>;
+    fn try_satisfy<Adj>(&mut self, adj: &Adj, goal: Goal) -> Option<usize> where Adj: adj::AdjacentIterator<Point = Self::Point>;
+}
+
+pub trait Tessellation: fmt::Display {
+    type Point: Point;
+    fn size(&self) -> usize;
+    fn try_satisfy<Codes, Adj>(&mut self, adj: &Adj, goal: Goal) -> Option<usize>
+    where Codes: codesets::Set<Item = Self::Point>, Adj: adj::AdjacentIterator<Point = Self::Point>;
+    fn try_satisfy_sat<Codes, Adj>(&mut self, adj: &Adj, goal: Goal) -> Option<usize>
+    where Codes: cnf::Encode<Item = Self::Point>, Adj: adj::AdjacentIterator<Point = Self::Point>;
+    // like try_satisfy, but with the search split across threads (stopping them all as soon as one finds a solution)
+    fn try_satisfy_parallel<Codes, Adj>(&mut self, adj: &Adj, goal: Goal, threads: usize) -> Option<usize>
+    where Codes: codesets::Set<Item = Self::Point>, Adj: adj::AdjacentIterator<Point = Self::Point>;
+    // calls f on every tiling meeting the goal (along with its basis and, if deduplicating by symmetry, its number of distinct images) until it asks to halt
+    fn for_each_solution<Codes, Adj, F>(&mut self, adj: &Adj, goal: Goal, engine: Engine, unique: bool, f: F) -> SearchCommand
+    where Codes: cnf::Encode<Item = Self::Point>, Adj: adj::AdjacentIterator<Point = Self::Point>, F: FnMut(&Geometry<Self::Point>, &[Self::Point], usize) -> SearchCommand;
+    // prepares the tessellation for graphs whose neighbors can be up to reach away in any coordinate (see adj::AdjacentIterator::reach)
+    fn fit_reach(&mut self, reach: isize) -> Result<(), Error>;
+}
+
+
+#[derive(Clone)]
+pub struct Geometry<P: Point = Point2> {
+    shape: BTreeSet<P>,
+    detectors: BTreeSet<P>,
+    extent: P, // the size of the bounding box in each coordinate
+}
+impl Geometry {
+    pub fn with_shape(path: &str) -> Result<Self, Error> {
+        let f = File::open(path).map_err(|e| Error::io("open geometry file", path, e))?;
+        Self::read_shape(path, BufReader::new(f))
+    }
+    // reads a shape where each element is either '.' (empty) or '@' (part of the shape) - path is only for error messages
+    fn read_shape<R: BufRead>(path: &str, f: R) -> Result<Self, Error> {
+        let format = |line: usize, col: usize, msg| Error::Format { path: path.to_owned(), line, col, msg };
+        let mut shape: BTreeSet<(isize, isize)> = Default::default();
+        let mut rows = 0;
+        for (row, line) in f.lines().enumerate() {
+            let line = line.map_err(|e| Error::io("read geometry file", path, e))?;
+            for (col, (pos, item)) in util::tokens(&line).enumerate() {
+                match item {
+                    x if x.chars().count() != 1 => return Err(format(row + 1, pos, "expected geometry element to be length 1")),
+                    "." => (),
+                    "@" => { shape.insert((row as isize, col as isize)); },
+                    _ => return Err(format(row + 1, pos, "encountered unexpected character")),
+                };
+            }
+            rows = row + 1;
+        }
+        if shape.is_empty() {
+            return Err(format(rows + 1, 1, "shape is empty"));
+        }
+        Ok(Geometry::for_printing(&shape, [].iter().copied()))
+    }
+    pub fn rectangle(rows: usize, cols: usize) -> Self {
+        assert!(rows > 0 && cols > 0);
+        Self::block((rows as isize, cols as isize))
+    }
+    pub fn width(&self) -> isize {
+        self.extent.1
+    }
+    pub fn height(&self) -> isize {
+        self.extent.0
+    }
+}
+impl<P: Point> Geometry<P> {
+    pub fn for_printing<I>(shape: &BTreeSet<P>, detectors: I) -> Self
+    where I: Iterator<Item = P>
+    {
+        assert!(!shape.is_empty());
+
+        let min = P::from_fn(|i| shape.iter().map(|p| p.coord(i)).min().unwrap());
+        let max = P::from_fn(|i| shape.iter().map(|p| p.coord(i)).max().unwrap());
+
+        Self {
+            shape: shape.iter().map(|p| p.sub(min)).collect(),
+            detectors: detectors.map(|p| p.sub(min)).collect(),
+            extent: P::from_fn(|i| max.coord(i) - min.coord(i) + 1),
+        }
+    }
+    // the box with the given size in each coordinate (rows x cols for a rectangle)
+    pub fn block(dims: P) -> Self {
+        assert!((0..P::DIM).all(|i| dims.coord(i) > 0));
+        Self {
+            shape: P::range(P::origin(), P::from_fn(|i| dims.coord(i) - 1)).into_iter().collect(),
+            detectors: Default::default(),
+            extent: dims,
+        }
+    }
+    pub fn extent(&self) -> P {
+        self.extent
+    }
+    pub fn size(&self) -> usize {
+        self.shape.len()
+    }
+    pub fn sub_geometries(self, size: usize) -> impl Iterator<Item=Geometry<P>> {
+        self.shape.into_iter().combinations(size).map(|set| Geometry::for_printing(&set.into_iter().collect(), [].iter().copied()))
+    }
+    pub fn shape(&self) -> &BTreeSet<P> {
+        &self.shape
+    }
+    pub fn detectors(&self) -> &BTreeSet<P> {
+        &self.detectors
+    }
+}
+#[test]
+fn test_geometry_read_shape() {
+    let geo = Geometry::read_shape("test", &b"@ @ .\n. @ @\n"[..]).unwrap();
+    assert_eq!(geo.size(), 4);
+    assert_eq!((geo.width(), geo.height()), (3, 2));
+
+    let err = |text: &[u8]| match Geometry::read_shape("test", text) {
+        Err(Error::Format { line, col, msg, .. }) => (line, col, msg),
+        _ => panic!(),
+    };
+    assert_eq!(err(b"@ @\n@  x @\n"), (2, 4, "encountered unexpected character"));
+    assert_eq!(err(b"@@ @\n"), (1, 1, "expected geometry element to be length 1"));
+    assert_eq!(err(b". .\n. .\n"), (3, 1, "shape is empty"));
+}
+// planar tiles print as a grid, and higher dimensional ones a (row, col) slice at a time under the rest of their coordinates
+impl<P: Point> fmt::Display for Geometry<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut slices: BTreeMap<Vec<isize>, Vec<(isize, isize)>> = Default::default();
+        for x in &self.shape {
+            let c = x.coords();
+            slices.entry(c[2..].to_vec()).or_default().push((c[0], c[1]));
+        }
+        for (layer, cells) in slices {
+            if P::DIM > 2 {
+                writeln!(f, "layer {}:", layer.iter().join(", "))?;
+            }
+            let mut working_row = !0;
+            let mut working_col = 0;
+            for (row, col) in cells {
+                if row != working_row {
+                    if working_row != !0 {
+                        for _ in 0..(row - working_row) { writeln!(f)?; }
+                    }
+                    working_row = row;
+                    working_col = 0;
+                }
+                for _ in 0..(col - working_col) { write!(f, "  ")?; }
+                working_col = col + 1;
+                let x = P::from_fn(|i| match i { 0 => row, 1 => col, _ => layer[i - 2] });
+                write!(f, "{} ", if self.detectors.contains(&x) { 1 } else { 0 })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+#[test]
+fn test_geometry_block() {
+    let geo = Geometry::block((2, 1, 3));
+    assert_eq!((geo.size(), geo.extent()), (6, (2, 1, 3)));
+    let geo = Geometry::for_printing(geo.shape(), [(1, 0, 2)].iter().copied());
+    assert_eq!(geo.to_string(), "layer 0:\n0 \n0 \nlayer 1:\n0 \n0 \nlayer 2:\n0 \n1 \n");
+    assert_eq!(Geometry::rectangle(2, 3).to_string(), "0 0 0 \n0 0 0 \n");
+}
+
+// a full rank sublattice of Z^d in hermite normal form: lower triangular rows with a positive diagonal, and everything left of
+// the diagonal reduced mod the diagonal entry of its column. every basis of the same lattice has the same form.
+struct Sublattice<P: Point> {
+    hnf: Vec<P>,
+}
+impl<P: Point> Sublattice<P> {
+    fn new(basis: &[P]) -> Self {
+        let d = P::DIM;
+        assert_eq!(basis.len(), d);
+        let mut rows: Vec<Vec<isize>> = basis.iter().map(|b| b.coords()).collect();
+        for j in (0..d).rev() {
+            // euclid down column j until only row j is left with anything in it
+            loop {
+                let pivot = (0..=j).filter(|&i| rows[i][j] != 0).min_by_key(|&i| rows[i][j].abs()).expect("singular basis");
+                rows.swap(pivot, j);
+                let mut done = true;
+                let (above, below) = rows.split_at_mut(j);
+                for row in above.iter_mut() {
+                    let q = row[j] / below[0][j];
+                    row.iter_mut().zip(&below[0]).for_each(|(x, y)| *x -= q * y);
+                    done &= row[j] == 0;
+                }
+                if done { break; }
+            }
+            if rows[j][j] < 0 {
+                rows[j].iter_mut().for_each(|x| *x = -*x);
+            }
+        }
+        for i in 0..d {
+            let (above, below) = rows.split_at_mut(i);
+            for j in (0..i).rev() {
+                let q = below[0][j].div_euclid(above[j][j]);
+                below[0].iter_mut().zip(&above[j]).for_each(|(x, y)| *x -= q * y);
+            }
+        }
+        Self { hnf: rows.iter().map(|r| P::from_fn(|k| r[k])).collect() }
+    }
+    // the representative of p's coset (the one in the box the diagonal spans)
+    fn reduce(&self, p: P) -> P {
+        (0..P::DIM).rev().fold(p, |p, i| p.sub(self.hnf[i].scale(p.coord(i).div_euclid(self.hnf[i].coord(i)))))
+    }
+}
+#[test]
+fn test_sublattice() {
+    let a = Sublattice::new(&[(2, 1), (0, 3)]);
+    let b = Sublattice::new(&[(2, 4), (4, 5)]);
+    assert_eq!(a.hnf, vec![(6, 0), (2, 1)]);
+    assert_eq!(a.hnf, b.hnf);
+    assert_eq!(a.reduce((7, 5)), a.reduce((7 + 6, 5 + 3 - 3)));
+
+    let c = Sublattice::new(&[(7, 0, 0), (5, 1, 0), (4, 0, 1)]);
+    assert_eq!(c.hnf, vec![(7, 0, 0), (5, 1, 0), (4, 0, 1)]);
+    let cosets: BTreeSet<_> = Point::range((0, 0, 0), (6, 6, 6)).into_iter().map(|p| c.reduce(p)).collect();
+    assert_eq!(cosets.len(), 7);
+}
+// the determinant of a square matrix (given by rows), by expanding along the first row
+fn det(m: &[Vec<isize>]) -> isize {
+    if m.is_empty() {
+        return 1;
+    }
+    (0..m.len()).map(|j| {
+        let minor: Vec<Vec<isize>> = m[1..].iter().map(|r| r.iter().enumerate().filter(|&(k, _)| k != j).map(|(_, &x)| x).collect()).collect();
+        if j % 2 == 0 { m[0][j] * det(&minor) } else { -m[0][j] * det(&minor) }
+    }).sum()
+}
+// calls f on every basis of vectors from vecs (in order) whose fundamental domain has the given size, which every basis a tile tessellates with has.
+// the determinant is linear in the last vector, so rather than trying them all it's a dot product with the cofactors of the others.
+fn for_each_basis<P: Point, F: FnMut(&[P])>(vecs: &[P], size: usize, basis: &mut Vec<P>, f: &mut F) {
+    if basis.len() + 1 < P::DIM {
+        for &v in vecs {
+            basis.push(v);
+            for_each_basis(vecs, size, basis, f);
+            basis.pop();
+        }
+        return;
+    }
+    let rows: Vec<Vec<isize>> = basis.iter().map(|b| b.coords()).collect();
+    let cofactors = P::from_fn(|i| {
+        let minor: Vec<Vec<isize>> = rows.iter().map(|r| r.iter().enumerate().filter(|&(k, _)| k != i).map(|(_, &x)| x).collect()).collect();
+        if (i + P::DIM - 1) % 2 == 0 { det(&minor) } else { -det(&minor) }
+    });
+    if cofactors == P::origin() {
+        return; // the others are already dependent
+    }
+    for &v in vecs {
+        if v.dot(cofactors).unsigned_abs() == size {
+            basis.push(v);
+            f(basis);
+            basis.pop();
+        }
+    }
+}
+
+// the number of shape cells decided up front for each work unit of the parallel search (so 2^this units in total)
+const TESS_SPLIT_DEPTH: usize = 8;
+
+type TessellationMap<P> = (HashMap<P, P>, Vec<P>);
+struct GeometrySolver<'a, Codes, P>
+where Codes: codesets::Set, P: Point
+{
+    shape: &'a BTreeSet<P>,
+    interior: &'a BTreeSet<P>,
+    shape_with_padding: &'a BTreeSet<P>,
+    old_set: &'a mut BTreeSet<P>,
+    is_detector: Vec<bool>, // mirrors old_set by index in shape
+
+    tessellation_maps: &'a [TessellationMap<P>],
+    current_tessellation_map: &'a TessellationMap<P>,
+    src_basis: &'a mut Vec<P>,
+
+    codes: Codes,
+    needed: usize,
+    partial: codesets::PartialCodes<Codes>, // the interior codes completed so far, for rejecting partial solutions early
+
+    classes_to_check: Vec<usize>,
+    checked_classes: Vec<usize>,
+
+    stop: Option<&'a AtomicBool>, // set by other threads to make this search halt early
+}
+impl<'a, Codes, P> GeometrySolver<'a, Codes, P>
+where Codes: codesets::Set<Item = P>, P: Point
+{
+    fn get_locating_code<Adj: adj::AdjacentIterator<Point = P>>(&self, adj: &Adj, pos: P, offset: P) -> Codes::LocatingCode {
+        let mut v = Vec::with_capacity(9);
+        let class = adj.class(pos.add(offset)); // compute the effective class as if we applied the given offset
+        for x in adj.with_class(pos, class) {
+            if self.old_set.contains(self.current_tessellation_map.0.get(&x).unwrap()) {
+                v.push(x);
+            }
+        }
+        let is_detector = self.old_set.contains(self.current_tessellation_map.0.get(&pos).unwrap());
+        Codes::LocatingCode::new(pos, is_detector, v)
+    }
+    fn calc_old_min_interior<'b, Adj, I, F>(&mut self, adj: &Adj, mut pos: I, f: &mut F) -> SearchCommand
+    where Adj: adj::AdjacentIterator<Point = P>, I: Iterator<Item = (usize, &'b P)> + Clone,
+    F: FnMut(&BTreeSet<P>, &[P]) -> SearchCommand
+    {
+        if self.stop.map(|s| s.load(Ordering::Relaxed)).unwrap_or(false) {
+            return SearchCommand::Halt;
+        }
+        if self.needed == self.old_set.len() {
+            if self.is_old(adj) {
+                return f(self.old_set, self.src_basis);
+            }
+        } else if let Some((i, &p)) = pos.next() {
+            if i + (self.needed - self.old_set.len()) > self.shape.len() {
+                return SearchCommand::Continue;
+            }
+
+            self.old_set.insert(p);
+            self.is_detector[i] = true;
+            if self.partial.push(i, &self.is_detector, self.needed - self.old_set.len()) {
+                let res = self.calc_old_min_interior(adj, pos.clone(), f);
+                self.partial.pop();
+                if res == SearchCommand::Halt {
+                    return SearchCommand::Halt;
+                }
+            }
+            self.old_set.remove(&p);
+            self.is_detector[i] = false;
+            if !self.partial.push(i, &self.is_detector, self.needed - self.old_set.len()) {
+                return SearchCommand::Continue;
+            }
+            let res = self.calc_old_min_interior(adj, pos, f);
+            self.partial.pop();
+            return res;
+        }
+
+        SearchCommand::Continue
+    }
+    // calls f on every tiling meeting the goal (detectors and basis) until it asks to halt
+    fn for_each_solution<Adj, F>(&mut self, adj: &Adj, goal: Goal, f: F) -> SearchCommand
+    where Adj: adj::AdjacentIterator<Point = P>, F: FnMut(&BTreeSet<P>, &[P]) -> SearchCommand
+    {
+        self.for_each_solution_from(adj, goal, &[], f)
+    }
+    // like for_each_solution, but only over the detector sets that start with the given decisions on the first cells of the shape (in order).
+    // every way of deciding those cells gives a disjoint piece of the full search, so the pieces can be searched separately.
+    fn for_each_solution_from<Adj, F>(&mut self, adj: &Adj, goal: Goal, prefix: &[bool], mut f: F) -> SearchCommand
+    where Adj: adj::AdjacentIterator<Point = P>, F: FnMut(&BTreeSet<P>, &[P]) -> SearchCommand
+    {
+        assert_eq!(adj.classes()[0], P::origin()); // for the love of all that's holy let class 0 be an identity
+
+        self.old_set.clear();
+        self.is_detector = vec![false; self.shape.len()];
+        self.needed = goal.get_value(self.shape.len());
+
+        // interior codes (with no offset) never leave the shape, so they can be checked as soon as they're decided
+        let index: HashMap<P, usize> = self.shape.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+        let neighborhoods = self.shape.iter().map(|p| {
+            if !self.interior.contains(p) { return None; }
+            adj.at(*p).map(|x| index.get(&x).copied()).collect()
+        }).collect();
+        self.partial = codesets::PartialCodes::new(self.shape.iter().copied().collect(), neighborhoods);
+
+        // replay the prefix decisions the same way calc_old_min_interior would have made them
+        let shape = self.shape;
+        for (i, (&p, &take)) in shape.iter().zip(prefix).enumerate() {
+            if self.needed == self.old_set.len() {
+                if take { return SearchCommand::Continue; } // the search would have stopped here, which is the prefix without this detector
+                continue;
+            }
+            if i + (self.needed - self.old_set.len()) > shape.len() {
+                return SearchCommand::Continue;
+            }
+            if take {
+                self.old_set.insert(p);
+                self.is_detector[i] = true;
+            }
+            if !self.partial.push(i, &self.is_detector, self.needed - self.old_set.len()) {
+                return SearchCommand::Continue;
+            }
+        }
+
+        self.calc_old_min_interior(adj, shape.iter().enumerate().skip(prefix.len()), &mut f)
+    }
+}
+impl<Codes, P> Solver for GeometrySolver<'_, Codes, P>
+where Codes: codesets::Set<Item = P>, P: Point
+{
+    type Point = P;
+    fn is_old<Adj: adj::AdjacentIterator<Point = P>>(&mut self, adj: &Adj) -> bool {
+        'next_tess: for tess in self.tessellation_maps {
+            self.current_tessellation_map = tess;
+
+            // check for validity in all induced classes, including the native one (class 0)
+            self.classes_to_check.clear();
+            self.checked_classes.clear();
+            self.classes_to_check.push(0);
+            while let Some(class) = self.classes_to_check.pop() {
+                self.checked_classes.push(class);
+                let c = adj.classes()[class]; // for an effective offset (center), we can just use the class position itself
+
+                // check validity for this class - on failure move on to the next tessellation
+                self.codes.clear();
+                for pos in self.shape_with_padding {
+                    let loc = self.get_locating_code(adj, *pos, c); // generate loc codes for this class
+                    if !self.codes.add(loc) {
+                        continue 'next_tess;
+                    }
+                }
+
+                // compute the induced classes from the current tessellation basis vectors
+                for b in &tess.1 {
+                    let induced_class = adj.class(c.add(*b));
+                    // if we haven't seen it before, add it to the list of classes to check
+                    if !self.classes_to_check.contains(&induced_class) && !self.checked_classes.contains(&induced_class) {
+                        self.classes_to_check.push(induced_class);
+                    }
+                }
+            }
+
+            // merciful domi, we've done it! update the source basis vecs before returning the good news
+            self.src_basis.clone_from(&tess.1);
+            return true;
+        }
+        false // otherwise no tessellation worked - failure
+    }
+    fn try_satisfy<Adj: adj::AdjacentIterator<Point = P>>(&mut self, adj: &Adj, goal: Goal) -> Option<usize> {
+        match self.for_each_solution(adj, goal, |_, _| SearchCommand::Halt) {
+            SearchCommand::Halt => Some(self.needed),
+            SearchCommand::Continue => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GeometryTessellation<P: Point = Point2> {
+    geo: Geometry<P>,
+    interior: BTreeSet<P>,
+    shape_with_padding: BTreeSet<P>,
+    tessellation_maps: Vec<TessellationMap<P>>,
+    basis: Vec<P>,
+    reach: isize, // how far the padding goes, in king moves
+}
+impl<P: Point> fmt::Display for GeometryTessellation<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.geo)?;
+        write!(f, "basis:")?;
+        for b in &self.basis {
+            write!(f, " {:?}", b)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "size: {}", self.size())?;
+        Ok(())
+    }
+}
+impl<P: Point> GeometryTessellation<P> {
+    // the tile along with its current detectors
+    pub fn geometry(&self) -> &Geometry<P> {
+        &self.geo
+    }
+    // the basis of the current tiling
+    pub fn basis(&self) -> &[P] {
+        &self.basis
+    }
+    // the bases of every distinct tiling the tile has
+    pub fn tilings(&self) -> impl Iterator<Item = &[P]> + '_ {
+        self.tessellation_maps.iter().map(|(_, basis)| &basis[..])
+    }
+    fn solver<Codes>(&mut self) -> GeometrySolver<'_, Codes, P>
+    where Codes: codesets::Set<Item = P>
+    {
+        GeometrySolver::<Codes, P> {
+            shape: &self.geo.shape,
+            interior: &self.interior,
+            shape_with_padding: &self.shape_with_padding,
+            old_set: &mut self.geo.detectors,
+            is_detector: vec![],
+
+            tessellation_maps: &self.tessellation_maps,
+            current_tessellation_map: &self.tessellation_maps[0],
+            src_basis: &mut self.basis,
+
+            codes: Default::default(),
+            needed: 0,
+            partial: codesets::PartialCodes::new(vec![], vec![]),
+
+            classes_to_check: Vec::with_capacity(8),
+            checked_classes: Vec::with_capacity(8),
+
+            stop: None,
+        }
+    }
+}
+impl<P: Point> TryFrom<Geometry<P>> for GeometryTessellation<P> {
+    type Error = Error;
+    fn try_from(geo: Geometry<P>) -> Result<Self, Self::Error> {
+        Self::with_reach(geo, 1)
+    }
+}
+impl<P: Point> GeometryTessellation<P> {
+    fn with_reach(geo: Geometry<P>, reach: isize) -> Result<Self, Error> {
+        // everything within reach king moves of the given points
+        let moves = P::king_moves();
+        let pad = |points: &BTreeSet<P>| {
+            let mut t = points.clone();
+            for _ in 0..reach {
+                let ring: Vec<_> = t.iter().flat_map(|&x| moves.iter().map(move |&d| x.add(d))).collect();
+                t.extend(ring);
+            }
+            t
+        };
+        let interior: BTreeSet<_> = geo.shape.iter().filter(|&x| pad(&iter::once(*x).collect()).is_subset(&geo.shape)).copied().collect();
+
+        let shape_with_padding = pad(&geo.shape);
+        let shape_with_extra_padding = pad(&shape_with_padding);
+
+        let tessellation_maps: Vec<_> = {
+            let mut valid_tessellations: BTreeMap<BTreeMap<P, P>, Vec<P>> = Default::default();
+            let mut m: BTreeMap<P, P> = Default::default();
+            let mut settled: HashSet<Vec<P>> = Default::default(); // the lattices (by hnf) we already know do or don't tessellate
+            let translates = P::range(P::from_fn(|_| -(2 * reach + 1)), P::from_fn(|_| 2 * reach + 1));
+
+            // needs to be 2w and 2h so that we allow them to slip between one another
+            let basis_vecs = P::range(P::origin(), geo.extent.scale(2));
+            for_each_basis(&basis_vecs, geo.size(), &mut Vec::with_capacity(P::DIM), &mut |basis: &[P]| {
+                let lattice = Sublattice::new(basis);
+                if settled.contains(&lattice.hnf) {
+                    return;
+                }
+                // the size already matches, so the tile tessellates exactly when its cells are all in different cosets - if not, this is no good
+                let cosets: HashSet<P> = geo.shape.iter().map(|&x| lattice.reduce(x)).collect();
+                if cosets.len() != geo.shape.len() {
+                    settled.insert(lattice.hnf);
+                    return;
+                }
+
+                // attempt the tessellation (the translates have to reach past the extra padding)
+                m.clear();
+                for &to in geo.shape.iter() {
+                    for t in &translates {
+                        let from = (0..P::DIM).fold(to, |x, i| x.add(basis[i].scale(t.coord(i))));
+                        if shape_with_extra_padding.contains(&from) {
+                            m.insert(from, to);
+                        }
+                    }
+                }
+                if m.len() != shape_with_extra_padding.len() {
+                    return; // if the translates aren't dense this basis is too skewed - another basis of the same lattice might still do
+                }
+
+                // we get to this point then tessellation is ok - add it - if we already had it, keep the old one
+                settled.insert(lattice.hnf);
+                valid_tessellations.entry(mem::take(&mut m)).or_insert_with(|| basis.to_vec());
+            });
+            if valid_tessellations.is_empty() {
+                return Err(Error::NoTessellation { shape: geo.to_string() });
+            }
+            valid_tessellations.into_iter().map(|(a, b)| (a.into_iter().collect(), b)).collect()
+        };
+        let basis = tessellation_maps[0].1.clone(); // the specific values don't really matter, but better to at least use real values
+
+        Ok(Self {
+            geo, interior, shape_with_padding, tessellation_maps, basis, reach,
+        })
+    }
+}
+impl<P: Point> Tessellation for GeometryTessellation<P> {
+    type Point = P;
+    fn size(&self) -> usize {
+        self.geo.shape.len()
+    }
+    fn try_satisfy<Codes, Adj>(&mut self, adj: &Adj, goal: Goal) -> Option<usize>
+    where Codes: codesets::Set<Item = P>, Adj: adj::AdjacentIterator<Point = P>
+    {
+        self.solver::<Codes>().try_satisfy(adj, goal)
+    }
+    fn try_satisfy_sat<Codes, Adj>(&mut self, adj: &Adj, goal: Goal) -> Option<usize>
+    where Codes: cnf::Encode<Item = P>, Adj: adj::AdjacentIterator<Point = P>
+    {
+        let needed = goal.get_value(self.geo.shape.len());
+        match self.for_each_solution::<Codes, _, _>(adj, goal, Engine::Sat, false, |_, _, _| SearchCommand::Halt) {
+            SearchCommand::Halt => Some(needed),
+            SearchCommand::Continue => None,
+        }
+    }
+    fn try_satisfy_parallel<Codes, Adj>(&mut self, adj: &Adj, goal: Goal, threads: usize) -> Option<usize>
+    where Codes: codesets::Set<Item = P>, Adj: adj::AdjacentIterator<Point = P>
+    {
+        // each work unit is one way of deciding the first few cells, handed out in the order the serial search would reach them
+        let depth = TESS_SPLIT_DEPTH.min(self.geo.shape.len());
+        let next = AtomicUsize::new(0);
+        let found = AtomicBool::new(false);
+        let solution = Mutex::new(None);
+        let this = &*self;
+        thread::scope(|s| {
+            for _ in 0..threads.max(1) {
+                s.spawn(|| {
+                    let mut tess = this.clone();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= 1 << depth || found.load(Ordering::Relaxed) { break; }
+
+                        let prefix: Vec<bool> = (0..depth).map(|j| i & (1 << (depth - 1 - j)) == 0).collect(); // include before exclude, like the recursion
+                        let mut hit = false;
+                        let mut solver = tess.solver::<Codes>();
+                        solver.stop = Some(&found);
+                        solver.for_each_solution_from(adj, goal, &prefix, |_, _| {
+                            hit = true;
+                            SearchCommand::Halt
+                        });
+                        // the solver left the solution in the tile, so the first worker to get here hands over its copy
+                        if hit {
+                            if !found.swap(true, Ordering::Relaxed) {
+                                *solution.lock().unwrap() = Some(tess);
+                            }
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let tess = solution.into_inner().unwrap()?;
+        *self = tess;
+        Some(goal.get_value(self.geo.shape.len()))
+    }
+    fn for_each_solution<Codes, Adj, F>(&mut self, adj: &Adj, goal: Goal, engine: Engine, unique: bool, mut f: F) -> SearchCommand
+    where Codes: cnf::Encode<Item = P>, Adj: adj::AdjacentIterator<Point = P>, F: FnMut(&Geometry<P>, &[P], usize) -> SearchCommand
+    {
+        let shape = self.geo.shape.clone();
+        let cells: Vec<P> = shape.iter().copied().collect();
+
+        // a detector set can work for several bases, so we remember what we've reported (and with dedupe, every translate of it)
+        let mut seen: HashSet<Vec<usize>> = Default::default();
+        let symmetries = if unique { lattice_symmetries(adj) } else { vec![] };
+        let mut translations: HashMap<Vec<P>, Vec<symmetry::Perm>> = Default::default();
+        let mut report = |detectors: &BTreeSet<P>, basis: &[P]| {
+            let set: Vec<usize> = detectors.iter().map(|p| cells.binary_search(p).unwrap()).collect();
+            if seen.contains(&set) { return SearchCommand::Continue; }
+            let count = if unique {
+                // the same tile detector set can be found with different bases (which are different tilings),
+                // so a translate or rotation of this tiling might have already been reported as a tiling with some other basis
+                let orbit = tiling_orbit(adj, &cells, basis, &set, &symmetries, &mut translations);
+                let count = orbit.len();
+                let repeat = orbit.iter().any(|x| seen.contains(x));
+                seen.extend(orbit);
+                if repeat { return SearchCommand::Continue; }
+                count
+            } else {
+                seen.insert(set);
+                1
+            };
+            f(&Geometry::for_printing(&shape, detectors.iter().copied()), basis, count)
+        };
+
+        match engine {
+            Engine::Recursive => self.solver::<Codes>().for_each_solution(adj, goal, report),
+            Engine::Sat => {
+                let needed = goal.get_value(cells.len());
+                let cell_vars: Vec<cnf::Lit> = (1..=cells.len() as cnf::Lit).collect();
+                for map in 0..self.tessellation_maps.len() {
+                    let mut cnf = self.to_cnf::<Codes, _>(adj, &self.tessellation_maps[map]);
+                    cnf.exactly(&cell_vars, needed);
+                    let basis = self.tessellation_maps[map].1.clone();
+
+                    let mut solver = sat::Solver::from_cnf(&cnf);
+                    while let Some(model) = solver.solve() {
+                        self.geo.detectors = cells.iter().zip(model).filter(|(_, x)| *x).map(|(p, _)| *p).collect();
+                        self.basis.clone_from(&basis);
+                        if report(&self.geo.detectors, &basis) == SearchCommand::Halt {
+                            return SearchCommand::Halt;
+                        }
+                        // block this detector set (there are exactly needed detectors, so one of them has to go)
+                        let block: Vec<cnf::Lit> = self.geo.detectors.iter().map(|p| -cell_vars[cells.binary_search(p).unwrap()]).collect();
+                        solver.add_clause(&block);
+                    }
+                }
+                SearchCommand::Continue
+            }
+        }
+    }
+    fn fit_reach(&mut self, reach: isize) -> Result<(), Error> {
+        // more padding never hurts, so we only rebuild for graphs reaching further than we've prepared for
+        if reach > self.reach {
+            *self = Self::with_reach(self.geo.clone(), reach)?;
+        }
+        Ok(())
+    }
+}
+impl<P: Point> GeometryTessellation<P> {
+    // encodes validity of a tiling under the given tessellation map (shape cell i is variable i + 1) with no size constraint
+    fn to_cnf<Codes, Adj>(&self, adj: &Adj, tess: &TessellationMap<P>) -> cnf::Cnf
+    where Codes: cnf::Encode<Item = P>, Adj: adj::AdjacentIterator<Point = P>
+    {
+        assert_eq!(adj.classes()[0], P::origin()); // for the love of all that's holy let class 0 be an identity
+
+        let (map, basis) = tess;
+        let cells: Vec<P> = self.geo.shape.iter().copied().collect();
+
+        // every position covered by the map is a point, using the variable of the shape cell it maps to
+        let mut points: Vec<P> = map.keys().copied().collect();
+        points.sort();
+        let var_of: Vec<cnf::Lit> = points.iter().map(|p| cells.binary_search(&map[p]).unwrap() as cnf::Lit + 1).collect();
+
+        // the tiling has to be valid in every induced class, just like in GeometrySolver::is_old
+        let mut cnf = cnf::Cnf::with_vars(cells.len());
+        for class in induced_classes(adj, basis) {
+            let c = adj.classes()[class];
+            let codes: Vec<cnf::Code> = self.shape_with_padding.iter().map(|&pos| {
+                let class = adj.class(pos.add(c));
+                let near = adj.with_class(pos, class).map(|x| points.binary_search(&x).unwrap()).collect();
+                (points.binary_search(&pos).unwrap(), near)
+            }).collect();
+            Codes::encode(&mut cnf, &cnf::CodeProblem { var_of: &var_of, codes: &codes });
+        }
+        cnf
+    }
+}
+// the nontrivial translations of a tiling (as permutations of the sorted tile cells) that preserve the classes of the lattice.
+// every translation is equivalent (mod the tiling lattice) to one taking the first cell to another cell of the tile.
+fn tiling_translations<Adj: AdjacentIterator>(adj: &Adj, cells: &[Adj::Point], basis: &[Adj::Point]) -> Vec<symmetry::Perm> {
+    // every cell is in its own coset of the tiling lattice, so a translated point is found by its coset
+    let lattice = Sublattice::new(basis);
+    let index: HashMap<Adj::Point, usize> = cells.iter().enumerate().map(|(i, &p)| (lattice.reduce(p), i)).collect();
+
+    let c0 = cells[0];
+    cells[1..].iter().filter_map(|&c| {
+        let t = c.sub(c0);
+        if cells.iter().any(|&p| adj.class(p.add(t)) != adj.class(p)) { return None; }
+        Some(cells.iter().map(|&p| index[&lattice.reduce(p.add(t))]).collect())
+    }).collect()
+}
+#[test]
+fn test_tiling_translations() {
+    // a 2x3 rectangle stacked directly has the whole 6 element translation group on the king grid
+    let cells: Vec<(isize, isize)> = (0..2).flat_map(|r| (0..3).map(move |c| (r, c))).collect();
+    let perms = tiling_translations(&adj::OpenKing::default(), &cells, &[(2, 0), (0, 3)]);
+    assert_eq!(perms.len(), 5);
+    assert_eq!(symmetry::set_orbit(&perms, &[0]).len(), 6);
+    assert_eq!(symmetry::set_orbit(&perms, &[0, 3]).len(), 3);
+
+    // shifted rows give the same group, just with different permutations
+    let perms = tiling_translations(&adj::OpenKing::default(), &cells, &[(2, 1), (0, 3)]);
+    assert_eq!(symmetry::set_orbit(&perms, &[0]).len(), 6);
+    for p in perms.iter() {
+        let mut sorted = p.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..6).collect::<Vec<_>>());
+    }
+}
+// an automorphism of a lattice graph, taking p to linear * p + offset (the linear part given by where it sends each unit vector)
+struct LatticeSymmetry<P: Point> {
+    linear: Vec<P>,
+    offset: P,
+}
+impl<P: Point> LatticeSymmetry<P> {
+    fn linear(&self, p: P) -> P {
+        (0..P::DIM).fold(P::origin(), |x, i| x.add(self.linear[i].scale(p.coord(i))))
+    }
+    fn apply(&self, p: P) -> P {
+        self.linear(p).add(self.offset)
+    }
+}
+// calls f on every ordered choice of k distinct vectors from vecs until it says to halt
+fn for_each_tuple<P: Point, F: FnMut(&[P]) -> SearchCommand>(vecs: &[P], k: usize, tuple: &mut Vec<P>, f: &mut F) -> SearchCommand {
+    if tuple.len() == k {
+        return f(tuple);
+    }
+    for &v in vecs {
+        if tuple.contains(&v) { continue; }
+        tuple.push(v);
+        let res = for_each_tuple(vecs, k, tuple, f);
+        tuple.pop();
+        if res == SearchCommand::Halt { return res; }
+    }
+    SearchCommand::Continue
+}
+// the affine automorphisms of Adj's graph that take the origin to one of the class representatives - every affine automorphism
+// is one of these followed by a translation that keeps the classes. that's the whole point group on the square, king and
+// triangular grids, but graphs drawn skewed (like the brick wall honeycomb) can have rotations that aren't affine in our points.
+// the linear part is pinned down by where it sends some independent neighbors of the origin, and each candidate is checked on
+// everything within a few reaches of the representatives.
+fn lattice_symmetries<Adj: AdjacentIterator>(adj: &Adj) -> Vec<LatticeSymmetry<Adj::Point>> {
+    let offsets = |p: Adj::Point| -> Vec<Adj::Point> {
+        let mut v: Vec<_> = adj.open().at(p).map(|q| q.sub(p)).collect();
+        v.sort_unstable();
+        v
+    };
+    let d = <Adj::Point as Point>::DIM;
+    let from = offsets(Adj::Point::origin());
+
+    // the first independent neighbors, along with the cofactors of their matrix (so the map is adj(frame) * images / det(frame))
+    let mut frame = vec![];
+    for_each_tuple(&from, d, &mut Vec::with_capacity(d), &mut |t: &[Adj::Point]| {
+        if det(&t.iter().map(|e| e.coords()).collect::<Vec<_>>()) == 0 { return SearchCommand::Continue; }
+        frame = t.to_vec();
+        SearchCommand::Halt
+    });
+    let rows: Vec<Vec<isize>> = frame.iter().map(|e| e.coords()).collect();
+    let frame_det = det(&rows);
+    let cofactor = |i: usize, j: usize| {
+        let minor: Vec<Vec<isize>> = rows.iter().enumerate().filter(|&(r, _)| r != i)
+            .map(|(_, row)| row.iter().enumerate().filter(|&(c, _)| c != j).map(|(_, &x)| x).collect()).collect();
+        [1, -1][(i + j) % 2] * det(&minor)
+    };
+
+    let reps = adj.classes();
+    let w = 3 * adj.reach();
+    let window = <Adj::Point as Point>::range(
+        Adj::Point::from_fn(|k| reps.iter().map(|p| p.coord(k)).min().unwrap() - w),
+        Adj::Point::from_fn(|k| reps.iter().map(|p| p.coord(k)).max().unwrap() + w));
+
+    let mut res = vec![];
+    for &offset in reps {
+        let to = offsets(offset);
+        if to.len() != from.len() { continue; }
+        for_each_tuple(&to, d, &mut Vec::with_capacity(d), &mut |images: &[Adj::Point]| {
+            // solve for the image of each unit vector, which has to be integral
+            let mut linear = Vec::with_capacity(d);
+            for j in 0..d {
+                let v = (0..d).fold(Adj::Point::origin(), |x, i| x.add(images[i].scale(cofactor(i, j))));
+                if (0..d).any(|k| v.coord(k) % frame_det != 0) { return SearchCommand::Continue; }
+                linear.push(Adj::Point::from_fn(|k| v.coord(k) / frame_det));
+            }
+            let sym = LatticeSymmetry { linear, offset };
+            let moved = |p: Adj::Point| {
+                let mut v: Vec<_> = offsets(p).into_iter().map(|x| sym.linear(x)).collect();
+                v.sort_unstable();
+                v
+            };
+            if moved(Adj::Point::origin()) == to && window.iter().all(|&p| moved(p) == offsets(sym.apply(p))) {
+                res.push(sym);
+            }
+            SearchCommand::Continue
+        });
+    }
+    res
+}
+#[test]
+fn test_lattice_symmetries() {
+    // the full point groups of the square, king and triangular grids
+    assert_eq!(lattice_symmetries(&adj::OpenGrid::default()).len(), 8);
+    assert_eq!(lattice_symmetries(&adj::ClosedKing::default()).len(), 8);
+    assert_eq!(lattice_symmetries(&adj::OpenTri::default()).len(), 12);
+    // the honeycomb is drawn as a brick wall, so only its mirrors are affine (the rotations bend the bricks)
+    assert_eq!(lattice_symmetries(&adj::OpenHex::default()).len(), 4);
+    assert_eq!(lattice_symmetries(&crate::cubic::OpenSC::default()).len(), 48);
+    for s in lattice_symmetries(&adj::OpenTri::default()) {
+        assert_eq!(s.offset, (0, 0));
+        assert_eq!(det(&s.linear.iter().map(|e| e.coords()).collect::<Vec<_>>()).abs(), 1);
+    }
+}
+// every tile detector set (as sorted cell indices) giving the same tiling as set does with the given basis, up to translations and lattice symmetries.
+// a symmetry can change the tiling lattice, so its image only counts if the tile tiles the image lattice too (otherwise the tile can't show it).
+fn tiling_orbit<Adj: AdjacentIterator>(adj: &Adj, cells: &[Adj::Point], basis: &[Adj::Point], set: &[usize], symmetries: &[LatticeSymmetry<Adj::Point>], translations: &mut HashMap<Vec<Adj::Point>, Vec<symmetry::Perm>>) -> Vec<Vec<usize>> {
+    let mut orbit: BTreeSet<Vec<usize>> = Default::default();
+    for sym in symmetries {
+        let image_basis: Vec<_> = basis.iter().map(|&b| sym.linear(b)).collect();
+        let lattice = Sublattice::new(&image_basis);
+        let index: HashMap<Adj::Point, usize> = cells.iter().enumerate().map(|(i, &p)| (lattice.reduce(p), i)).collect();
+        if index.len() != cells.len() { continue; }
+
+        let mut image: Vec<usize> = set.iter().map(|&i| index[&lattice.reduce(sym.apply(cells[i]))]).collect();
+        image.sort_unstable();
+        let perms = translations.entry(lattice.hnf.clone()).or_insert_with(|| tiling_translations(adj, cells, &image_basis));
+        orbit.extend(symmetry::set_orbit(perms, &image));
+    }
+    orbit.into_iter().collect()
+}
+#[test]
+fn test_tiling_orbit() {
+    // on the king grid, a vertical domino in a 2x2 tile is a rotated horizontal one
+    let cells = [(0, 0), (0, 1), (1, 0), (1, 1)];
+    let symmetries = lattice_symmetries(&adj::ClosedKing::default());
+    let mut translations = Default::default();
+    let orbit = tiling_orbit(&adj::ClosedKing::default(), &cells, &[(2, 0), (0, 2)], &[0, 1], &symmetries, &mut translations);
+    assert!(orbit.contains(&vec![0, 2]));
+    assert_eq!(orbit.len(), 4);
+}
+// computes the classes a tiling with the given basis vectors induces from class 0 (including class 0 itself)
+fn induced_classes<Adj: AdjacentIterator>(adj: &Adj, basis: &[Adj::Point]) -> Vec<usize> {
+    let mut classes = vec![0];
+    let mut pos = 0;
+    while pos < classes.len() {
+        let c = adj.classes()[classes[pos]];
+        for b in basis {
+            let induced = adj.class(c.add(*b));
+            if !classes.contains(&induced) {
+                classes.push(induced);
+            }
+        }
+        pos += 1;
+    }
+    classes
+}
+
+
+// the goal a search for param is after: a density threshold, or an exact number of detectors (out of size) for the perfect parameters
+pub fn parse_goal(param: Parameter, goal: &str, size: usize) -> Result<Goal, Error> {
+    Ok(match param {
+        Parameter::EDOM | Parameter::EODOM => Goal::Exactly(params::parse_exact(goal, size)?),
+        _ => Goal::MeetOrBeat(params::parse_thresh(goal)?),
+    })
+}
+// searches the tiling for a detector set meeting the goal, returning the number of detectors if one was found.
+// the recursive search is split across threads if there's more than one (the sat solver always uses one).
+pub fn solve<T: Tessellation<Point = Point2>>(tess: &mut T, param: Parameter, graph: &Graph, goal: &str, engine: Engine, threads: usize) -> Result<Option<usize>, Error> {
+    let goal = parse_goal(param, goal, tess.size())?;
+    tess.fit_reach(graph.reach())?;
+    macro_rules! calc {
+        ($set:ident, $adj:expr) => {
+            match engine {
+                Engine::Recursive if threads > 1 => tess.try_satisfy_parallel::<codesets::$set<(isize, isize)>, _>(&$adj, goal, threads),
+                Engine::Recursive => tess.try_satisfy::<codesets::$set<(isize, isize)>, _>(&$adj, goal),
+                Engine::Sat => tess.try_satisfy_sat::<codesets::$set<(isize, isize)>, _>(&$adj, goal),
+            }
+        }
+    }
+    Ok(graph_family!(graph, |open, closed| param_family!(param, calc, open, closed)))
+}
+// calls f on every tiling meeting the goal, as in Tessellation::for_each_solution
+pub fn for_each_solution<T, F>(tess: &mut T, param: Parameter, graph: &Graph, goal: &str, engine: Engine, unique: bool, mut f: F) -> Result<SearchCommand, Error>
+where T: Tessellation<Point = Point2>, F: FnMut(&Geometry, &[Point2], usize) -> SearchCommand
+{
+    let goal = parse_goal(param, goal, tess.size())?;
+    tess.fit_reach(graph.reach())?;
+    macro_rules! calc {
+        ($set:ident, $adj:expr) => {
+            tess.for_each_solution::<codesets::$set<(isize, isize)>, _, _>(&$adj, goal, engine, unique, &mut f)
+        }
+    }
+    Ok(graph_family!(graph, |open, closed| param_family!(param, calc, open, closed)))
+}
+#[test]
+fn test_tess_sat_matches_recursive() {
+    for &(rows, cols) in &[(2, 2), (2, 3)] {
+        for param in &["dom", "ld", "ic", "redic", "detic", "old"] {
+            for graph in &["king", "tri", "grid", "hex"] {
+                for goal in &["0.5", "0.75"] {
+                    let param: Parameter = param.parse().unwrap();
+                    let graph: Graph = graph.parse().unwrap();
+                    let mut rec = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                    let mut sat = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                    let a = solve(&mut rec, param, &graph, goal, Engine::Recursive, 1).unwrap();
+                    let b = solve(&mut sat, param, &graph, goal, Engine::Sat, 1).unwrap();
+                    assert_eq!(a, b, "{}x{} {:?} {:?} {}", rows, cols, param, graph, goal);
+                }
+            }
+        }
+    }
+}
+#[test]
+fn test_tess_archimedean() {
+    for &(rows, cols) in &[(2, 2), (2, 3)] {
+        for param in &["dom", "ld", "ic"] {
+            for graph in &["sns", "kag", "rth", "trh", "etri", "tsq", "ttrh", "snh"] {
+                let param: Parameter = param.parse().unwrap();
+                let graph: Graph = graph.parse().unwrap();
+                let mut rec = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                let mut sat = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                let a = solve(&mut rec, param, &graph, "0.5", Engine::Recursive, 1).unwrap();
+                let b = solve(&mut sat, param, &graph, "0.5", Engine::Sat, 1).unwrap();
+                assert_eq!(a, b, "{}x{} {:?} {:?}", rows, cols, param, graph);
+            }
+        }
+    }
+}
+#[test]
+fn test_tess_parallel_matches_serial() {
+    for &(rows, cols) in &[(2, 3)] {
+        for param in &["dom", "ld", "old"] {
+            for graph in &["grid", "hex"] {
+                for goal in &["0.3", "0.5"] {
+                    let param: Parameter = param.parse().unwrap();
+                    let graph: Graph = graph.parse().unwrap();
+                    let mut serial = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                    let mut parallel = GeometryTessellation::try_from(Geometry::rectangle(rows, cols)).unwrap();
+                    let a = solve(&mut serial, param, &graph, goal, Engine::Recursive, 1).unwrap();
+                    let b = solve(&mut parallel, param, &graph, goal, Engine::Recursive, 3).unwrap();
+                    assert_eq!(a, b, "{}x{} {:?} {:?} {}", rows, cols, param, graph, goal);
+                    if let Some(min) = b {
+                        assert_eq!(parallel.geometry().detectors().len(), min); // the solution should make it back from the worker
+                    }
+                }
+            }
+        }
+    }
+}
+#[test]
+fn test_solve_bad_goal() {
+    use crate::params::ValueError;
+
+    let mut tess = GeometryTessellation::try_from(Geometry::rectangle(2, 2)).unwrap();
+    assert!(matches!(solve(&mut tess, Parameter::DOM, &Graph::SQ, "1.5", Engine::Recursive, 1), Err(Error::Value(ValueError::OutOfRange(_)))));
+    assert!(matches!(solve(&mut tess, Parameter::EDOM, &Graph::SQ, "5", Engine::Recursive, 1), Err(Error::Value(ValueError::OutOfRange(_)))));
+    assert!(matches!(solve(&mut tess, Parameter::EDOM, &Graph::SQ, "0.5", Engine::Recursive, 1), Err(Error::Value(ValueError::Malformed(_)))));
+}
+
+// shared sync state for entropy searches
+struct EntropyState {
+    geos: Box<dyn Iterator<Item = Geometry> + Send>,
+    next: usize, // index of the next geometry geos will give us
+    done_geos: BTreeSet<String>, // shapes we've already handed out
+    in_flight: BTreeSet<usize>, // indices of the geometries currently being searched
+    requeue: Vec<(usize, Geometry)>, // geometries that were in flight when the checkpoint we resumed from was saved
+    found: Option<(GeometryTessellation, usize)>, // set once a solution has been found
+    error: Option<Error>, // set if a thread had to stop on an error
+}
+fn write_entropy_checkpoint(search: &str, data: &EntropyState) -> checkpoint::Writer {
+    let mut w = checkpoint::Writer::new(search);
+    w.field("next", data.next);
+    w.field("in-flight", data.in_flight.iter().chain(data.requeue.iter().map(|(i, _)| i)).join(" "));
+    w.field("done", data.done_geos.len());
+    for geo in &data.done_geos {
+        w.block("geo", geo);
+    }
+    w
+}
+fn read_entropy_checkpoint(path: &str, search: &str) -> Result<(usize, BTreeSet<usize>, BTreeSet<String>), CheckpointError> {
+    let mut r = checkpoint::Reader::open(path, search)?;
+    let next = r.parse("next")?;
+    let in_flight = r.field("in-flight")?;
+    let in_flight = in_flight.split_whitespace().map(|x| x.parse()).collect::<Result<_, _>>()
+        .map_err(|_| CheckpointError::InvalidFormat(format!("failed to parse '{}' as geometry indices", in_flight)))?;
+    let count: usize = r.parse("done")?;
+    let mut done_geos = BTreeSet::new();
+    for _ in 0..count {
+        done_geos.insert(r.block("geo")?);
+    }
+    Ok((next, in_flight, done_geos))
+}
+// the outcome of an entropy search: the first tiling found (with its number of detectors) and how many shapes were handed out
+pub struct EntropyResult {
+    pub found: Option<(GeometryTessellation, usize)>,
+    pub tested: usize,
+}
+// a search of every distinct sub-geometry of a given size of a big geometry, stopping at the first one that tiles with a set meeting the goal
+pub struct EntropySearch {
+    data: EntropyState,
+    search: String, // the description checkpoints are tied to
+    param: Parameter,
+    graph: Graph,
+    goal: String,
+}
+impl EntropySearch {
+    pub fn new(big_geo: Geometry, size: usize, param: Parameter, graph: Graph, goal: &str) -> Result<Self, Error> {
+        if size > big_geo.size() {
+            return Err(ValueError::OutOfRange(format!("entropy size cannot exceed size of geometry (geo size {}, entropy size {})", big_geo.size(), size)).into());
+        }
+        parse_goal(param, goal, size)?; // catch a bad goal before starting any threads
+
+        let search = periodic::spell_out(format!("entropy {} {:?} {:?} {}\n{}", size, param, graph, goal, big_geo), &graph);
+        let data = EntropyState {
+            geos: Box::new(big_geo.sub_geometries(size)),
+            next: 0,
+            done_geos: Default::default(),
+            in_flight: Default::default(),
+            requeue: vec![],
+            found: None,
+            error: None,
+        };
+        Ok(EntropySearch { data, search, param, graph, goal: goal.to_owned() })
+    }
+    // skips the geometries a checkpoint of the same search already handed out, but hangs on to the ones that never finished.
+    // returns the number of geometries done and the number to redo.
+    pub fn resume(&mut self, path: &str) -> Result<(usize, usize), Error> {
+        let data = &mut self.data;
+        let (next, in_flight, done_geos) = read_entropy_checkpoint(path, &self.search).map_err(|source| Error::Checkpoint { path: path.to_owned(), source })?;
+        for i in 0..next {
+            match data.geos.next() {
+                Some(geo) if in_flight.contains(&i) => data.requeue.push((i, geo)),
+                Some(_) => (),
+                None => return Err(Error::Checkpoint { path: path.to_owned(), source: CheckpointError::InvalidFormat("checkpoint is past the last geometry".to_owned()) }),
+            }
+        }
+        data.next = next;
+        data.done_geos = done_geos;
+        Ok((data.done_geos.len() - data.requeue.len(), data.requeue.len()))
+    }
+    // searches the remaining geometries across threads, saving checkpoints with saver along the way if given
+    pub fn run(self, threads: usize, saver: Option<checkpoint::Saver>) -> Result<EntropyResult, Error> {
+        let EntropySearch { data, search, param, graph, goal } = self;
+        let data = Mutex::new(data);
+        let saver = Mutex::new(saver);
+
+        thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    loop {
+                        let (index, geo) = {
+                            let mut data = data.lock().unwrap();
+                            if data.found.is_some() || data.error.is_some() {
+                                break // if a solution was found (or another thread failed), abort
+                            }
+
+                            // this is a consistent point to save a checkpoint, since we hold the lock
+                            if let Some(saver) = saver.lock().unwrap().as_mut().filter(|s| s.is_due()) {
+                                if let Err(e) = saver.save(&write_entropy_checkpoint(&search, &data)) {
+                                    data.error = Some(Error::io("write checkpoint", saver.path(), e));
+                                    break;
+                                }
+                            }
+
+                            let next = match data.requeue.pop() {
+                                Some(x) => x,
+                                None => match data.geos.next() {
+                                    Some(geo) => {
+                                        data.next += 1;
+                                        if !data.done_geos.insert(geo.to_string()) {
+                                            continue // if we've already seen a similar shape, skip it
+                                        }
+                                        (data.next - 1, geo)
+                                    },
+                                    None => break, // if there are no more geometries we're done
+                                }
+                            };
+                            data.in_flight.insert(next.0);
+                            next
+                        };
+
+                        // generate the tessellation structure and search it (tessellation failures just move on to the next geometry)
+                        let found = match GeometryTessellation::try_from(geo) {
+                            Ok(mut tess) => solve(&mut tess, param, &graph, &goal, Engine::Recursive, 1).map(|min| min.map(|min| (tess, min))),
+                            Err(_) => Ok(None),
+                        };
+
+                        let mut data = data.lock().unwrap();
+                        data.in_flight.remove(&index);
+                        match found {
+                            Ok(None) => (),
+                            Ok(found) => {
+                                // only the first solution counts
+                                if data.found.is_none() {
+                                    data.found = found;
+                                }
+                                break;
+                            }
+                            Err(e) => {
+                                data.error = Some(e);
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut data = data.into_inner().unwrap(); // we no longer have running threads, so take ownership of the final data
+        if let Some(e) = data.error.take() {
+            return Err(e);
+        }
+        debug_assert!(data.found.is_some() || data.geos.next().is_none()); // without a solution we should have processed all subgeometries
+        Ok(EntropyResult { found: data.found, tested: data.done_geos.len() })
+    }
+}
+#[test]
+fn test_entropy_search() {
+    // some 4 cell piece of a 3x3 square tiles the king grid with a dominating set of density 1/4 (the 2x2 square does)
+    let search = EntropySearch::new(Geometry::rectangle(3, 3), 4, Parameter::DOM, Graph::K, "0.25").unwrap();
+    let res = search.run(2, None).unwrap();
+    let (tess, min) = res.found.unwrap();
+    assert_eq!((tess.size(), min), (4, 1));
+
+    // no 2 cell piece can do better than 1/2, so every distinct shape gets tested
+    let search = EntropySearch::new(Geometry::rectangle(3, 3), 2, Parameter::DOM, Graph::K, "0.4").unwrap();
+    let res = search.run(1, None).unwrap();
+    assert!(res.found.is_none());
+    assert_eq!(res.tested, 12); // one per offset between the two cells
+
+    assert!(matches!(EntropySearch::new(Geometry::rectangle(2, 2), 5, Parameter::DOM, Graph::K, "0.5"), Err(Error::Value(ValueError::OutOfRange(_)))));
+    assert!(matches!(EntropySearch::new(Geometry::rectangle(2, 2), 2, Parameter::DOM, Graph::K, "1.5"), Err(Error::Value(_))));
+}
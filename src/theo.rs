@@ -0,0 +1,1105 @@
+// discharging lower bounds: every detector on the infinite graph gets a share, and we show no configuration around a center
+// can have share more than 1/thresh (after averaging or discharging with its neighbors), which proves thresh is a lower bound on density
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::mem;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use num::{BigInt, BigRational};
+use num::traits::{Zero, One};
+
+use crate::{codesets, certificate, checkpoint, util, Error, SearchCommand};
+use crate::adj::AdjacentIterator;
+use crate::codesets::LOC;
+use crate::perf::{PointMap, PointSet};
+use crate::params::{Parameter, Graph};
+use crate::point::{Point, Point2};
+use crate::tessellation::Geometry;
+
+struct ExpansionLands<P> {
+    field: Vec<P>,
+    total_exterior: Vec<P>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum TheoStrategy {
+    #[default]
+    Trivial,
+    Avg,
+    Dis,
+    DisWeightExcess,
+    DisWeightShare,
+}
+impl FromStr for TheoStrategy {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "trivial" => TheoStrategy::Trivial,
+            "avg" => TheoStrategy::Avg,
+            "dis" => TheoStrategy::Dis,
+            "dis-weight-excess" => TheoStrategy::DisWeightExcess,
+            "dis-weight-share" => TheoStrategy::DisWeightShare,
+
+            _ => return Err(Error::UnknownStrategy(s.to_owned())),
+        })
+    }
+}
+
+pub type Share = BigRational;
+
+
+#[derive(PartialEq, PartialOrd, Eq, Ord)]
+pub struct TheoProblem<P: Point = Point2> {
+    pub center: P,
+    pub share: Share,
+    pub avg_share: Share,
+    pub structure: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MaxShareMode {
+    Max,
+    MaxOrOverThresh,
+}
+// a theo search's checkpoint file, along with where to resume from (and after that, where the last save was).
+// the position is the center class and the detector decisions (in open interior order) of the leaf the search was at.
+pub struct TheoCheckpoint<P: Point = Point2> {
+    pub search: String, // identifies the search, so a checkpoint can't be resumed by a different one
+    pub saver: checkpoint::Saver,
+    pub class: usize,
+    pub path: Vec<bool>,
+    pub problems: BTreeSet<TheoProblem<P>>,
+}
+pub fn write_theo_checkpoint<P: Point>(search: &str, class: usize, path: &[bool], problems: &BTreeSet<TheoProblem<P>>) -> checkpoint::Writer {
+    let mut w = checkpoint::Writer::new(search);
+    w.field("class", class);
+    w.field("path", path.iter().map(|&x| if x { '1' } else { '0' }).collect::<String>());
+    w.field("problems", problems.len());
+    for p in problems {
+        w.field("center", p.center.coords().iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" "));
+        w.field("share", &p.share);
+        w.field("avg-share", &p.avg_share);
+        w.block("structure", &p.structure);
+    }
+    w
+}
+// the center class, the decision path to resume from and the problems recorded so far
+pub type TheoResume<P> = (usize, Vec<bool>, BTreeSet<TheoProblem<P>>);
+pub fn read_theo_checkpoint<P: Point>(path: &str, search: &str) -> Result<TheoResume<P>, checkpoint::CheckpointError> {
+    use checkpoint::CheckpointError::InvalidFormat;
+
+    let mut r = checkpoint::Reader::open(path, search)?;
+    let class = r.parse("class")?;
+    let decisions = r.field("path")?.chars().map(|c| match c {
+        '0' => Ok(false),
+        '1' => Ok(true),
+        _ => Err(InvalidFormat(format!("unexpected character '{}' in path", c))),
+    }).collect::<Result<_, _>>()?;
+    let count: usize = r.parse("problems")?;
+    let mut problems = BTreeSet::new();
+    for _ in 0..count {
+        let center = r.field("center")?;
+        let center = match center.split_whitespace().map(str::parse).collect::<Result<Vec<isize>, _>>() {
+            Ok(c) if c.len() == P::DIM => P::from_fn(|i| c[i]),
+            _ => return Err(InvalidFormat(format!("failed to parse '{}' as a center", center))),
+        };
+        problems.insert(TheoProblem {
+            center,
+            share: r.parse("share")?,
+            avg_share: r.parse("avg-share")?,
+            structure: r.block("structure")?,
+        });
+    }
+    Ok((class, decisions, problems))
+}
+#[test]
+fn test_theo_checkpoint_resume() {
+    use crate::adj;
+
+    let path = std::env::temp_dir().join(format!("old-set-theo-checkpoint-{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+    let run = |checkpoint: Option<&mut TheoCheckpoint>| {
+        calc_lower_bound::<codesets::LD<(isize, isize)>, _, _>(&adj::OpenHex::default(), &adj::ClosedHex::default(), TheoStrategy::Dis, "2/5".parse().unwrap(), Some(&mut io::sink()), checkpoint, None).unwrap()
+    };
+    let full = run(None);
+    assert!(!full.is_empty());
+
+    // saving at every leaf leaves the very last one (every decision excluded) in the file
+    let saver = || checkpoint::Saver::new(path, std::time::Duration::from_secs(0));
+    let mut c = TheoCheckpoint { search: "test".into(), saver: saver(), class: 0, path: vec![], problems: Default::default() };
+    assert!(run(Some(&mut c)) == full);
+    let (class, decisions, problems) = read_theo_checkpoint(path, "test").unwrap();
+    assert_eq!(class, adj::OpenHex::default().classes().len() - 1);
+    assert!(!decisions.is_empty() && decisions.iter().all(|&x| !x));
+    assert!(read_theo_checkpoint::<Point2>(path, "other").is_err());
+
+    // so resuming from it only redoes that leaf, but still has all the problems
+    let mut c = TheoCheckpoint { search: "test".into(), saver: saver(), class, path: decisions, problems };
+    assert!(run(Some(&mut c)) == full);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+// a certificate a search is writing (see the certificate module), which only makes sense if the search finds no problems
+pub struct TheoCertificate<'a> {
+    pub path: String, // for error messages
+    pub out: &'a mut dyn io::Write,
+}
+impl TheoCertificate<'_> {
+    fn write(&mut self, s: &str) -> Result<(), Error> {
+        self.out.write_all(s.as_bytes()).map_err(|e| Error::io("write certificate", &self.path, e))
+    }
+}
+
+struct TheoSearcher<'a, 'b, 'c, Codes, P: Point, Adj: AdjacentIterator<Point = P>, ShareAdj> {
+    adj: &'a Adj,
+    open: Adj::Open, // the open neighborhoods on adj's graph, kept to avoid remaking them
+    share_adj: &'a ShareAdj, // whose neighborhoods the center's share is spread over
+
+    center: P,
+    class: usize, // index of center in the center classes
+    closed_interior: &'a [P], // everything up to radius 2
+    open_interior: &'a [P], // the points we make decisions on, in order
+    exterior: &'a [P], // everything at exactly radius 3
+    detectors: &'a mut PointSet<P>,
+
+    expansion_map: &'a PointMap<ExpansionLands<P>, P>, // maps a neighbor of radius 1 or 2 to its outer points up to radius 2, plus a radius 3 border
+
+    codes: &'a mut Codes,
+
+    thresh: &'a Share,
+    pipe: &'a mut Option<&'b mut dyn io::Write>,
+    problems: &'a mut BTreeSet<TheoProblem<P>>,
+    strategy: TheoStrategy,
+
+    checkpoint: Option<&'a mut TheoCheckpoint<P>>,
+    resume: VecDeque<bool>, // decisions still to replay when resuming from a checkpoint
+    failure: Option<Error>, // set if the search had to halt on an error
+
+    cert: Option<&'a mut TheoCertificate<'c>>,
+    transfers: Vec<certificate::Transfer<P>>, // the discharging done by the last call to do_averaging (only kept for certificates)
+    impossible: Option<P>, // set by do_averaging if the configuration turned out to be impossible (only kept for certificates)
+}
+impl<Codes, P, Adj, ShareAdj> TheoSearcher<'_, '_, '_, Codes, P, Adj, ShareAdj>
+where Codes: codesets::Set<Item = P>, P: Point, Adj: AdjacentIterator<Point = P>, ShareAdj: AdjacentIterator<Point = P>
+{
+    #[must_use]
+    fn get_locating_code(&self, pos: P) -> Codes::LocatingCode {
+        let mut v = Vec::with_capacity(9);
+        for p in self.adj.at(pos) {
+            if self.detectors.contains(&p) {
+                v.push(p);
+            }
+        }
+        let is_detector = self.detectors.contains(&pos);
+        Codes::LocatingCode::new(pos, is_detector, v)
+    }
+    #[must_use]
+    fn is_valid_over<T>(&mut self, range: T) -> bool
+    where T: Iterator<Item = P>
+    {
+        self.codes.clear();
+        for p in range {
+            let loc = self.get_locating_code(p);
+            if !self.codes.add(loc) {
+                return false;
+            }
+        }
+        true
+    }
+    #[must_use]
+    fn calc_share(&self, pos: P) -> Share {
+        assert!(self.detectors.contains(&pos));
+
+        let mut share = Zero::zero();
+        for p in self.share_adj.at(pos) {
+            let c = self.get_locating_code(p).dom();
+            share += Share::new(One::one(), c.into());
+        }
+        share
+    }
+    fn max_simultaneous_problem_neighbors_recursive(&mut self, neighbors: &[P], total_field: &[P], total_exterior: &[P], mut field_pos: std::slice::Iter<P>) -> Option<usize> {
+        match field_pos.next() {
+            None => {
+                // if it's not a valid configuration, don't even bother looking at it (return None to denote illegality)
+                for point in total_exterior {
+                    self.detectors.insert(*point);
+                }
+                if !self.is_valid_over(self.closed_interior.iter().chain(total_field.iter()).copied()) {
+                    return None;
+                }
+                // otherwise count the number of neighbors with problem shares
+                Some(neighbors.iter().filter(|x| self.calc_share(**x) > *self.thresh).count())
+            }
+            Some(p) => {
+                // perform the recursive field expansion
+                self.detectors.insert(*p);
+                let a = self.max_simultaneous_problem_neighbors_recursive(neighbors, total_field, total_exterior, field_pos.clone());
+                self.detectors.remove(p);
+                let b = self.max_simultaneous_problem_neighbors_recursive(neighbors, total_field, total_exterior, field_pos.clone());
+
+                // return none if both failed, otherwise return whatever was larger
+                match (a, b) {
+                    (None, None) => None,
+                    (Some(a), None) => Some(a),
+                    (None, Some(b)) => Some(b),
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                }
+            }
+        }
+    }
+    fn max_simultaneous_problem_neighbors(&mut self, pos: P) -> usize {
+        assert!(self.detectors.contains(&pos));
+        let mut non_center_neighbors = Vec::with_capacity(8);
+        for neighbor in self.open.at(pos) {
+            if neighbor != self.center && self.detectors.contains(&neighbor) {
+                non_center_neighbors.push(neighbor);
+            }
+        }
+        if non_center_neighbors.is_empty() {
+            return 1; // if there are no non-center neighbors, we just have the one problem (center is assumed to have problem share)
+        }
+
+        // gather up all the fields we will have to expand in total
+        let r = P::from_fn(|_| 5 * self.adj.reach());
+        let bounds = (self.center.sub(r), self.center.add(r));
+        let mut total_field = PointSet::with_bounds(bounds.0, bounds.1);
+        let mut total_exterior = PointSet::with_bounds(bounds.0, bounds.1);
+
+        for neighbor in non_center_neighbors.iter() {
+            let lands = self.expansion_map.get(neighbor).unwrap();
+            total_field.extend(lands.field.iter().copied());
+            total_exterior.extend(lands.total_exterior.iter().copied());
+        }
+        for point in total_field.iter() {
+            total_exterior.remove(&point);
+        }
+
+        #[cfg(debug)]
+        {
+            println!("total field:\n{}", Geometry::for_printing(&total_field.iter().collect(), self.detectors.iter()));
+            println!("total exterior:\n{}", Geometry::for_printing(&total_exterior.iter().collect(), self.detectors.iter()));
+            println!("badditude: {}", total_field.iter().count());
+            println!("-------------------------------------");
+        }
+
+        // convert sets into vectors for fastitude
+        let total_field = total_field.iter().collect::<Vec<_>>();
+        let total_exterior = total_exterior.iter().collect::<Vec<_>>();
+        
+        // we have the center problem (by assumption), plus max of non-center neighbors via recursive expansion
+        1 + self.max_simultaneous_problem_neighbors_recursive(&non_center_neighbors, &total_field, &total_exterior, total_field.iter()).unwrap()
+    }
+    #[must_use]
+    fn calc_max_share_expansion_recursive<I>(&mut self, pos: P, lands: &ExpansionLands<P>, mut ext_pos: I, mode: MaxShareMode) -> Share
+    where I: Iterator<Item = P> + Clone
+    {
+        match ext_pos.next() {
+            None => {
+                // if it's an invalid configuration, don't even bother looking at it
+                for p in lands.total_exterior.iter() {
+                    self.detectors.insert(*p);
+                }
+                if !self.is_valid_over(self.closed_interior.iter().chain(lands.field.iter()).copied()) {
+                    return -Share::one(); // return -1 to denote nothing (no share here at all)
+                }
+
+                // otherwise return the share
+                self.calc_share(pos)
+            }
+            Some(p) => {
+                self.detectors.insert(p);
+                let r1 = self.calc_max_share_expansion_recursive(pos, lands, ext_pos.clone(), mode);
+                if mode == MaxShareMode::MaxOrOverThresh && &r1 > self.thresh {
+                    return r1; // if > thresh max will be too - short circuit if allowed
+                }
+                self.detectors.remove(&p);
+                let r2 = self.calc_max_share_expansion_recursive(pos, lands, ext_pos, mode);
+
+                // return max share found
+                if r1 >= r2 { r1 } else { r2 }
+            }
+        }
+    }
+    // expands around boundary to radius 2, returning the maximum share or some possible share > thresh for short-circuitting.
+    // returns -1 if no valid configuration exists.
+    #[must_use]
+    fn calc_max_share_expansion(&mut self, pos: P, mode: MaxShareMode) -> Share {
+        let lands = self.expansion_map.get(&pos).unwrap();
+
+        // go ahead and prepare the total exterior before we start searching so we don't have to do it at every terminal node
+        for p in lands.total_exterior.iter() {
+            self.detectors.insert(*p);
+        }
+
+        // compute the max share recursively
+        self.calc_max_share_expansion_recursive(pos, lands, lands.field.iter().copied(), mode)
+    }
+    #[must_use]
+    fn do_averaging(&mut self, center_share: &Share) -> Share {
+        assert_gt!(center_share, &self.thresh); // by hypothesis, center is a problem
+
+        // cache share values since they take forever to compute
+        let mut shares: HashMap<P, Share> = HashMap::with_capacity(25);
+        shares.insert(self.center, center_share.clone());
+
+        // also keep track of averaging candidates
+        let mut candidates: Vec<(Share, P)> = Default::default();
+
+        // compute the weakest max share mode we need in order to work
+        let max_share_mode = match self.strategy {
+            TheoStrategy::Trivial => panic!("we shouldn't be here"),
+            TheoStrategy::Avg | TheoStrategy::Dis => MaxShareMode::MaxOrOverThresh,
+            TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare => MaxShareMode::Max,
+        };
+
+        // for each neighbor of center which is a detector
+        for neighbor in self.open.at(self.center) {
+            if !self.detectors.contains(&neighbor) {
+                continue;
+            }
+
+            // compute max share of neighbor and store in cache
+            let share = match self.calc_max_share_expansion(neighbor, max_share_mode) {
+                x if x < Share::zero() => return self.no_configurations(neighbor), // if invalid there were no legal configurations in the first place
+                x => x,
+            };
+            shares.insert(neighbor, share.clone());
+
+            // if this is strictly less than thresh it's an averaging candidate
+            if &share < self.thresh {
+                candidates.push((share, neighbor));
+            }
+        }
+
+        // sort averagee/discharge candidates by ascending share (use position to break ties just to guarantee invariant exec order)
+        candidates.sort();
+
+        // go through the average/discharge candidates and keep track of working share as we do averaging/discharging
+        let mut working_share = center_share.clone();
+        'next_candidate: for (share, neighbor) in candidates.iter() {
+            // look at each of my adjacent detectors and keep track of how many problems i'm next to
+            let mut adj_problems = 0;
+            let mut sum_weights = Share::zero(); // this is only updated if using weighted strategy
+            for other in self.open.at(*neighbor) {
+                if !self.detectors.contains(&other) {
+                    continue;
+                }
+
+                // compute its max share - use cache for lookups when possible (at this point center and neighbors are in cache, so only misses are boundary points)
+                let sh = match shares.entry(other).or_insert_with(|| self.calc_max_share_expansion(other, max_share_mode)) {
+                    x if *x < Share::zero() => return self.no_configurations(other), // if invalid there were no legal configurations in the first place
+                    x => x,
+                };
+
+                // if this is strictly larger than thresh it's a problem
+                if &*sh > self.thresh {
+                    adj_problems += 1; // mark as a problem
+
+                    // do any extra needed work for whatever strategy we're using
+                    match self.strategy {
+                        TheoStrategy::Trivial => panic!("we shouldn't be here"),
+                        TheoStrategy::Avg => if adj_problems > 1 { continue 'next_candidate; } // averaging is discharging that requires adj_problems == 1
+                        TheoStrategy::Dis => (),                                               // discharge has no other requirements
+                        TheoStrategy::DisWeightExcess => sum_weights += &*sh - self.thresh,    // weighted discharging with excess
+                        TheoStrategy::DisWeightShare => sum_weights += &*sh,                   // weighted discharging with share
+                    }
+                }
+            }
+            assert_ne!(adj_problems, 0); // this should never be zero because by hypothesis center itself is a problem
+
+            // compute the total amount of safe discharge
+            let max_safe_discharge = {
+                let pool = self.thresh - share; // the total amount of share this neighbor can accept
+                match self.strategy {
+                    TheoStrategy::Trivial => panic!("we shouldn't be here"),
+                    TheoStrategy::Avg | TheoStrategy::Dis => pool / Share::from_integer(adj_problems.into()), // same logic due to above
+                    TheoStrategy::DisWeightExcess => pool * ((center_share - self.thresh) / sum_weights),     // same logic as avg/dis, except weighted by excess
+                    TheoStrategy::DisWeightShare => pool * (center_share / sum_weights),                      // same but different weights
+                }
+            };
+            assert_gt!(max_safe_discharge, Share::zero()); // sanity check
+            if self.cert.is_some() {
+                self.transfers.push(certificate::Transfer { neighbor: *neighbor, bound: share.clone(), problems: adj_problems, simultaneous: false, amount: max_safe_discharge.clone() });
+            }
+
+            // apply maximum safe discharging - if we drop down to or below the target thresh, we're done - yay!
+            working_share -= max_safe_discharge;
+            if &working_share <= self.thresh {
+                return working_share;
+            }
+        }
+        // otherwise we failed to correct - discharge still has some hope
+        match self.strategy {
+            TheoStrategy::Trivial => panic!("we shouldn't be here"),
+            TheoStrategy::Avg => (), // this can't do anything better
+            TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare => (), // these aren't supported for batch discharge source logic yet - probably never will be
+            TheoStrategy::Dis => {
+                let mut new_working_share = center_share.clone(); // get a new working share
+                self.transfers.clear();
+
+                // go through the candidates again
+                for (share, neighbor) in candidates.iter() {
+                    let simultaneous_adj_problems = self.max_simultaneous_problem_neighbors(*neighbor);
+                    assert!(simultaneous_adj_problems > 0); // sanity check
+
+                    let dis = (self.thresh - share) / Share::from_integer(simultaneous_adj_problems.into());
+                    assert_gt!(dis, Share::zero()); // sanity check
+                    if self.cert.is_some() {
+                        self.transfers.push(certificate::Transfer { neighbor: *neighbor, bound: share.clone(), problems: simultaneous_adj_problems, simultaneous: true, amount: dis.clone() });
+                    }
+
+                    new_working_share -= dis;
+                    if &new_working_share <= self.thresh {
+                        return new_working_share;
+                    }
+                }
+
+                // at the end we should at least do as well as the non-simultaneous expansion
+                assert_le!(new_working_share, working_share);
+                working_share = new_working_share; // replace with the better value
+            }
+        }
+        // but if that also failed, just return the best we could do
+        working_share
+    }
+    // do_averaging's answer when p has no valid expansion: the configuration can't happen, so it's never a problem
+    fn no_configurations(&mut self, p: P) -> Share {
+        if self.cert.is_some() {
+            self.impossible = Some(p);
+            self.transfers.clear();
+        }
+        Share::zero()
+    }
+    // adds the configuration at the current leaf to the certificate (if we're writing one)
+    fn write_certificate(&mut self, share: &Share, avg_share: &Share) -> Result<(), Error> {
+        let cert = match self.cert.as_mut() {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let detectors = &self.detectors;
+        let mut config: Vec<_> = self.open_interior.iter().copied().filter(|p| detectors.contains(p)).collect();
+        config.sort_unstable();
+        let outcome = match self.impossible {
+            Some(p) => certificate::Outcome::Impossible(p),
+            None => certificate::Outcome::Final(avg_share.clone()),
+        };
+        cert.write(&certificate::config(&config, share, &self.transfers, &outcome))
+    }
+    // saves a checkpoint if one is due - only called at leaves, where every decision has been made
+    fn save_checkpoint(&mut self) -> Result<(), Error> {
+        let checkpoint = match self.checkpoint.as_mut() {
+            Some(c) if c.saver.is_due() => c,
+            _ => return Ok(()),
+        };
+        let detectors = &self.detectors;
+        let path: Vec<bool> = self.open_interior.iter().map(|p| detectors.contains(p)).collect();
+        let w = write_theo_checkpoint(&checkpoint.search, self.class, &path, self.problems);
+        checkpoint.saver.save(&w).map_err(|e| Error::io("write checkpoint", checkpoint.saver.path(), e))
+    }
+    #[must_use]
+    fn calc_recursive<I>(&mut self, mut pos: I) -> SearchCommand
+    where I: Iterator<Item = P> + Clone
+    {
+        match pos.next() {
+            // if we have no positions remaining, check for first order validity
+            None => {
+                // resuming redoes this leaf, which is fine since problems is a set
+                if let Err(e) = self.save_checkpoint() {
+                    self.failure = Some(e);
+                    return SearchCommand::Halt;
+                }
+
+                // fill in the exterior
+                for p in self.exterior.iter() {
+                    self.detectors.insert(*p);
+                }
+                // if not valid over the ball2 iter field, ignore (invalid configuration)
+                if !self.is_valid_over(self.closed_interior.iter().copied()) {
+                    return SearchCommand::Continue;
+                }
+
+                // compute share of center
+                let share = self.calc_share(self.center);
+                
+                // compute average share - if share is over thresh, attempt to perform averaging if enabled, otherwise just use same value
+                self.transfers.clear();
+                self.impossible = None;
+                let avg_share = {
+                    if &share > self.thresh && self.strategy != TheoStrategy::Trivial {
+                        let avg = self.do_averaging(&share);
+                        assert_ge!(avg, Share::zero()); // should be valid
+                        assert_le!(avg, share); // should never be worse than we started with
+                        avg
+                    }
+                    else {
+                        share.clone() 
+                    }
+                };
+
+                // if it was over thresh, display as problem case
+                if &avg_share > self.thresh {
+                    // gather up all the info into a problem description
+                    let geo = Geometry::for_printing(&self.closed_interior.iter().copied().collect(), self.detectors.iter());
+                    let structure = format!("{}", geo);
+                    let problem = TheoProblem {
+                        center: self.center,
+                        share,
+                        avg_share,
+                        structure,
+                    };
+                    // add to problems list (automatically sorted like we want)
+                    self.problems.insert(problem);
+
+                    match *self.pipe {
+                        // if printing enabled, show early warning if this was the first problem (nice since we delay problem printing till end for sorted order)
+                        Some(ref mut f) => {
+                            if self.problems.len() == 1 {
+                                writeln!(f, "encountered problems...\n").unwrap();
+                            }
+                        }
+                        // otherwise we're not interested in the problems, so stop looking for more of them
+                        None => return SearchCommand::Halt,
+                    }
+                }
+                else if let Err(e) = self.write_certificate(&share, &avg_share) {
+                    self.failure = Some(e);
+                    return SearchCommand::Halt;
+                }
+
+                SearchCommand::Continue
+            }
+            // otherwise recurse on both branches at this position
+            Some(p) => {
+                // when resuming, a saved exclude decision means the include branch was already searched
+                if self.resume.pop_front() != Some(false) {
+                    self.detectors.insert(p);
+                    if self.calc_recursive(pos.clone()) == SearchCommand::Halt {
+                        return SearchCommand::Halt;
+                    }
+                    self.detectors.remove(&p);
+                }
+                self.calc_recursive(pos)
+            }
+        }
+    }
+}
+// everything a search around one center needs that stays the same throughout
+struct CenterLands<P: Point> {
+    center: P,
+    bounds: (P, P),
+    closed_interior: Vec<P>, // everything up to radius 2
+    open_interior: Vec<P>,   // everything up to radius 2 except center (the points we make decisions on, in order)
+    exterior: Vec<P>,        // everything at exactly radius 3
+    expansion_map: PointMap<ExpansionLands<P>, P>,
+}
+impl<P: Point> CenterLands<P> {
+    fn new<Adj: AdjacentIterator<Point = P>>(adj: &Adj, center: P) -> Self {
+        // convenience function since PointSet cannot impl FromIterator
+        fn collect<P: Point, I: IntoIterator<Item = P>>(bounds: &(P, P), iter: I) -> PointSet<P> {
+            let mut s = PointSet::with_bounds(bounds.0, bounds.1);
+            s.extend(iter);
+            s
+        }
+
+        // set bounds for all the sets/maps we need - nothing gets more than 5 steps from the center
+        let r = P::from_fn(|_| 5 * adj.reach());
+        let bounds = (center.sub(r), center.add(r));
+        let (open, closed) = (adj.open(), adj.closed());
+
+        // generate closed interior - everything up to radius 2
+        let closed_interior = collect(&bounds, open.at(center).flat_map(|p| closed.at(p)));
+
+        #[cfg(debug)]
+        println!("closed interior:\n{}", Geometry::for_printing(&closed_interior, &Default::default()));
+
+        // generate open interior - everything up to radius 2 except the center
+        let mut open_interior = closed_interior.clone();
+        open_interior.remove(&center);
+
+        #[cfg(debug)]
+        println!("open interior:\n{}", Geometry::for_printing(&open_interior, &Default::default()));
+
+        // generate exterior - everything at exactly radius 3
+        let exterior = collect(&bounds, closed_interior.iter().flat_map(|p| open.at(p)).filter(|p| !closed_interior.contains(p)));
+
+        #[cfg(debug)]
+        println!("exterior:\n{}", Geometry::for_printing(&exterior, &Default::default()));
+
+        // generate boundary - everything at exactly radius 2
+        let boundary = collect(&bounds, exterior.iter().flat_map(|p| open.at(p)).filter(|p| closed_interior.contains(p)));
+
+        #[cfg(debug)]
+        println!("boundary:\n{}", Geometry::for_printing(&boundary, &Default::default()));
+
+        // populate expansion map with neighbors and boundary points
+        let mut expansion_map = PointMap::with_bounds(bounds.0, bounds.1);
+        for p in open.at(center).chain(boundary.iter()) {
+            let ball2 = collect(&bounds, open.at(p).flat_map(|q| closed.at(q)));
+            let ball3 = collect(&bounds, ball2.iter().flat_map(|q| closed.at(q)));
+
+            let field = collect(&bounds, ball2.iter().filter(|x| !closed_interior.contains(x)));
+            let total_exterior = collect(&bounds, exterior.iter().chain(ball3.iter()).filter(|x| !closed_interior.contains(x) && !field.contains(x)));
+
+            let lands = ExpansionLands {
+                field: field.iter().collect(),
+                total_exterior: total_exterior.iter().collect(),
+            };
+            #[cfg(debug)]
+            {
+                println!("{:?} field:          {:?}", p, lands.field);
+                println!("{:?} total_exterior: {:?}", p, lands.total_exterior);
+                println!();
+            }
+
+            expansion_map.insert(p, lands);
+        }
+
+        // convert sets into vectors for fastitude
+        CenterLands {
+            center,
+            bounds,
+            closed_interior: closed_interior.iter().collect(),
+            open_interior: open_interior.iter().collect(),
+            exterior: exterior.iter().collect(),
+            expansion_map,
+        }
+    }
+}
+// searches every configuration around the center that starts with the given decisions on the open interior (in order).
+// problems are added to problems, and a checkpoint (if any) is saved as the search goes - resume is where to pick up within it.
+// every configuration that isn't a problem goes in the certificate (if any).
+#[allow(clippy::too_many_arguments)]
+fn search_center<Codes, Adj, ShareAdj>(adj: &Adj, share_adj: &ShareAdj, lands: &CenterLands<Adj::Point>, class: usize, prefix: &[bool], strategy: TheoStrategy, thresh: &Share, pipe: &mut Option<&mut dyn io::Write>, problems: &mut BTreeSet<TheoProblem<Adj::Point>>, checkpoint: Option<&mut TheoCheckpoint<Adj::Point>>, resume: Vec<bool>, cert: Option<&mut TheoCertificate>) -> Result<SearchCommand, Error>
+where Codes: codesets::Set<Item = Adj::Point>, Adj: AdjacentIterator, ShareAdj: AdjacentIterator<Point = Adj::Point>
+{
+    // each search starts with no detectors except the center (and whatever the prefix says)
+    let mut detectors = PointSet::with_bounds(lands.bounds.0, lands.bounds.1);
+    detectors.insert(lands.center);
+    for (p, _) in lands.open_interior.iter().zip(prefix).filter(|(_, &x)| x) {
+        detectors.insert(*p);
+    }
+    let mut codes: Codes = Default::default();
+
+    // generate recursive search object (encodes the borrow contracts for borrowchecker)
+    let mut searcher = TheoSearcher {
+        adj,
+        open: adj.open(),
+        share_adj,
+
+        center: lands.center,
+        class,
+        closed_interior: &lands.closed_interior,
+        open_interior: &lands.open_interior,
+        exterior: &lands.exterior,
+        detectors: &mut detectors,
+
+        expansion_map: &lands.expansion_map,
+
+        codes: &mut codes,
+
+        thresh,
+        pipe,
+        problems,
+        strategy,
+
+        checkpoint,
+        resume: resume.into(),
+        failure: None,
+
+        cert,
+        transfers: vec![],
+        impossible: None,
+    };
+
+    // perform center folding
+    let res = searcher.calc_recursive(lands.open_interior[prefix.len()..].iter().copied());
+    match searcher.failure {
+        Some(e) => Err(e),
+        None => Ok(res),
+    }
+}
+pub fn calc_lower_bound<Codes, Adj, ShareAdj>(adj: &Adj, share_adj: &ShareAdj, strategy: TheoStrategy, thresh: Share, mut pipe: Option<&mut dyn io::Write>, mut checkpoint: Option<&mut TheoCheckpoint<Adj::Point>>, mut cert: Option<&mut TheoCertificate>) -> Result<BTreeSet<TheoProblem<Adj::Point>>, Error>
+where Codes: codesets::Set<Item = Adj::Point> + 'static, Adj: AdjacentIterator, ShareAdj: AdjacentIterator<Point = Adj::Point>
+{
+    assert_gt!(thresh, Share::zero()); // we require thresh in (0, 1]
+    assert_le!(thresh, Share::one());
+
+    // pick up where the checkpoint left off (if any)
+    let (start_class, mut resume, mut problems) = match checkpoint {
+        Some(ref mut c) if c.class >= adj.classes().len() => {
+            let msg = format!("center class {} is out of range (graph has {})", c.class, adj.classes().len());
+            return Err(Error::Checkpoint { path: c.saver.path().to_owned(), source: checkpoint::CheckpointError::InvalidFormat(msg) });
+        }
+        Some(ref mut c) => (c.class, mem::take(&mut c.path), mem::take(&mut c.problems)),
+        None => (0, vec![], Default::default()),
+    };
+
+    let share_thresh = thresh.recip();
+
+    // fold recursive results from all provided center values
+    for (class, &center) in adj.classes().iter().enumerate().skip(start_class) {
+        let lands = CenterLands::new(adj, center);
+        let resume = mem::take(&mut resume); // only the first class we search is partly done
+        if let Some(c) = cert.as_mut() {
+            c.write(&certificate::class(class, center))?;
+        }
+        if search_center::<Codes, _, _>(adj, share_adj, &lands, class, &[], strategy, &share_thresh, &mut pipe, &mut problems, checkpoint.as_deref_mut(), resume, cert.as_deref_mut())? == SearchCommand::Halt {
+            break;
+        }
+    }
+
+    // no problems means we've proven thresh works
+    Ok(problems)
+}
+
+// the number of open interior points decided up front for each work unit of the parallel search (so 2^this units per center class)
+const SPLIT_DEPTH: usize = 6;
+
+// like calc_lower_bound, but splits the search into work units (a center class and the decisions on its first few points) across threads.
+// there's no single place in the search to save, so this can't be checkpointed. the certificate comes out the same as the serial one.
+pub fn calc_lower_bound_parallel<Codes, Adj, ShareAdj>(adj: &Adj, share_adj: &ShareAdj, strategy: TheoStrategy, thresh: Share, mut pipe: Option<&mut dyn io::Write>, mut cert: Option<&mut TheoCertificate>, threads: usize) -> Result<BTreeSet<TheoProblem<Adj::Point>>, Error>
+where Codes: codesets::Set<Item = Adj::Point> + 'static, Adj: AdjacentIterator, ShareAdj: AdjacentIterator<Point = Adj::Point>
+{
+    assert_gt!(thresh, Share::zero()); // we require thresh in (0, 1]
+    assert_le!(thresh, Share::one());
+
+    let share_thresh = thresh.recip();
+    let lands: Vec<CenterLands<Adj::Point>> = adj.classes().iter().map(|&center| CenterLands::new(adj, center)).collect();
+    let units: Vec<(usize, Vec<bool>)> = lands.iter().enumerate().flat_map(|(class, lands)| {
+        let depth = SPLIT_DEPTH.min(lands.open_interior.len());
+        (0..1usize << depth).map(move |bits| (class, (0..depth).map(|i| bits & (1 << (depth - 1 - i)) == 0).collect())) // include before exclude, like the recursion
+    }).collect();
+
+    // without a pipe we only care whether there are any problems at all, so everyone stops at the first one
+    let collect_all = pipe.is_some();
+    let cert_path = cert.as_ref().map(|c| c.path.clone());
+    let next = AtomicUsize::new(0);
+    let halt = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    let mut problems = BTreeSet::new();
+    let mut failure = None;
+    let mut pending = BTreeMap::new(); // certificate parts that finished before the units ahead of them
+    let mut flushed = 0; // the number of units whose certificate parts have been written
+    thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            let sender = sender.clone();
+            let (next, halt, lands, units, share_thresh, cert_path) = (&next, &halt, &lands, &units, &share_thresh, &cert_path);
+            s.spawn(move || {
+                let mut sink = io::sink();
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= units.len() || halt.load(Ordering::Relaxed) { break; }
+
+                    let (class, prefix) = &units[i];
+                    let mut unit_pipe: Option<&mut dyn io::Write> = if collect_all { Some(&mut sink) } else { None };
+                    let mut unit_problems = BTreeSet::new();
+                    let mut unit_cert = vec![];
+                    let res = {
+                        let mut cert = cert_path.as_ref().map(|path| TheoCertificate { path: path.clone(), out: &mut unit_cert });
+                        search_center::<Codes, _, _>(adj, share_adj, &lands[*class], *class, prefix, strategy, share_thresh, &mut unit_pipe, &mut unit_problems, None, vec![], cert.as_mut())
+                    };
+                    if sender.send((i, res.map(|_| (unit_problems, unit_cert)))).is_err() { break; }
+                }
+            });
+        }
+        drop(sender); // so the receiver stops once every worker is done
+
+        for (i, res) in receiver {
+            match res {
+                Ok((unit_problems, unit_cert)) => {
+                    // the workers can't share the pipe, so we give the early warning for them
+                    if problems.is_empty() && !unit_problems.is_empty() {
+                        match pipe {
+                            Some(ref mut f) => writeln!(f, "encountered problems...\n").unwrap(),
+                            None => halt.store(true, Ordering::Relaxed),
+                        }
+                    }
+                    problems.extend(unit_problems);
+
+                    // write out the certificate in unit order, which is the order the serial search goes in
+                    if let Some(c) = cert.as_mut() {
+                        pending.insert(i, unit_cert);
+                        while let Some(part) = pending.remove(&flushed) {
+                            let class = units[flushed].0;
+                            let mut res = Ok(());
+                            if flushed == 0 || units[flushed - 1].0 != class {
+                                res = c.write(&certificate::class(class, lands[class].center));
+                            }
+                            if let Err(e) = res.and_then(|_| c.out.write_all(&part).map_err(|e| Error::io("write certificate", &c.path, e))) {
+                                halt.store(true, Ordering::Relaxed);
+                                failure.get_or_insert(e);
+                                break;
+                            }
+                            flushed += 1;
+                        }
+                    }
+                }
+                Err(e) => {
+                    halt.store(true, Ordering::Relaxed);
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+    });
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(problems),
+    }
+}
+#[test]
+fn test_calc_lower_bound_parallel() {
+    use crate::adj;
+
+    let serial = |thresh: &str| calc_lower_bound::<codesets::LD<(isize, isize)>, _, _>(&adj::OpenHex::default(), &adj::ClosedHex::default(), TheoStrategy::Dis, thresh.parse().unwrap(), Some(&mut io::sink()), None, None).unwrap();
+    let parallel = |thresh: &str, pipe: bool| {
+        let mut sink = io::sink();
+        let pipe = if pipe { Some(&mut sink as &mut dyn io::Write) } else { None };
+        calc_lower_bound_parallel::<codesets::LD<(isize, isize)>, _, _>(&adj::OpenHex::default(), &adj::ClosedHex::default(), TheoStrategy::Dis, thresh.parse().unwrap(), pipe, None, 3).unwrap()
+    };
+    assert!(parallel("2/5", true) == serial("2/5"));
+    assert!(!parallel("2/5", false).is_empty());
+    assert!(parallel("1/3", true).is_empty() && parallel("1/3", false).is_empty());
+}
+
+// checks that a certificate can be written for a lower bound search on graph with strategy (call before writing its header)
+pub fn check_certificate(graph: &Graph, strategy: TheoStrategy) -> Result<(), Error> {
+    if let TheoStrategy::DisWeightExcess | TheoStrategy::DisWeightShare = strategy {
+        return Err(Error::Usage("--cert only works with the trivial, avg and dis strategies".to_owned()));
+    }
+    if let Graph::PERIODIC(_) = graph {
+        return Err(Error::Usage("--cert only works with the built-in graphs".to_owned())); // the certificate couldn't name it
+    }
+    Ok(())
+}
+
+// runs calc_lower_bound with the code set type and adjacency param denotes on graph (split across threads if there's more than one).
+// the parallel search can't be checkpointed, so passing a checkpoint with more than one thread is a usage error. certificates only work for the unweighted strategies on the built-in graphs.
+#[allow(clippy::too_many_arguments)]
+pub fn lower_bound(param: Parameter, graph: &Graph, strategy: TheoStrategy, thresh: Share, mut pipe: Option<&mut dyn io::Write>, mut checkpoint: Option<&mut TheoCheckpoint>, mut cert: Option<&mut TheoCertificate>, threads: usize) -> Result<BTreeSet<TheoProblem>, Error> {
+    if threads > 1 && checkpoint.is_some() {
+        return Err(Error::Usage("--checkpoint and --resume only work with --threads 1".to_owned()));
+    }
+    if cert.is_some() {
+        check_certificate(graph, strategy)?;
+    }
+
+    // the bitset codes are exact here since everything stays within 5 steps of the center, as long as a step is at most a king's move
+    #[cfg(feature = "bitset")]
+    use crate::bitcodes as codes;
+    #[cfg(feature = "bitset")]
+    if graph.reach() > 1 {
+        return Err(Error::Usage("the bitset build can only bound graphs whose neighbors are within a king's move".to_owned()));
+    }
+    #[cfg(not(feature = "bitset"))]
+    use crate::codesets as codes;
+
+    macro_rules! calc {
+        ($set:ident, $adj:expr, $shadj:expr) => {
+            if threads > 1 {
+                calc_lower_bound_parallel::<codes::$set<(isize, isize)>, _, _>(&$adj, &$shadj, strategy, thresh, pipe.as_mut().map(|f| &mut **f as &mut dyn io::Write), cert.as_deref_mut(), threads)
+            }
+            else {
+                calc_lower_bound::<codes::$set<(isize, isize)>, _, _>(&$adj, &$shadj, strategy, thresh, pipe.as_mut().map(|f| &mut **f as &mut dyn io::Write), checkpoint.as_deref_mut(), cert.as_deref_mut())
+            }
+        }
+    }
+    graph_family!(graph, |open, closed| param_family!(share param, calc, open, closed))
+}
+#[test]
+fn test_lower_bound_unsupported() {
+    assert!(matches!(lower_bound(Parameter::EDOM, &Graph::HEX, TheoStrategy::Dis, "1/2".parse().unwrap(), None, None, None, 1), Err(Error::Unsupported { .. })));
+}
+#[test]
+fn test_lower_bound_cert_usage() {
+    let mut out = vec![];
+    let mut cert = TheoCertificate { path: "test".into(), out: &mut out };
+    assert!(matches!(lower_bound(Parameter::LD, &Graph::HEX, TheoStrategy::DisWeightShare, "1/3".parse().unwrap(), None, None, Some(&mut cert), 1), Err(Error::Usage(_))));
+    let periodic = Graph::PERIODIC(std::sync::Arc::new(crate::periodic::PeriodicGraph::builtin(&Graph::HEX)));
+    assert!(matches!(lower_bound(Parameter::LD, &periodic, TheoStrategy::Dis, "1/3".parse().unwrap(), None, None, Some(&mut cert), 1), Err(Error::Usage(_))));
+    assert!(out.is_empty());
+}
+#[test]
+fn test_lower_bound_parallel_checkpoint() {
+    let mut checkpoint = TheoCheckpoint { search: "test".into(), saver: checkpoint::Saver::new("unused.txt", std::time::Duration::from_secs(3600)), class: 0, path: vec![], problems: Default::default() };
+    assert!(matches!(lower_bound(Parameter::LD, &Graph::HEX, TheoStrategy::Dis, "1/3".parse().unwrap(), None, Some(&mut checkpoint), None, 2), Err(Error::Usage(_))));
+}
+#[test]
+fn test_center_lands_3d() {
+    use crate::cubic::ClosedSC;
+
+    // the balls around a center on the cubic lattice are octahedra: 25 points out to radius 2, then 38 at radius 3
+    let lands = CenterLands::new(&ClosedSC::default(), (0, 0, 0));
+    assert_eq!(lands.closed_interior.len(), 25);
+    assert_eq!(lands.open_interior.len(), 24);
+    assert_eq!(lands.exterior.len(), 38);
+    assert!(lands.closed_interior.iter().all(|p| p.0.abs() + p.1.abs() + p.2.abs() <= 2));
+}
+
+// how auto_lower_bound picks the next threshold to try
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoTheoSearch {
+    Bisect, // split the search space in half
+    SternBrocot, // try the mediant of the ends, which visits fractions in order of denominator (the ends stay stern-brocot neighbors)
+}
+// when auto_lower_bound should stop searching (it runs until stopped if none of these are given)
+#[derive(Default)]
+pub struct AutoTheoLimits {
+    pub max_iterations: Option<usize>,
+    pub min_width: Option<Share>,
+    pub time_limit: Option<Duration>, // only checked between searches, so the last one can run over
+    pub max_denom: Option<BigInt>, // snap to the only fraction with at most this denominator once the search space is narrow enough
+}
+// which of the limits stopped auto_lower_bound
+#[derive(Debug, PartialEq)]
+pub enum AutoTheoStop {
+    Denom(BigInt), // no fraction with at most this denominator beats the bound
+    Snapped(BigInt), // the bound is the only fraction with at most this denominator that was left
+    Width(Share),
+    Iterations(usize),
+    Time(Duration),
+}
+impl fmt::Display for AutoTheoStop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutoTheoStop::Denom(d) => write!(f, "no fraction with denominator at most {} beats it", d),
+            AutoTheoStop::Snapped(d) => write!(f, "snapped to the only fraction with denominator at most {} left", d),
+            AutoTheoStop::Width(width) => write!(f, "search space narrower than {}", width),
+            AutoTheoStop::Iterations(n) => write!(f, "hit the limit of {} iterations", n),
+            AutoTheoStop::Time(t) => write!(f, "ran out of time after {:.2}s", t.as_secs_f64()),
+        }
+    }
+}
+// the best bound auto_lower_bound could prove, and how it got there
+pub struct AutoTheo {
+    pub bound: Share,
+    pub iterations: usize,
+    pub stop: AutoTheoStop,
+}
+// whether lower_bound proves thresh (without anything to report along the way)
+fn proves(param: Parameter, graph: &Graph, strategy: TheoStrategy, thresh: Share, threads: usize) -> Result<bool, Error> {
+    Ok(lower_bound(param, graph, strategy, thresh, None, None, None, threads)?.is_empty())
+}
+// searches for the best lower bound theo can prove until one of the limits is hit, returning the best bound it certified.
+// f is called with the search space and the fraction it predicts before each search.
+pub fn auto_lower_bound<F>(param: Parameter, graph: &Graph, strategy: TheoStrategy, search: AutoTheoSearch, limits: &AutoTheoLimits, threads: usize, mut f: F) -> Result<AutoTheo, Error>
+where F: FnMut(&Share, &Share, &Share)
+{
+    let two = BigInt::from(2);
+    let start = Instant::now();
+
+    // we know the value is in (0, 1], so start searching there
+    let mut low = Share::zero();
+    let mut high = Share::one();
+    let mut thresh = &high - &low;
+    let mut iterations = 0;
+    let (bound, stop) = loop {
+        // any two fractions with denominators at most d are at least 1/d^2 apart, so once the space is narrower than that
+        // there's at most one left to try (and rationalize finds it, since it's the smallest denominator in the space)
+        let mediant = Share::new(low.numer() + high.numer(), low.denom() + high.denom());
+        if let Some(d) = &limits.max_denom {
+            // every fraction between stern-brocot neighbors has a denominator at least that of their mediant, so low is already the best
+            if search == AutoTheoSearch::SternBrocot && mediant.denom() > d {
+                break (low, AutoTheoStop::Denom(d.clone()));
+            }
+            if &high - &low < Share::new(BigInt::one(), d * d) {
+                let snap = util::rationalize(&((&low + &high) / &two), &((&high - &low) / &two));
+                if snap.denom() <= d && snap > low && proves(param, graph, strategy, snap.clone(), threads)? {
+                    break (snap, AutoTheoStop::Snapped(d.clone()));
+                }
+                break (low, AutoTheoStop::Denom(d.clone()));
+            }
+        }
+        if let Some(width) = &limits.min_width {
+            if &high - &low <= *width {
+                break (low, AutoTheoStop::Width(width.clone()));
+            }
+        }
+        if limits.max_iterations.map(|n| iterations >= n).unwrap_or(false) {
+            break (low, AutoTheoStop::Iterations(iterations));
+        }
+        if limits.time_limit.map(|t| start.elapsed() >= t).unwrap_or(false) {
+            break (low, AutoTheoStop::Time(start.elapsed()));
+        }
+
+        let (mid, rat) = match search {
+            AutoTheoSearch::Bisect => {
+                thresh /= &two;
+                let mid = (&low + &high) / &two;
+                let rat = util::rationalize(&mid, &thresh);
+                (mid, rat)
+            }
+            AutoTheoSearch::SternBrocot => (mediant.clone(), mediant),
+        };
+        f(&low, &high, &rat);
+        if proves(param, graph, strategy, mid.clone(), threads)? {
+            low = mid;
+        }
+        else {
+            high = mid;
+        }
+        iterations += 1;
+    };
+    Ok(AutoTheo { bound, iterations, stop })
+}
+#[test]
+fn test_auto_theo_limits() {
+    let bound = |limits: &AutoTheoLimits| auto_lower_bound(Parameter::LD, &Graph::HEX, TheoStrategy::Dis, AutoTheoSearch::Bisect, limits, 1, |_, _, _| ()).unwrap();
+    let res = bound(&AutoTheoLimits { max_iterations: Some(3), ..Default::default() });
+    assert_eq!((res.bound, res.iterations, res.stop), (Share::new(1.into(), 4.into()), 3, AutoTheoStop::Iterations(3)));
+    let res = bound(&AutoTheoLimits { max_denom: Some(5.into()), ..Default::default() });
+    assert_eq!((res.bound, res.stop), (Share::new(1.into(), 3.into()), AutoTheoStop::Snapped(5.into())));
+    let res = bound(&AutoTheoLimits { min_width: Some(Share::new(1.into(), 2.into())), ..Default::default() });
+    assert_eq!(res.bound, Share::zero());
+}
+#[test]
+fn test_auto_theo_stern_brocot() {
+    let bound = |limits: &AutoTheoLimits| auto_lower_bound(Parameter::LD, &Graph::HEX, TheoStrategy::Dis, AutoTheoSearch::SternBrocot, limits, 1, |_, _, _| ()).unwrap();
+    // 1/2 fails, 1/3 works and 2/5 fails, after which the next candidate (3/8) has too big a denominator
+    let res = bound(&AutoTheoLimits { max_denom: Some(5.into()), ..Default::default() });
+    assert_eq!((res.bound, res.stop), (Share::new(1.into(), 3.into()), AutoTheoStop::Denom(5.into())));
+    let res = bound(&AutoTheoLimits { max_iterations: Some(2), ..Default::default() });
+    assert_eq!(res.bound, Share::new(1.into(), 3.into()));
+}
+
+#[cfg(test)]
+fn holds(param: &str, graph: &str, thresh: &str, threads: usize) -> bool {
+    let thresh = crate::params::parse_thresh_frac(thresh).unwrap();
+    proves(param.parse().unwrap(), &graph.parse().unwrap(), TheoStrategy::Dis, thresh, threads).unwrap()
+}
+#[test]
+fn test_theo_hex_works() {
+    assert!(holds("ld", "hex", "1/3", 1));
+    assert!(holds("det:ld", "hex", "3/5", 1));
+    assert!(holds("red:ic", "hex", "4/7", 1));
+    assert!(holds("det:ic", "hex", "12/17", 1));
+    assert!(holds("err:ic", "hex", "5/6", 1));
+    assert!(holds("old", "hex", "1/2", 1));
+}
+#[test]
+fn test_theo_tmb_works() {
+    assert!(holds("red:ic", "tmb", "4/9", 1));
+    assert!(holds("det:ic", "tmb", "3/5", 1));
+    assert!(holds("err:ic", "tmb", "12/19", 1));
+    assert!(holds("det:ld", "tmb", "3/5", 1));
+}
+#[test]
+fn test_theo_tmb_parallel() {
+    // splitting the search across threads has to reach the same verdict as the serial search, whichever way it goes
+    for (param, thresh) in [("red:ic", "4/9"), ("det:ic", "3/5"), ("err:ic", "12/19"), ("det:ld", "3/5"), ("red:ic", "2/5"), ("det:ld", "1/2")] {
+        let serial = holds(param, "tmb", thresh, 1);
+        assert_eq!(holds(param, "tmb", thresh, 4), serial, "{} at {}", param, thresh);
+    }
+}
+#[test]
+fn test_theo_tmb_not_works() {
+    assert!(!holds("red:ic", "tmb", "0.44444444444444444444444444444444444444444444444444444444444444444444444444444444444444444444445", 1));
+}
@@ -1,5 +1,5 @@
 use std::cmp;
-use num::{BigRational, BigInt, Zero};
+use num::{BigRational, BigInt, BigUint, Zero, One};
 
 pub fn modulus(a: isize, b: isize) -> usize {
     assert!(b > 0);
@@ -174,6 +174,24 @@ fn test_gcd() {
     assert_eq!(gcd(11, 3), 1);
 }
 
+// number of ways to choose k items from n
+pub fn binomial(n: usize, k: usize) -> BigUint {
+    if k > n { return BigUint::zero(); }
+    let k = k.min(n - k);
+    let mut res = BigUint::one();
+    for i in 0..k {
+        res = res * (n - i) / (i + 1); // always exact, since res holds binomial(n, i) at each step
+    }
+    res
+}
+#[test]
+fn test_binomial() {
+    assert_eq!(binomial(5, 0), BigUint::one());
+    assert_eq!(binomial(5, 2), BigUint::from(10u32));
+    assert_eq!(binomial(5, 6), BigUint::zero());
+    assert_eq!(binomial(60, 30), BigUint::from(118264581564861424u64));
+}
+
 // Vec::is_sorted is nightly-only, so use this workaround
 #[cfg(test)]
 pub fn is_sorted<T: PartialOrd>(arr: &[T]) -> bool {
@@ -192,6 +210,20 @@ fn test_is_sorted() {
     assert!(!is_sorted(&[6, 3, 4]));
 }
 
+// like str::split_whitespace, but also gives the column (in chars, starting at 1) each token starts at
+pub fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |tok| {
+        let offset = tok.as_ptr() as usize - line.as_ptr() as usize;
+        (line[..offset].chars().count() + 1, tok)
+    })
+}
+#[test]
+fn test_tokens() {
+    assert_eq!(tokens("  a bc\td").collect::<Vec<_>>(), vec![(3, "a"), (5, "bc"), (8, "d")]);
+    assert_eq!(tokens("é x").collect::<Vec<_>>(), vec![(1, "é"), (3, "x")]);
+    assert_eq!(tokens("   ").count(), 0);
+}
+
 pub fn rationalize(val: &BigRational, thresh: &BigRational) -> BigRational {
     let mut denom = BigInt::zero();
     loop {